@@ -273,7 +273,8 @@ if (os.platform() === 'win32' || os.platform() === 'darwin') {
     console.time('activeWindow')
     const data = activeWindow()
     console.timeEnd('activeWindow')
-    t.not(data.url.startsWith('http'), false)
+    t.not(data.url, null)
+    t.true(data.url?.startsWith('http'))
     return t.pass()
   })
 
@@ -281,7 +282,8 @@ if (os.platform() === 'win32' || os.platform() === 'darwin') {
     console.time('url getter - activeWindowAsync')
     const data = await activeWindowAsync()
     console.timeEnd('url getter - activeWindowAsync')
-    t.not(data.url.startsWith('http'), false)
+    t.not(data.url, null)
+    t.true(data.url?.startsWith('http'))
     return t.pass()
   })
 
@@ -294,7 +296,7 @@ if (os.platform() === 'win32' || os.platform() === 'darwin') {
       (window_info) =>
         Browsers.findIndex((browser) => {
           t.log(window_info.info.execName)
-          return browser === window_info.info.execName && window_info.url.startsWith('http')
+          return browser === window_info.info.execName && window_info.url?.startsWith('http')
         }) !== -1,
     )
     t.not(filtred.length, 0)
@@ -1,5 +1,47 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/** * Turn focus changes into `ActivitySpan`.
+ * When the idle time given with `setIdleTime` reaches `idleThreshold`, the current span ends at the last input
+ * and no span is recorded until the user comes back. The same goes while the session is locked or inactive.
+ */
+export declare class ActivityTracker {
+  /** * `idleThreshold` in milliseconds, 5 minutes by default
+   */
+  constructor(idleThreshold?: number | undefined | null)
+  /** * Record that `window` has the focus, at `at` milliseconds since the UNIX epoch or now
+   */
+  focus(window: WindowInfo, url?: string | undefined | null, at?: number | undefined | null): void
+  /** * Extend the current span and check if the user is idle
+   */
+  tick(at?: number | undefined | null): void
+  /** * Read the session state, the active window and its url, then record them with `sessionChanged` and `focus`
+   */
+  poll(): void
+  /** * End the current span, the next `focus` starts a new one
+   */
+  stop(at?: number | undefined | null): void
+  /** * Milliseconds since the last input of the user, read on the next `focus` or `tick`
+   */
+  setIdleTime(idleTime: number): void
+  /** * Record a new state of the session, like the ones sent by `subscribeSessionState`.
+   * The current span ends when the session is locked or inactive and resumes once it is back.
+   */
+  sessionChanged(state: SessionState, at?: number | undefined | null): void
+  get idle(): boolean
+  /** * Session is locked or inactive
+   */
+  get away(): boolean
+  /** * Span still being recorded
+   */
+  get current(): ActivitySpan | null
+  /** * Finished spans
+   */
+  spans(): Array<ActivitySpan>
+  /** * Return and forget finished spans
+   */
+  takeSpans(): Array<ActivitySpan>
+}
+
 /** * Struct to store all informations of the window
  */
 export declare class WindowInfo {
@@ -16,9 +58,12 @@ export declare class WindowInfo {
   /** * Promise funciton who help to recover icon of application and will return `IconInfo`.
    */
   getIconAsync(): Promise<IconInfo>
-  /** * Getter to recover browser url
+  /** * Promise funciton who help to recover icon of application as raw PNG bytes and will return `IconBuffer`.
+   */
+  getIconBuffer(): Promise<IconBuffer>
+  /** * Getter to recover browser url, `null` when the window is not a browser or the url can't be read
    */
-  get url(): string
+  get url(): string | null
   /** * Return an Object to make it easy to use data instead of the class
    */
   toObject(): WindowInfoObject
@@ -84,6 +129,81 @@ export declare function activeWindow(): WindowInfo
  */
 export declare function activeWindowAsync(): Promise<WindowInfo>
 
+/** * Time spent on one window with the same title and url.
+ * `start` and `end` are milliseconds since the UNIX epoch.
+ */
+export interface ActivitySpan {
+  windowId: number
+  app: string
+  title: string
+  url: string | null
+  start: number
+  end: number
+}
+
+/** * Time spent on one app, title or url during an interval, in milliseconds
+ */
+export interface ActivitySummary {
+  key: string
+  start: number
+  end: number
+  duration: number
+}
+
+/** * Backend used to recover windows
+ */
+export declare enum Backend {
+  Win32 = 'Win32',
+  MacOS = 'MacOS',
+  X11 = 'X11',
+  GnomeShellEval = 'GnomeShellEval',
+  GnomeShellExtension = 'GnomeShellExtension',
+  Fake = 'Fake',
+  Unsupported = 'Unsupported'
+}
+
+/** * Return the features available with the backend detected for the current session.
+ * Useful to hide features that can't work, like browser urls without accessibility bus or windows before the gnome extension is enabled.
+ */
+export declare function capabilities(): Capabilities
+
+/** * Features available with the detected backend
+ */
+export interface Capabilities {
+  backend: Backend
+  activeWindow: boolean
+  openWindows: boolean
+  icons: boolean
+  browserUrl: boolean
+  events: boolean
+  windowControl: boolean
+  workspaces: boolean
+  extensionInstall: boolean
+}
+
+/** * Remove all icons kept in the icon cache.
+ * Icons are cached by application path and size to avoid recovering them on each call.
+ */
+export declare function clearIconCache(): void
+
+/** * Report the session type, desktop, Gnome Shell version and the state of the gnome extension with suggestions to fix them.
+ * Useful to understand why windows can not be recovered on Linux.
+ */
+export declare function diagnose(): DiagnosticReport
+
+/** * Report of the environment used to recover windows, with suggestions to fix a broken setup
+ */
+export interface DiagnosticReport {
+  os: string
+  sessionType: SessionType
+  desktop: string | null
+  gnomeShellVersion: string | null
+  extensionStatus: ExtensionStatus | null
+  dbusResponds: boolean | null
+  dbusError: string | null
+  suggestions: Array<string>
+}
+
 /** * Disable Gnome extensions required for Linux using Gnome > 41.
  * This function will disable extension needed to correctly detect working windows with Wayland desktop environment.
  */
@@ -94,6 +214,51 @@ export declare function disableExtension(): boolean
  */
 export declare function enableExtension(): boolean
 
+/** * State of the "x-win@miniben90.org" Gnome extension
+ */
+export declare enum ExtensionState {
+  NotInstalled = 'NotInstalled',
+  Outdated = 'Outdated',
+  InstalledNeedsRestart = 'InstalledNeedsRestart',
+  Disabled = 'Disabled',
+  Enabled = 'Enabled',
+  Error = 'Error'
+}
+
+/** * Struct to store the state of the Gnome extension with the error reported by Gnome Shell
+ */
+export interface ExtensionStatus {
+  state: ExtensionState
+  error: string | null
+}
+
+/** * Return the state of the gnome extension for Linux using Gnome > 41.
+ * Tell if the extension is not installed, written by another x-win version, waiting a session restart, enabled or in error. Working only with Wayland windows manager.
+ */
+export declare function extensionStatus(): ExtensionStatus
+
+/** * Return the time in milliseconds since the user last used the keyboard or the mouse.
+ * Uses MIT-SCREEN-SAVER on X11, the Mutter idle monitor on GNOME and `ext-idle-notify-v1` on other Wayland compositors.
+ */
+export declare function getIdleTime(): number
+
+/** * Field used to group spans in `summarizeActivity`
+ */
+export declare enum GroupBy {
+  App = 'App',
+  Title = 'Title',
+  Url = 'Url'
+}
+
+/** * Struct to store raw bytes of the icon with its mime type
+ */
+export interface IconBuffer {
+  data: Buffer
+  mime: string
+  height: number
+  width: number
+}
+
 /** * Struct to store process information of the window
  */
 export interface IconInfo {
@@ -102,12 +267,48 @@ export interface IconInfo {
   width: number
 }
 
+/** * Change of the idle state sent by `subscribeIdle`.
+ * `idleTime` is the time in milliseconds since the last input when the user became idle, `0` when they are active again.
+ */
+export interface IdleEvent {
+  idle: boolean
+  idleTime: number
+}
+
 /** * Install "@mininben90/x-win" Gnome extension required for Linux using Gnome > 41.
  * This function will write extension files needed to correctly detect working windows with Wayland desktop environment.
  * **Restart session will be require to install the gnome extension.**
  */
 export declare function installExtension(): boolean
 
+/** * Install "@mininben90/x-win" Gnome extension for the current user, for all users or into a custom prefix.
+ * With `enableByDefault` the extension is added to `org.gnome.shell enabled-extensions` to be enabled on next session.
+ * **Restart session will be require to install the gnome extension.**
+ */
+export declare function installExtensionWith(options: InstallOptions): boolean
+
+/** * Struct to store options used to install the Gnome extension
+ */
+export interface InstallOptions {
+  scope?: InstallScope
+  prefix?: string
+  enableByDefault?: boolean
+}
+
+/** * Where the Gnome extension is installed
+ */
+export declare enum InstallScope {
+  /** * `$HOME/.local/share/gnome-shell/extensions`, for the current user only
+   */
+  User = 'User',
+  /** * `/usr/share/gnome-shell/extensions`, for all users (root permissions are needed)
+   */
+  System = 'System',
+  /** * `<prefix>/share/gnome-shell/extensions`, `prefix` option is required
+   */
+  Custom = 'Custom'
+}
+
 /** * Return true of false if gnome extension is enabled for Linux using Gnome > 41.
  * This function will return true or false if the extension is set to enabled on extension info. Working only with Wayland windows manager.
  */
@@ -197,6 +398,114 @@ export interface ProcessInfo {
   execName: string
 }
 
+/** * What a `RedactionRule` does to the windows it applies to
+ */
+export declare enum RedactionAction {
+  /** * Remove the window from open windows, the active window becomes an empty window with the id `0`
+   */
+  DropWindow = 'DropWindow',
+  /** * Replace the title with its hash, equal titles keep equal hashes
+   */
+  HashTitle = 'HashTitle',
+  /** * Keep the first `length` characters of the title
+   */
+  TruncateTitle = 'TruncateTitle',
+  /** * Remove the query and the fragment of the url
+   */
+  StripUrlQuery = 'StripUrlQuery',
+  /** * Keep the scheme and the host of the url
+   */
+  StripUrlPath = 'StripUrlPath',
+  /** * Replace the title with "Private browsing" and hide the url
+   */
+  MarkPrivate = 'MarkPrivate'
+}
+
+/** * Rule applied to windows and urls before they are returned, set with `setRedactionRules`
+ */
+export interface RedactionRule {
+  matches: RuleMatch
+  pattern?: string
+  action: RedactionAction
+  length?: number
+}
+
+/** * Return the rules set with `setRedactionRules`
+ */
+export declare function redactionRules(): Array<RedactionRule>
+
+/** * Windows a `RedactionRule` applies to
+ */
+export declare enum RuleMatch {
+  Any = 'Any',
+  /** * Application name or executable name given in `pattern`, case insensitive
+   */
+  App = 'App',
+  /** * Regular expression given in `pattern`, searched in the title
+   */
+  Title = 'Title',
+  /** * Private browsing, incognito or InPrivate window guessed from the title
+   */
+  PrivateBrowsing = 'PrivateBrowsing'
+}
+
+/** * What x-win can reach from the sandbox it runs in
+ */
+export interface SandboxInfo {
+  flatpakId: string | null
+  hostCommands: boolean
+  processInfo: boolean
+  processInfoError: string | null
+  extensionInstall: boolean
+  gnomeShellBus: boolean
+}
+
+/** * Tell if x-win runs inside a Flatpak sandbox and what it can reach from there.
+ * Outside of a sandbox and on Windows and macOS nothing is restricted.
+ */
+export declare function sandboxInfo(): SandboxInfo
+
+/** * Lock and activity state of the session.
+ * `active` is false when another user or a TTY is in the foreground of the seat.
+ */
+export interface SessionState {
+  locked: boolean
+  active: boolean
+  sessionType: SessionType
+}
+
+/** * Tell if the session is locked or inactive, to stop attributing time to the focused window meanwhile.
+ * Read from logind and the screen saver over D-Bus, Linux only.
+ */
+export declare function sessionState(): SessionState
+
+/** * Kind of graphical session used to recover windows
+ */
+export declare enum SessionType {
+  X11 = 'X11',
+  Wayland = 'Wayland',
+  Native = 'Native',
+  Unknown = 'Unknown'
+}
+
+/** * Redact windows and urls with `rules` before they are returned by x-win, including `subscribeActiveWindow` callbacks and `toObject()`.
+ * Every rule matching a window is applied in order, an empty list removes the rules.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { RedactionAction, RuleMatch, setRedactionRules } from '@miniben90/x-win';
+ *
+ * setRedactionRules([
+ *   { matches: RuleMatch.App, pattern: 'keepassxc', action: RedactionAction.DropWindow },
+ *   { matches: RuleMatch.Title, pattern: '(?i)inbox', action: RedactionAction.HashTitle },
+ *   { matches: RuleMatch.Any, action: RedactionAction.StripUrlQuery },
+ *   { matches: RuleMatch.PrivateBrowsing, action: RedactionAction.MarkPrivate },
+ * ]);
+ * ```
+ */
+export declare function setRedactionRules(rules: Array<RedactionRule>): void
+
 /** * Subscribe an observer thread to monitor changes in the active window.
  * @param {function} callback - Callback function that returns the active window when it changes
  * @param {number} [interval=100] - Interval between checks for changes in the active window (default: 100ms)
@@ -248,12 +557,75 @@ export interface ProcessInfo {
  */
 export declare function subscribeActiveWindow(callback: (error: Error | null, info: WindowInfo | undefined) => void, interval?: number): number
 
+/** * Subscribe an observer thread called when the user becomes idle and when they are active again.
+ * Returns a unique ID to unsubscribe it with `unsubscribeIdle`.
+ *
+ * @param {function} callback - Callback function that returns the new idle state
+ * @param {number} [threshold=300000] - Time without input before the user is idle (default: 5min)
+ * @param {number} [interval=1000] - Interval between checks of the idle time (default: 1000ms)
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { subscribeIdle, unsubscribeIdle } from '@miniben90/x-win';
+ *
+ * const id = subscribeIdle((err, event) => {
+ *   console.log(event.idle ? `Idle since ${event.idleTime}ms` : 'Active');
+ * }, 60 * 1000);
+ *
+ * unsubscribeIdle(id);
+ * ```
+ */
+export declare function subscribeIdle(callback: (error: Error | null, event: IdleEvent | undefined) => void, threshold?: number, interval?: number): number
+
+/** * Subscribe an observer thread called with the current session state, then each time the session is locked, unlocked, activated or deactivated.
+ * Returns a unique ID to unsubscribe it with `unsubscribeSessionState`. Linux only.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { ActivityTracker, subscribeSessionState, unsubscribeSessionState } from '@miniben90/x-win';
+ *
+ * const tracker = new ActivityTracker();
+ * const id = subscribeSessionState((err, state) => {
+ *   if (state) tracker.sessionChanged(state);
+ * });
+ *
+ * unsubscribeSessionState(id);
+ * ```
+ */
+export declare function subscribeSessionState(callback: (error: Error | null, state: SessionState | undefined) => void): number
+
+/** * Sum the time of `spans` by app, title or url for each `interval` in milliseconds.
+ * Spans are split at the interval boundaries, without `interval` a single interval covers all spans.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { ActivityTracker, GroupBy, summarizeActivity } from '@miniben90/x-win';
+ *
+ * const tracker = new ActivityTracker();
+ * setInterval(() => tracker.poll(), 1000);
+ *
+ * // Time spent per application for each hour
+ * const summaries = summarizeActivity(tracker.spans(), GroupBy.App, 60 * 60 * 1000);
+ * ```
+ */
+export declare function summarizeActivity(spans: Array<ActivitySpan>, groupBy: GroupBy, interval?: number | undefined | null): Array<ActivitySummary>
+
 /** * Uninstall "@mininben90/x-win" Gnome extension.
  * This function will disable and remove extension files.
  * **Restart session will be require to remove the gnome extension.**
  */
 export declare function uninstallExtension(): boolean
 
+/** * Uninstall "@mininben90/x-win" Gnome extension installed for the current user, for all users or into a custom prefix.
+ * The schema override written with `enableByDefault` is removed from system and custom prefixes,
+ * for the current user `enableByDefault` removes the extension from `org.gnome.shell enabled-extensions`.
+ * **Restart session will be require to remove the gnome extension.**
+ */
+export declare function uninstallExtensionWith(options: InstallOptions): boolean
+
 /** * Terminate and unsubscribe a specific observer using their ID.
  *
  * # Example
@@ -342,6 +714,14 @@ export declare function unsubscribeActiveWindow(threadId: number): void
  */
 export declare function unsubscribeAllActiveWindow(): void
 
+/** * Terminate and unsubscribe an observer of `subscribeIdle` using its ID.
+ */
+export declare function unsubscribeIdle(threadId: number): void
+
+/** * Terminate and unsubscribe an observer of `subscribeSessionState` using its ID.
+ */
+export declare function unsubscribeSessionState(threadId: number): void
+
 /** * Struct to store usage data of the window
  */
 export interface UsageInfo {
@@ -358,7 +738,7 @@ export interface WindowInfoObject {
   info: ProcessInfo
   usage: UsageInfo
   icon: IconInfo
-  url: string
+  url: string | null
 }
 
 /** * Struct to store position and size of the window
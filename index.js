@@ -576,17 +576,41 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.ActivityTracker = nativeBinding.ActivityTracker
 module.exports.WindowInfo = nativeBinding.WindowInfo
 module.exports.activeWindow = nativeBinding.activeWindow
 module.exports.activeWindowAsync = nativeBinding.activeWindowAsync
+module.exports.Backend = nativeBinding.Backend
+module.exports.capabilities = nativeBinding.capabilities
+module.exports.clearIconCache = nativeBinding.clearIconCache
+module.exports.diagnose = nativeBinding.diagnose
 module.exports.disableExtension = nativeBinding.disableExtension
 module.exports.enableExtension = nativeBinding.enableExtension
+module.exports.ExtensionState = nativeBinding.ExtensionState
+module.exports.extensionStatus = nativeBinding.extensionStatus
+module.exports.getIdleTime = nativeBinding.getIdleTime
+module.exports.GroupBy = nativeBinding.GroupBy
 module.exports.installExtension = nativeBinding.installExtension
+module.exports.installExtensionWith = nativeBinding.installExtensionWith
+module.exports.InstallScope = nativeBinding.InstallScope
 module.exports.isEnabledExtension = nativeBinding.isEnabledExtension
 module.exports.isInstalledExtension = nativeBinding.isInstalledExtension
 module.exports.openWindows = nativeBinding.openWindows
 module.exports.openWindowsAsync = nativeBinding.openWindowsAsync
+module.exports.RedactionAction = nativeBinding.RedactionAction
+module.exports.redactionRules = nativeBinding.redactionRules
+module.exports.RuleMatch = nativeBinding.RuleMatch
+module.exports.sandboxInfo = nativeBinding.sandboxInfo
+module.exports.sessionState = nativeBinding.sessionState
+module.exports.SessionType = nativeBinding.SessionType
+module.exports.setRedactionRules = nativeBinding.setRedactionRules
 module.exports.subscribeActiveWindow = nativeBinding.subscribeActiveWindow
+module.exports.subscribeIdle = nativeBinding.subscribeIdle
+module.exports.subscribeSessionState = nativeBinding.subscribeSessionState
+module.exports.summarizeActivity = nativeBinding.summarizeActivity
 module.exports.uninstallExtension = nativeBinding.uninstallExtension
+module.exports.uninstallExtensionWith = nativeBinding.uninstallExtensionWith
 module.exports.unsubscribeActiveWindow = nativeBinding.unsubscribeActiveWindow
 module.exports.unsubscribeAllActiveWindow = nativeBinding.unsubscribeAllActiveWindow
+module.exports.unsubscribeIdle = nativeBinding.unsubscribeIdle
+module.exports.unsubscribeSessionState = nativeBinding.unsubscribeSessionState
@@ -0,0 +1,152 @@
+#![deny(unused_imports)]
+
+use std::time::Duration;
+
+use napi::Result;
+use x_win::activity::ManualIdle;
+
+use crate::error::xwin_error;
+
+use super::x_win_struct::{
+  activity_span::ActivitySpan, session_state::SessionState, window_info::WindowInfo,
+};
+
+/**
+ * Turn focus changes into `ActivitySpan`.
+ * When the idle time given with `setIdleTime` reaches `idleThreshold`, the current span ends at the last input
+ * and no span is recorded until the user comes back. The same goes while the session is locked or inactive.
+ */
+#[napi]
+pub struct ActivityTracker {
+  tracker: x_win::activity::ActivityTracker,
+  idle: ManualIdle,
+}
+
+#[napi]
+impl ActivityTracker {
+  /**
+   * `idleThreshold` in milliseconds, 5 minutes by default
+   */
+  #[napi(constructor)]
+  pub fn new(idle_threshold: Option<u32>) -> Self {
+    let idle = ManualIdle::new();
+    let idle_threshold = Duration::from_millis(idle_threshold.unwrap_or(5 * 60 * 1000) as u64);
+    Self {
+      tracker: x_win::activity::ActivityTracker::new()
+        .with_idle_source(idle.clone(), idle_threshold),
+      idle,
+    }
+  }
+
+  /**
+   * Record that `window` has the focus, at `at` milliseconds since the UNIX epoch or now
+   */
+  #[napi]
+  pub fn focus(&mut self, window: &WindowInfo, url: Option<String>, at: Option<i64>) -> Result<()> {
+    let window: x_win::WindowInfo = window.clone().into();
+    match at {
+      Some(at) => self.tracker.focus_at(&window, url, at.max(0) as u64),
+      None => self.tracker.focus(&window, url),
+    }
+    .map_err(xwin_error)
+  }
+
+  /**
+   * Extend the current span and check if the user is idle
+   */
+  #[napi]
+  pub fn tick(&mut self, at: Option<i64>) -> Result<()> {
+    match at {
+      Some(at) => self.tracker.tick_at(at.max(0) as u64),
+      None => self.tracker.tick(),
+    }
+    .map_err(xwin_error)
+  }
+
+  /**
+   * Read the session state, the active window and its url, then record them with `sessionChanged` and `focus`
+   */
+  #[napi]
+  pub fn poll(&mut self) -> Result<()> {
+    self.tracker.poll().map_err(xwin_error)
+  }
+
+  /**
+   * End the current span, the next `focus` starts a new one
+   */
+  #[napi]
+  pub fn stop(&mut self, at: Option<i64>) {
+    match at {
+      Some(at) => self.tracker.stop_at(at.max(0) as u64),
+      None => self.tracker.stop(),
+    }
+  }
+
+  /**
+   * Milliseconds since the last input of the user, read on the next `focus` or `tick`
+   */
+  #[napi]
+  pub fn set_idle_time(&self, idle_time: u32) {
+    self.idle.set(Duration::from_millis(idle_time as u64));
+  }
+
+  /**
+   * Record a new state of the session, like the ones sent by `subscribeSessionState`.
+   * The current span ends when the session is locked or inactive and resumes once it is back.
+   */
+  #[napi]
+  pub fn session_changed(&mut self, state: SessionState, at: Option<i64>) {
+    let state: x_win::SessionState = state.into();
+    match at {
+      Some(at) => self.tracker.session_changed_at(&state, at.max(0) as u64),
+      None => self.tracker.session_changed(&state),
+    }
+  }
+
+  #[napi(getter)]
+  pub fn idle(&self) -> bool {
+    self.tracker.is_idle()
+  }
+
+  /**
+   * Session is locked or inactive
+   */
+  #[napi(getter)]
+  pub fn away(&self) -> bool {
+    self.tracker.is_away()
+  }
+
+  /**
+   * Span still being recorded
+   */
+  #[napi(getter)]
+  pub fn current(&self) -> Option<ActivitySpan> {
+    self.tracker.current().map(|span| span.clone().into())
+  }
+
+  /**
+   * Finished spans
+   */
+  #[napi]
+  pub fn spans(&self) -> Vec<ActivitySpan> {
+    self
+      .tracker
+      .spans()
+      .iter()
+      .map(|span| span.clone().into())
+      .collect()
+  }
+
+  /**
+   * Return and forget finished spans
+   */
+  #[napi]
+  pub fn take_spans(&mut self) -> Vec<ActivitySpan> {
+    self
+      .tracker
+      .take_spans()
+      .into_iter()
+      .map(|span| span.into())
+      .collect()
+  }
+}
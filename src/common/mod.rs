@@ -1,4 +1,5 @@
 #![deny(unused_imports)]
 
+pub mod activity;
 pub mod thread;
 pub mod x_win_struct;
@@ -0,0 +1,82 @@
+/**
+ * Time spent on one window with the same title and url.
+ * `start` and `end` are milliseconds since the UNIX epoch.
+ */
+#[derive(Debug, Clone)]
+#[napi(object, use_nullable = true)]
+pub struct ActivitySpan {
+  pub window_id: u32,
+  pub app: String,
+  pub title: String,
+  pub url: Option<String>,
+  pub start: i64,
+  pub end: i64,
+}
+
+impl From<x_win::activity::ActivitySpan> for ActivitySpan {
+  fn from(value: x_win::activity::ActivitySpan) -> Self {
+    ActivitySpan {
+      window_id: value.window_id,
+      app: value.app,
+      title: value.title,
+      url: value.url,
+      start: value.start as i64,
+      end: value.end as i64,
+    }
+  }
+}
+
+impl From<ActivitySpan> for x_win::activity::ActivitySpan {
+  fn from(value: ActivitySpan) -> Self {
+    x_win::activity::ActivitySpan {
+      window_id: value.window_id,
+      app: value.app,
+      title: value.title,
+      url: value.url,
+      start: value.start.max(0) as u64,
+      end: value.end.max(0) as u64,
+    }
+  }
+}
+
+/**
+ * Field used to group spans in `summarizeActivity`
+ */
+#[napi(string_enum)]
+pub enum GroupBy {
+  App,
+  Title,
+  Url,
+}
+
+impl From<GroupBy> for x_win::activity::GroupBy {
+  fn from(value: GroupBy) -> Self {
+    match value {
+      GroupBy::App => x_win::activity::GroupBy::App,
+      GroupBy::Title => x_win::activity::GroupBy::Title,
+      GroupBy::Url => x_win::activity::GroupBy::Url,
+    }
+  }
+}
+
+/**
+ * Time spent on one app, title or url during an interval, in milliseconds
+ */
+#[napi(object)]
+pub struct ActivitySummary {
+  pub key: String,
+  pub start: i64,
+  pub end: i64,
+  pub duration: i64,
+}
+
+impl From<x_win::activity::ActivitySummary> for ActivitySummary {
+  fn from(value: x_win::activity::ActivitySummary) -> Self {
+    ActivitySummary {
+      key: value.key,
+      start: value.start as i64,
+      end: value.end as i64,
+      duration: value.duration as i64,
+    }
+  }
+}
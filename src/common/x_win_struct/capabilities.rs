@@ -0,0 +1,59 @@
+/**
+ * Backend used to recover windows
+ */
+#[napi(string_enum)]
+pub enum Backend {
+  Win32,
+  MacOS,
+  X11,
+  GnomeShellEval,
+  GnomeShellExtension,
+  Fake,
+  Unsupported,
+}
+
+impl From<x_win::Backend> for Backend {
+  fn from(value: x_win::Backend) -> Self {
+    match value {
+      x_win::Backend::Win32 => Backend::Win32,
+      x_win::Backend::MacOS => Backend::MacOS,
+      x_win::Backend::X11 => Backend::X11,
+      x_win::Backend::GnomeShellEval => Backend::GnomeShellEval,
+      x_win::Backend::GnomeShellExtension => Backend::GnomeShellExtension,
+      x_win::Backend::Fake => Backend::Fake,
+      x_win::Backend::Unsupported => Backend::Unsupported,
+    }
+  }
+}
+
+/**
+ * Features available with the detected backend
+ */
+#[napi(object)]
+pub struct Capabilities {
+  pub backend: Backend,
+  pub active_window: bool,
+  pub open_windows: bool,
+  pub icons: bool,
+  pub browser_url: bool,
+  pub events: bool,
+  pub window_control: bool,
+  pub workspaces: bool,
+  pub extension_install: bool,
+}
+
+impl From<x_win::Capabilities> for Capabilities {
+  fn from(value: x_win::Capabilities) -> Self {
+    Capabilities {
+      backend: value.backend.into(),
+      active_window: value.active_window,
+      open_windows: value.open_windows,
+      icons: value.icons,
+      browser_url: value.browser_url,
+      events: value.events,
+      window_control: value.window_control,
+      workspaces: value.workspaces,
+      extension_install: value.extension_install,
+    }
+  }
+}
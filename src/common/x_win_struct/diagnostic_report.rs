@@ -0,0 +1,64 @@
+use super::extension_status::ExtensionStatus;
+
+/**
+ * Kind of graphical session used to recover windows
+ */
+#[napi(string_enum)]
+pub enum SessionType {
+  X11,
+  Wayland,
+  Native,
+  Unknown,
+}
+
+impl From<x_win::SessionType> for SessionType {
+  fn from(value: x_win::SessionType) -> Self {
+    match value {
+      x_win::SessionType::X11 => SessionType::X11,
+      x_win::SessionType::Wayland => SessionType::Wayland,
+      x_win::SessionType::Native => SessionType::Native,
+      x_win::SessionType::Unknown => SessionType::Unknown,
+    }
+  }
+}
+
+impl From<SessionType> for x_win::SessionType {
+  fn from(value: SessionType) -> Self {
+    match value {
+      SessionType::X11 => x_win::SessionType::X11,
+      SessionType::Wayland => x_win::SessionType::Wayland,
+      SessionType::Native => x_win::SessionType::Native,
+      SessionType::Unknown => x_win::SessionType::Unknown,
+    }
+  }
+}
+
+/**
+ * Report of the environment used to recover windows, with suggestions to fix a broken setup
+ */
+#[napi(object, use_nullable = true)]
+pub struct DiagnosticReport {
+  pub os: String,
+  pub session_type: SessionType,
+  pub desktop: Option<String>,
+  pub gnome_shell_version: Option<String>,
+  pub extension_status: Option<ExtensionStatus>,
+  pub dbus_responds: Option<bool>,
+  pub dbus_error: Option<String>,
+  pub suggestions: Vec<String>,
+}
+
+impl From<x_win::DiagnosticReport> for DiagnosticReport {
+  fn from(value: x_win::DiagnosticReport) -> Self {
+    DiagnosticReport {
+      os: value.os,
+      session_type: value.session_type.into(),
+      desktop: value.desktop,
+      gnome_shell_version: value.gnome_shell_version,
+      extension_status: value.extension_status.map(|status| status.into()),
+      dbus_responds: value.dbus_responds,
+      dbus_error: value.dbus_error,
+      suggestions: value.suggestions,
+    }
+  }
+}
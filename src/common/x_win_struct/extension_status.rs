@@ -0,0 +1,37 @@
+/**
+ * State of the "x-win@miniben90.org" Gnome extension
+ */
+#[napi(string_enum)]
+pub enum ExtensionState {
+  NotInstalled,
+  Outdated,
+  InstalledNeedsRestart,
+  Disabled,
+  Enabled,
+  Error,
+}
+
+/**
+ * Struct to store the state of the Gnome extension with the error reported by Gnome Shell
+ */
+#[napi(object, use_nullable = true)]
+pub struct ExtensionStatus {
+  pub state: ExtensionState,
+  pub error: Option<String>,
+}
+
+impl From<x_win::ExtensionStatus> for ExtensionStatus {
+  fn from(value: x_win::ExtensionStatus) -> Self {
+    let (state, error) = match value {
+      x_win::ExtensionStatus::NotInstalled => (ExtensionState::NotInstalled, None),
+      x_win::ExtensionStatus::Outdated => (ExtensionState::Outdated, None),
+      x_win::ExtensionStatus::InstalledNeedsRestart => {
+        (ExtensionState::InstalledNeedsRestart, None)
+      }
+      x_win::ExtensionStatus::Disabled => (ExtensionState::Disabled, None),
+      x_win::ExtensionStatus::Enabled => (ExtensionState::Enabled, None),
+      x_win::ExtensionStatus::Error(error) => (ExtensionState::Error, Some(error)),
+    };
+    ExtensionStatus { state, error }
+  }
+}
@@ -0,0 +1,23 @@
+use napi::bindgen_prelude::Buffer;
+
+/**
+ * Struct to store raw bytes of the icon with its mime type
+ */
+#[napi(object)]
+pub struct IconBuffer {
+  pub data: Buffer,
+  pub mime: String,
+  pub height: u32,
+  pub width: u32,
+}
+
+impl From<x_win::IconData> for IconBuffer {
+  fn from(value: x_win::IconData) -> Self {
+    IconBuffer {
+      data: value.data.into(),
+      mime: value.mime,
+      height: value.height,
+      width: value.width,
+    }
+  }
+}
@@ -0,0 +1,25 @@
+/**
+ * Change of the idle state sent by `subscribeIdle`.
+ * `idleTime` is the time in milliseconds since the last input when the user became idle, `0` when they are active again.
+ */
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct IdleEvent {
+  pub idle: bool,
+  pub idle_time: i64,
+}
+
+impl From<x_win::activity::IdleEvent> for IdleEvent {
+  fn from(value: x_win::activity::IdleEvent) -> Self {
+    match value {
+      x_win::activity::IdleEvent::Idle { idle_time } => IdleEvent {
+        idle: true,
+        idle_time: idle_time.as_millis() as i64,
+      },
+      x_win::activity::IdleEvent::Active => IdleEvent {
+        idle: false,
+        idle_time: 0,
+      },
+    }
+  }
+}
@@ -0,0 +1,54 @@
+use napi::{Error, Status};
+
+/**
+ * Where the Gnome extension is installed
+ */
+#[napi(string_enum)]
+pub enum InstallScope {
+  /**
+   * `$HOME/.local/share/gnome-shell/extensions`, for the current user only
+   */
+  User,
+  /**
+   * `/usr/share/gnome-shell/extensions`, for all users (root permissions are needed)
+   */
+  System,
+  /**
+   * `<prefix>/share/gnome-shell/extensions`, `prefix` option is required
+   */
+  Custom,
+}
+
+/**
+ * Struct to store options used to install the Gnome extension
+ */
+#[napi(object)]
+pub struct InstallOptions {
+  pub scope: Option<InstallScope>,
+  pub prefix: Option<String>,
+  pub enable_by_default: Option<bool>,
+}
+
+impl TryFrom<InstallOptions> for x_win::InstallOptions {
+  type Error = Error;
+
+  fn try_from(value: InstallOptions) -> Result<Self, Self::Error> {
+    let scope = match value.scope.unwrap_or(InstallScope::User) {
+      InstallScope::User => x_win::InstallScope::User,
+      InstallScope::System => x_win::InstallScope::System,
+      InstallScope::Custom => match value.prefix {
+        Some(prefix) => x_win::InstallScope::Custom(prefix.into()),
+        None => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "`prefix` is required with `Custom` scope",
+          ))
+        }
+      },
+    };
+    Ok(x_win::InstallOptions::new(
+      scope,
+      value.enable_by_default.unwrap_or(false),
+    ))
+  }
+}
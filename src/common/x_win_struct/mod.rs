@@ -1,7 +1,17 @@
 #![deny(unused_imports)]
 
+pub mod activity_span;
+pub mod capabilities;
+pub mod diagnostic_report;
+pub mod extension_status;
+pub mod icon_buffer;
 pub mod icon_info;
+pub mod idle_event;
+pub mod install_options;
 pub mod process_info;
+pub mod redaction_rule;
+pub mod sandbox_info;
+pub mod session_state;
 pub mod usage_info;
 pub mod window_info;
 pub mod window_position;
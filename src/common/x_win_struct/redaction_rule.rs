@@ -0,0 +1,126 @@
+use napi::{Error, Status};
+
+/**
+ * Windows a `RedactionRule` applies to
+ */
+#[napi(string_enum)]
+pub enum RuleMatch {
+  Any,
+  /**
+   * Application name or executable name given in `pattern`, case insensitive
+   */
+  App,
+  /**
+   * Regular expression given in `pattern`, searched in the title
+   */
+  Title,
+  /**
+   * Private browsing, incognito or InPrivate window guessed from the title
+   */
+  PrivateBrowsing,
+}
+
+/**
+ * What a `RedactionRule` does to the windows it applies to
+ */
+#[napi(string_enum)]
+pub enum RedactionAction {
+  /**
+   * Remove the window from open windows, the active window becomes an empty window with the id `0`
+   */
+  DropWindow,
+  /**
+   * Replace the title with its hash, equal titles keep equal hashes
+   */
+  HashTitle,
+  /**
+   * Keep the first `length` characters of the title
+   */
+  TruncateTitle,
+  /**
+   * Remove the query and the fragment of the url
+   */
+  StripUrlQuery,
+  /**
+   * Keep the scheme and the host of the url
+   */
+  StripUrlPath,
+  /**
+   * Replace the title with "Private browsing" and hide the url
+   */
+  MarkPrivate,
+}
+
+/**
+ * Rule applied to windows and urls before they are returned, set with `setRedactionRules`
+ */
+#[napi(object)]
+pub struct RedactionRule {
+  pub matches: RuleMatch,
+  pub pattern: Option<String>,
+  pub action: RedactionAction,
+  pub length: Option<u32>,
+}
+
+impl TryFrom<RedactionRule> for x_win::RedactionRule {
+  type Error = Error;
+
+  fn try_from(value: RedactionRule) -> Result<Self, Self::Error> {
+    let matches = match (value.matches, value.pattern) {
+      (RuleMatch::Any, _) => x_win::RuleMatch::Any,
+      (RuleMatch::App, Some(pattern)) => x_win::RuleMatch::App(pattern),
+      (RuleMatch::Title, Some(pattern)) => x_win::RuleMatch::Title(pattern),
+      (RuleMatch::PrivateBrowsing, _) => x_win::RuleMatch::PrivateBrowsing,
+      (_, None) => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "`pattern` is required with `App` and `Title` matches",
+        ))
+      }
+    };
+    let action = match value.action {
+      RedactionAction::DropWindow => x_win::RedactionAction::DropWindow,
+      RedactionAction::HashTitle => x_win::RedactionAction::HashTitle,
+      RedactionAction::TruncateTitle => match value.length {
+        Some(length) => x_win::RedactionAction::TruncateTitle(length as usize),
+        None => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "`length` is required with `TruncateTitle` action",
+          ))
+        }
+      },
+      RedactionAction::StripUrlQuery => x_win::RedactionAction::StripUrlQuery,
+      RedactionAction::StripUrlPath => x_win::RedactionAction::StripUrlPath,
+      RedactionAction::MarkPrivate => x_win::RedactionAction::MarkPrivate,
+    };
+    Ok(x_win::RedactionRule::new(matches, action))
+  }
+}
+
+impl From<x_win::RedactionRule> for RedactionRule {
+  fn from(value: x_win::RedactionRule) -> Self {
+    let (matches, pattern) = match value.matches {
+      x_win::RuleMatch::Any => (RuleMatch::Any, None),
+      x_win::RuleMatch::App(name) => (RuleMatch::App, Some(name)),
+      x_win::RuleMatch::Title(pattern) => (RuleMatch::Title, Some(pattern)),
+      x_win::RuleMatch::PrivateBrowsing => (RuleMatch::PrivateBrowsing, None),
+    };
+    let (action, length) = match value.action {
+      x_win::RedactionAction::DropWindow => (RedactionAction::DropWindow, None),
+      x_win::RedactionAction::HashTitle => (RedactionAction::HashTitle, None),
+      x_win::RedactionAction::TruncateTitle(length) => {
+        (RedactionAction::TruncateTitle, Some(length as u32))
+      }
+      x_win::RedactionAction::StripUrlQuery => (RedactionAction::StripUrlQuery, None),
+      x_win::RedactionAction::StripUrlPath => (RedactionAction::StripUrlPath, None),
+      x_win::RedactionAction::MarkPrivate => (RedactionAction::MarkPrivate, None),
+    };
+    RedactionRule {
+      matches,
+      pattern,
+      action,
+      length,
+    }
+  }
+}
@@ -0,0 +1,25 @@
+/**
+ * What x-win can reach from the sandbox it runs in
+ */
+#[napi(object, use_nullable = true)]
+pub struct SandboxInfo {
+  pub flatpak_id: Option<String>,
+  pub host_commands: bool,
+  pub process_info: bool,
+  pub process_info_error: Option<String>,
+  pub extension_install: bool,
+  pub gnome_shell_bus: bool,
+}
+
+impl From<x_win::SandboxInfo> for SandboxInfo {
+  fn from(value: x_win::SandboxInfo) -> Self {
+    SandboxInfo {
+      flatpak_id: value.flatpak_id,
+      host_commands: value.host_commands,
+      process_info: value.process_info,
+      process_info_error: value.process_info_error,
+      extension_install: value.extension_install,
+      gnome_shell_bus: value.gnome_shell_bus,
+    }
+  }
+}
@@ -0,0 +1,28 @@
+use super::diagnostic_report::SessionType;
+
+/**
+ * Lock and activity state of the session.
+ * `active` is false when another user or a TTY is in the foreground of the seat.
+ */
+#[napi(object)]
+pub struct SessionState {
+  pub locked: bool,
+  pub active: bool,
+  pub session_type: SessionType,
+}
+
+impl From<x_win::SessionState> for SessionState {
+  fn from(value: x_win::SessionState) -> Self {
+    SessionState {
+      locked: value.locked,
+      active: value.active,
+      session_type: value.session_type.into(),
+    }
+  }
+}
+
+impl From<SessionState> for x_win::SessionState {
+  fn from(value: SessionState) -> Self {
+    x_win::SessionState::new(value.locked, value.active, value.session_type.into())
+  }
+}
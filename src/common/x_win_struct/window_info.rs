@@ -7,7 +7,7 @@ use super::{
  * Struct to store all informations of the window
  */
 #[derive(Debug, Clone)]
-#[napi(object)]
+#[napi(object, use_nullable = true)]
 pub struct WindowInfoObject {
   pub id: u32,
   pub os: String,
@@ -16,7 +16,7 @@ pub struct WindowInfoObject {
   pub info: ProcessInfo,
   pub usage: UsageInfo,
   pub icon: IconInfo,
-  pub url: String,
+  pub url: Option<String>,
 }
 
 /**
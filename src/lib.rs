@@ -9,13 +9,26 @@ use napi_derive::napi;
 use common::{
   thread::ThreadManager,
   x_win_struct::{
+    activity_span::{ActivitySpan, ActivitySummary, GroupBy},
+    capabilities::Capabilities,
+    diagnostic_report::DiagnosticReport,
+    extension_status::ExtensionStatus,
+    icon_buffer::IconBuffer,
     icon_info::IconInfo,
+    idle_event::IdleEvent,
+    install_options::InstallOptions,
+    redaction_rule::RedactionRule,
+    sandbox_info::SandboxInfo,
+    session_state::SessionState,
     window_info::{WindowInfo, WindowInfoObject},
   },
 };
 use error::xwin_error;
 use napi::{bindgen_prelude::AsyncTask, JsNumber, Result, Task};
-use x_win::{empty_entity, get_active_window, get_browser_url, get_open_windows, get_window_icon};
+use x_win::{
+  empty_entity, get_active_window, get_browser_url, get_open_windows, get_window_icon,
+  get_window_icon_data, IconOptions,
+};
 
 #[macro_use]
 extern crate napi_derive;
@@ -40,6 +53,16 @@ impl GetIconTask {
   }
 }
 
+pub struct GetIconBufferTask {
+  data: WindowInfo,
+}
+
+impl GetIconBufferTask {
+  pub fn new(data: WindowInfo) -> Self {
+    Self { data }
+  }
+}
+
 #[napi]
 impl Task for OpenWindowsTask {
   type Output = Vec<WindowInfo>;
@@ -82,6 +105,20 @@ impl Task for GetIconTask {
   }
 }
 
+#[napi]
+impl Task for GetIconBufferTask {
+  type Output = x_win::IconData;
+  type JsValue = IconBuffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    get_icon_data(&self.data)
+  }
+
+  fn resolve(&mut self, _: napi::Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output.into())
+  }
+}
+
 fn get_icon(window_info: &WindowInfo) -> Result<IconInfo> {
   let t: x_win::WindowInfo = window_info.clone().into();
   match get_window_icon(&t) {
@@ -90,10 +127,32 @@ fn get_icon(window_info: &WindowInfo) -> Result<IconInfo> {
   }
 }
 
-fn get_url(window_info: &WindowInfo) -> Result<String> {
+fn get_icon_data(window_info: &WindowInfo) -> Result<x_win::IconData> {
+  let t: x_win::WindowInfo = window_info.clone().into();
+  match get_window_icon_data(&t, IconOptions::default()) {
+    Ok(icon_data) => Ok(icon_data),
+    Err(err) => Err(xwin_error(err)),
+  }
+}
+
+fn get_url(window_info: &WindowInfo) -> Result<Option<String>> {
   let t: x_win::WindowInfo = window_info.clone().into();
   match get_browser_url(&t) {
-    Ok(browser_url) => Ok(browser_url),
+    // Urls of windows marked private are emptied by the redaction rules
+    Ok(browser_info) => Ok(
+      browser_info
+        .map(|browser_info| browser_info.url)
+        .filter(|url| !url.is_empty()),
+    ),
+    // Browsers and systems not allowing to read the url are seen as windows without url
+    Err(err)
+      if matches!(
+        err.downcast_ref::<x_win::XWinError>(),
+        Some(x_win::XWinError::Unsupported(_))
+      ) =>
+    {
+      Ok(None)
+    }
     Err(err) => Err(xwin_error(err)),
   }
 }
@@ -118,10 +177,18 @@ impl WindowInfo {
   }
 
   /**
-   * Getter to recover browser url
+   * Promise funciton who help to recover icon of application as raw PNG bytes and will return `IconBuffer`.
+   */
+  #[napi]
+  pub fn get_icon_buffer(&self) -> AsyncTask<GetIconBufferTask> {
+    AsyncTask::new(GetIconBufferTask::new(self.clone()))
+  }
+
+  /**
+   * Getter to recover browser url, `null` when the window is not a browser or the url can't be read
    */
   #[napi(getter)]
-  pub fn url(&self) -> Result<String> {
+  pub fn url(&self) -> Result<Option<String>> {
     get_url(self)
   }
 
@@ -131,7 +198,7 @@ impl WindowInfo {
   #[napi]
   pub fn to_object(&self) -> Result<WindowInfoObject> {
     let window_info = &self.clone();
-    let url = window_info.url()?.clone();
+    let url = window_info.url()?;
     let icon: IconInfo = window_info.get_icon()?.clone();
     Ok(WindowInfoObject {
       id: window_info.id,
@@ -523,6 +590,262 @@ pub fn unsubscribe_all_active_window() -> Result<()> {
   }
 }
 
+/**
+ * Return the time in milliseconds since the user last used the keyboard or the mouse.
+ * Uses MIT-SCREEN-SAVER on X11, the Mutter idle monitor on GNOME and `ext-idle-notify-v1` on other Wayland compositors.
+ */
+#[napi]
+pub fn get_idle_time() -> Result<i64> {
+  x_win::get_idle_time()
+    .map(|idle_time| idle_time.as_millis() as i64)
+    .map_err(xwin_error)
+}
+
+/**
+ * Subscribe an observer thread called when the user becomes idle and when they are active again.
+ * Returns a unique ID to unsubscribe it with `unsubscribeIdle`.
+ *
+ * @param {function} callback - Callback function that returns the new idle state
+ * @param {number} [threshold=300000] - Time without input before the user is idle (default: 5min)
+ * @param {number} [interval=1000] - Interval between checks of the idle time (default: 1000ms)
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { subscribeIdle, unsubscribeIdle } from '@miniben90/x-win';
+ *
+ * const id = subscribeIdle((err, event) => {
+ *   console.log(event.idle ? `Idle since ${event.idleTime}ms` : 'Active');
+ * }, 60 * 1000);
+ *
+ * unsubscribeIdle(id);
+ * ```
+ */
+#[napi(
+  ts_args_type = "callback: (error: Error | null, event: IdleEvent | undefined) => void, threshold?: number, interval?: number"
+)]
+pub fn subscribe_idle(
+  callback: ThreadsafeFunction<IdleEvent>,
+  threshold: Option<u32>,
+  interval: Option<u32>,
+) -> Result<u32> {
+  let threshold = Duration::from_millis(threshold.unwrap_or(5 * 60 * 1000) as u64);
+  let interval =
+    Duration::from_millis(interval.filter(|interval| interval.gt(&0)).unwrap_or(1000) as u64);
+
+  let thread_manager = THREAD_MANAGER
+    .lock()
+    .map_err(|_| napi::Error::from_reason("Filed to lock THREAD_MANAGER"))?;
+
+  let id = thread_manager
+    .start_thread(move |receiver| {
+      let mut watch = x_win::activity::IdleWatch::new(threshold);
+      loop {
+        match receiver.try_recv() {
+          Ok(_) | Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+            break;
+          }
+          _ => {
+            match x_win::get_idle_time() {
+              Ok(idle_time) => {
+                if let Some(event) = watch.update(idle_time) {
+                  callback.call(Ok(event.into()), ThreadsafeFunctionCallMode::Blocking);
+                }
+              }
+              Err(err) => {
+                callback.call(Err(xwin_error(err)), ThreadsafeFunctionCallMode::Blocking);
+                break;
+              }
+            }
+            thread::sleep(interval);
+          }
+        }
+      }
+    })
+    .map_err(napi::Error::from_reason)?;
+
+  Ok(id)
+}
+
+/**
+ * Terminate and unsubscribe an observer of `subscribeIdle` using its ID.
+ */
+#[napi]
+pub fn unsubscribe_idle(thread_id: u32) -> Result<()> {
+  if let Ok(thread_manager) = THREAD_MANAGER.lock() {
+    thread_manager
+      .stop_thread(thread_id)
+      .map_err(napi::Error::from_reason)?;
+  }
+  Ok(())
+}
+
+/**
+ * Tell if the session is locked or inactive, to stop attributing time to the focused window meanwhile.
+ * Read from logind and the screen saver over D-Bus, Linux only.
+ */
+#[napi]
+pub fn session_state() -> Result<SessionState> {
+  x_win::session_state()
+    .map(|state| state.into())
+    .map_err(xwin_error)
+}
+
+/**
+ * Subscribe an observer thread called with the current session state, then each time the session is locked, unlocked, activated or deactivated.
+ * Returns a unique ID to unsubscribe it with `unsubscribeSessionState`. Linux only.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { ActivityTracker, subscribeSessionState, unsubscribeSessionState } from '@miniben90/x-win';
+ *
+ * const tracker = new ActivityTracker();
+ * const id = subscribeSessionState((err, state) => {
+ *   if (state) tracker.sessionChanged(state);
+ * });
+ *
+ * unsubscribeSessionState(id);
+ * ```
+ */
+#[napi(ts_args_type = "callback: (error: Error | null, state: SessionState | undefined) => void")]
+pub fn subscribe_session_state(callback: ThreadsafeFunction<SessionState>) -> Result<u32> {
+  let states = x_win::watch_session_state().map_err(xwin_error)?;
+
+  let thread_manager = THREAD_MANAGER
+    .lock()
+    .map_err(|_| napi::Error::from_reason("Filed to lock THREAD_MANAGER"))?;
+
+  let id = thread_manager
+    .start_thread(move |receiver| loop {
+      match receiver.try_recv() {
+        Ok(_) | Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+          break;
+        }
+        _ => match states.recv_timeout(Duration::from_millis(100)) {
+          Ok(state) => {
+            callback.call(Ok(state.into()), ThreadsafeFunctionCallMode::Blocking);
+          }
+          Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+          Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+            callback.call(
+              Err(napi::Error::from_reason(
+                "Session state is no longer followed",
+              )),
+              ThreadsafeFunctionCallMode::Blocking,
+            );
+            break;
+          }
+        },
+      }
+    })
+    .map_err(napi::Error::from_reason)?;
+
+  Ok(id)
+}
+
+/**
+ * Terminate and unsubscribe an observer of `subscribeSessionState` using its ID.
+ */
+#[napi]
+pub fn unsubscribe_session_state(thread_id: u32) -> Result<()> {
+  if let Ok(thread_manager) = THREAD_MANAGER.lock() {
+    thread_manager
+      .stop_thread(thread_id)
+      .map_err(napi::Error::from_reason)?;
+  }
+  Ok(())
+}
+
+/**
+ * Sum the time of `spans` by app, title or url for each `interval` in milliseconds.
+ * Spans are split at the interval boundaries, without `interval` a single interval covers all spans.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { ActivityTracker, GroupBy, summarizeActivity } from '@miniben90/x-win';
+ *
+ * const tracker = new ActivityTracker();
+ * setInterval(() => tracker.poll(), 1000);
+ *
+ * // Time spent per application for each hour
+ * const summaries = summarizeActivity(tracker.spans(), GroupBy.App, 60 * 60 * 1000);
+ * ```
+ */
+#[napi]
+pub fn summarize_activity(
+  spans: Vec<ActivitySpan>,
+  group_by: GroupBy,
+  interval: Option<u32>,
+) -> Vec<ActivitySummary> {
+  let spans: Vec<x_win::activity::ActivitySpan> =
+    spans.into_iter().map(|span| span.into()).collect();
+  x_win::activity::summarize(
+    &spans,
+    group_by.into(),
+    Duration::from_millis(interval.unwrap_or(0) as u64),
+  )
+  .into_iter()
+  .map(|summary| summary.into())
+  .collect()
+}
+
+/**
+ * Redact windows and urls with `rules` before they are returned by x-win, including `subscribeActiveWindow` callbacks and `toObject()`.
+ * Every rule matching a window is applied in order, an empty list removes the rules.
+ *
+ * # Example
+ *
+ * ```typescript
+ * import { RedactionAction, RuleMatch, setRedactionRules } from '@miniben90/x-win';
+ *
+ * setRedactionRules([
+ *   { matches: RuleMatch.App, pattern: 'keepassxc', action: RedactionAction.DropWindow },
+ *   { matches: RuleMatch.Title, pattern: '(?i)inbox', action: RedactionAction.HashTitle },
+ *   { matches: RuleMatch.Any, action: RedactionAction.StripUrlQuery },
+ *   { matches: RuleMatch.PrivateBrowsing, action: RedactionAction.MarkPrivate },
+ * ]);
+ * ```
+ */
+#[napi]
+pub fn set_redaction_rules(rules: Vec<RedactionRule>) -> Result<()> {
+  let rules = rules
+    .into_iter()
+    .map(|rule| rule.try_into())
+    .collect::<Result<Vec<x_win::RedactionRule>>>()?;
+  x_win::set_redaction_rules(rules).map_err(xwin_error)
+}
+
+/**
+ * Return the rules set with `setRedactionRules`
+ */
+#[napi]
+pub fn redaction_rules() -> Vec<RedactionRule> {
+  x_win::redaction_rules()
+    .into_iter()
+    .map(|rule| rule.into())
+    .collect()
+}
+
+/**
+ * Remove all icons kept in the icon cache.
+ * Icons are cached by application path and size to avoid recovering them on each call.
+ */
+#[napi]
+pub fn clear_icon_cache() -> Result<()> {
+  x_win::clear_icon_cache().map_err(xwin_error)
+}
+
+/**
+ * Return the features available with the backend detected for the current session.
+ * Useful to hide features that can't work, like browser urls without accessibility bus or windows before the gnome extension is enabled.
+ */
+#[napi]
+pub fn capabilities() -> Capabilities {
+  x_win::capabilities().into()
+}
+
 /**
  * Install "@mininben90/x-win" Gnome extension required for Linux using Gnome > 41.
  * This function will write extension files needed to correctly detect working windows with Wayland desktop environment.
@@ -533,6 +856,16 @@ pub fn install_extension() -> Result<bool> {
   x_win::install_extension().map_err(xwin_error)
 }
 
+/**
+ * Install "@mininben90/x-win" Gnome extension for the current user, for all users or into a custom prefix.
+ * With `enableByDefault` the extension is added to `org.gnome.shell enabled-extensions` to be enabled on next session.
+ * **Restart session will be require to install the gnome extension.**
+ */
+#[napi]
+pub fn install_extension_with(options: InstallOptions) -> Result<bool> {
+  x_win::install_extension_with(options.try_into()?).map_err(xwin_error)
+}
+
 /**
  * Uninstall "@mininben90/x-win" Gnome extension.
  * This function will disable and remove extension files.
@@ -543,6 +876,17 @@ pub fn uninstall_extension() -> Result<bool> {
   x_win::uninstall_extension().map_err(xwin_error)
 }
 
+/**
+ * Uninstall "@mininben90/x-win" Gnome extension installed for the current user, for all users or into a custom prefix.
+ * The schema override written with `enableByDefault` is removed from system and custom prefixes,
+ * for the current user `enableByDefault` removes the extension from `org.gnome.shell enabled-extensions`.
+ * **Restart session will be require to remove the gnome extension.**
+ */
+#[napi]
+pub fn uninstall_extension_with(options: InstallOptions) -> Result<bool> {
+  x_win::uninstall_extension_with(options.try_into()?).map_err(xwin_error)
+}
+
 /**
  * Enable Gnome extensions required for Linux using Gnome > 41.
  * This function will enable extension needed to correctly detect working windows with Wayland desktop environment.
@@ -578,3 +922,36 @@ pub fn is_enabled_extension() -> Result<bool> {
 pub fn is_installed_extension() -> Result<bool> {
   x_win::is_installed_extension().map_err(xwin_error)
 }
+
+/**
+ * Return the state of the gnome extension for Linux using Gnome > 41.
+ * Tell if the extension is not installed, written by another x-win version, waiting a session restart, enabled or in error. Working only with Wayland windows manager.
+ */
+#[napi]
+pub fn extension_status() -> Result<ExtensionStatus> {
+  x_win::extension_status()
+    .map(|status| status.into())
+    .map_err(xwin_error)
+}
+
+/**
+ * Report the session type, desktop, Gnome Shell version and the state of the gnome extension with suggestions to fix them.
+ * Useful to understand why windows can not be recovered on Linux.
+ */
+#[napi]
+pub fn diagnose() -> Result<DiagnosticReport> {
+  x_win::diagnose()
+    .map(|report| report.into())
+    .map_err(xwin_error)
+}
+
+/**
+ * Tell if x-win runs inside a Flatpak sandbox and what it can reach from there.
+ * Outside of a sandbox and on Windows and macOS nothing is restricted.
+ */
+#[napi]
+pub fn sandbox_info() -> Result<SandboxInfo> {
+  x_win::sandbox_info()
+    .map(|info| info.into())
+    .map_err(xwin_error)
+}
@@ -0,0 +1,12 @@
+use x_win::diagnose;
+
+fn main() {
+  match diagnose() {
+    Ok(report) => {
+      println!("diagnostic report: {:#?}", report);
+    }
+    Err(_) => {
+      println!("error occurred while diagnosing the session");
+    }
+  }
+}
@@ -0,0 +1,16 @@
+use std::time::Duration;
+use x_win::testing::record;
+
+fn main() {
+  let path = std::env::args()
+    .nth(1)
+    .unwrap_or_else(|| String::from("timeline.jsonl"));
+  match record(10, Duration::from_secs(1)).and_then(|timeline| timeline.save(&path)) {
+    Ok(_) => {
+      println!("timeline saved in {}", path);
+    }
+    Err(err) => {
+      println!("error occurred while recording the timeline: {err}");
+    }
+  }
+}
@@ -0,0 +1,15 @@
+use futures_lite::StreamExt;
+use std::time::Duration;
+use x_win::r#async::watch_active_window;
+
+fn main() {
+  futures_lite::future::block_on(async {
+    let mut windows = watch_active_window(Duration::from_millis(500));
+    while let Some(window) = windows.next().await {
+      match window {
+        Ok(window) => println!("active window: {:#?}", window),
+        Err(err) => println!("error occurred while watching the active window: {err}"),
+      }
+    }
+  });
+}
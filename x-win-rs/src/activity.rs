@@ -0,0 +1,653 @@
+#![deny(unused_imports)]
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::common::{
+  result::Result,
+  x_win_struct::{session_state::SessionState, window_info::WindowInfo},
+};
+
+/**
+ * Time spent on one window with the same title and url.
+ * `start` and `end` are milliseconds since the UNIX epoch.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ActivitySpan {
+  pub window_id: u32,
+  /**
+   * Name of the application owning the window
+   */
+  pub app: String,
+  pub title: String,
+  pub url: Option<String>,
+  pub start: u64,
+  pub end: u64,
+}
+
+impl ActivitySpan {
+  /**
+   * Length of the span in milliseconds
+   */
+  pub fn duration(&self) -> u64 {
+    self.end.saturating_sub(self.start)
+  }
+
+  fn is_same(&self, window: &WindowInfo, url: &Option<String>) -> bool {
+    self.window_id.eq(&window.id) && self.title.eq(&window.title) && self.url.eq(url)
+  }
+}
+
+/**
+ * Tell for how long the user has not used the keyboard or the mouse
+ */
+pub trait IdleSource: Send {
+  fn idle_time(&self) -> Result<Duration>;
+}
+
+/**
+ * Idle time of the session from `get_idle_time`
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlatformIdle;
+
+impl IdleSource for PlatformIdle {
+  fn idle_time(&self) -> Result<Duration> {
+    crate::get_idle_time()
+  }
+}
+
+/**
+ * Idle source never idle, used by default
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoIdle;
+
+impl IdleSource for NoIdle {
+  fn idle_time(&self) -> Result<Duration> {
+    Ok(Duration::ZERO)
+  }
+}
+
+/**
+ * Idle time given by the application, clones share the same value
+ */
+#[derive(Debug, Clone, Default)]
+pub struct ManualIdle {
+  idle_time: Arc<Mutex<Duration>>,
+}
+
+impl ManualIdle {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn set(&self, idle_time: Duration) {
+    if let Ok(mut value) = self.idle_time.lock() {
+      *value = idle_time;
+    }
+  }
+}
+
+impl IdleSource for ManualIdle {
+  fn idle_time(&self) -> Result<Duration> {
+    match self.idle_time.lock() {
+      Ok(value) => Ok(*value),
+      Err(_) => Err("Not possible to read the idle time".into()),
+    }
+  }
+}
+
+/**
+ * Turn focus changes into `ActivitySpan`.
+ * When the idle source reports an idle time longer than the threshold, the current span ends at the last input
+ * and no span is recorded until the user comes back. The same goes while the session is locked or inactive.
+ */
+pub struct ActivityTracker {
+  idle_source: Box<dyn IdleSource>,
+  idle_threshold: Duration,
+  /**
+   * Last focused window with its url, kept while idle to resume on it
+   */
+  focused: Option<(WindowInfo, Option<String>)>,
+  current: Option<ActivitySpan>,
+  spans: Vec<ActivitySpan>,
+  idle: bool,
+  /**
+   * Session is locked or inactive
+   */
+  away: bool,
+}
+
+impl Default for ActivityTracker {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ActivityTracker {
+  pub fn new() -> Self {
+    Self {
+      idle_source: Box::new(NoIdle),
+      idle_threshold: Duration::from_secs(5 * 60),
+      focused: None,
+      current: None,
+      spans: vec![],
+      idle: false,
+      away: false,
+    }
+  }
+
+  /**
+   * Stop spans when `idle_source` reports no input for `idle_threshold`
+   */
+  pub fn with_idle_source(
+    mut self,
+    idle_source: impl IdleSource + 'static,
+    idle_threshold: Duration,
+  ) -> Self {
+    self.idle_source = Box::new(idle_source);
+    self.idle_threshold = idle_threshold;
+    self
+  }
+
+  /**
+   * Record that `window` has the focus now, with the url of its tab when it is a browser
+   */
+  pub fn focus(&mut self, window: &WindowInfo, url: Option<String>) -> Result<()> {
+    self.focus_at(window, url, timestamp())
+  }
+
+  /**
+   * Record that `window` has the focus at `at`, in milliseconds since the UNIX epoch.
+   * A window with the id `0` means that no window has the focus.
+   */
+  pub fn focus_at(&mut self, window: &WindowInfo, url: Option<String>, at: u64) -> Result<()> {
+    self.check_idle(at)?;
+    if let Some(current) = self.current.as_mut() {
+      if current.is_same(window, &url) {
+        current.end = at;
+        return Ok(());
+      }
+    }
+    self.close(at);
+    self.focused = Some((window.clone(), url)).filter(|(window, _)| window.id.ne(&0));
+    if !self.idle && !self.away {
+      self.open(at);
+    }
+    Ok(())
+  }
+
+  /**
+   * Extend the current span until now and check if the user is idle
+   */
+  pub fn tick(&mut self) -> Result<()> {
+    self.tick_at(timestamp())
+  }
+
+  pub fn tick_at(&mut self, at: u64) -> Result<()> {
+    self.check_idle(at)?;
+    if let Some(current) = self.current.as_mut() {
+      current.end = current.end.max(at);
+    }
+    Ok(())
+  }
+
+  /**
+   * Record that the session got locked, unlocked, activated or deactivated now
+   */
+  pub fn session_changed(&mut self, state: &SessionState) {
+    self.session_changed_at(state, timestamp())
+  }
+
+  /**
+   * End the current span at `at` when the session is locked or inactive, resume on the focused window once it is back
+   */
+  pub fn session_changed_at(&mut self, state: &SessionState, at: u64) {
+    let away = state.is_away();
+    if away.eq(&self.away) {
+      return;
+    }
+    self.away = away;
+    match away {
+      true => self.close(at),
+      false if !self.idle => self.open(at),
+      false => {}
+    }
+  }
+
+  /**
+   * Read the session state, the active window and its url, then record them with `session_changed` and `focus`
+   */
+  pub fn poll(&mut self) -> Result<()> {
+    // Session state is not available on every platform
+    if let Ok(state) = crate::session_state() {
+      self.session_changed(&state);
+    }
+    let window = crate::get_active_window()?;
+    let url = match window.id {
+      0 => None,
+      _ => crate::get_browser_url(&window)
+        .ok()
+        .flatten()
+        .map(|browser| browser.url),
+    };
+    self.focus(&window, url)
+  }
+
+  /**
+   * End the current span now, the next `focus` starts a new one
+   */
+  pub fn stop(&mut self) {
+    self.stop_at(timestamp())
+  }
+
+  pub fn stop_at(&mut self, at: u64) {
+    self.close(at);
+    self.focused = None;
+  }
+
+  /**
+   * Span still being recorded
+   */
+  pub fn current(&self) -> Option<&ActivitySpan> {
+    self.current.as_ref()
+  }
+
+  /**
+   * Finished spans
+   */
+  pub fn spans(&self) -> &[ActivitySpan] {
+    &self.spans
+  }
+
+  /**
+   * Return and forget finished spans, to store them elsewhere
+   */
+  pub fn take_spans(&mut self) -> Vec<ActivitySpan> {
+    std::mem::take(&mut self.spans)
+  }
+
+  pub fn is_idle(&self) -> bool {
+    self.idle
+  }
+
+  pub fn is_away(&self) -> bool {
+    self.away
+  }
+
+  fn check_idle(&mut self, at: u64) -> Result<()> {
+    let idle_time = self.idle_source.idle_time()?;
+    if idle_time.ge(&self.idle_threshold) {
+      if !self.idle {
+        self.idle = true;
+        let last_input = at.saturating_sub(idle_time.as_millis() as u64);
+        self.close(last_input);
+      }
+    } else if self.idle {
+      self.idle = false;
+      if !self.away {
+        self.open(at.saturating_sub(idle_time.as_millis() as u64));
+      }
+    }
+    Ok(())
+  }
+
+  fn open(&mut self, at: u64) {
+    self.current = self.focused.as_ref().map(|(window, url)| ActivitySpan {
+      window_id: window.id,
+      app: window.info.name.clone(),
+      title: window.title.clone(),
+      url: url.clone(),
+      start: at,
+      end: at,
+    });
+  }
+
+  fn close(&mut self, at: u64) {
+    if let Some(mut span) = self.current.take() {
+      span.end = at.max(span.start);
+      if span.duration().gt(&0) {
+        self.spans.push(span);
+      }
+    }
+  }
+}
+
+/**
+ * Change of the idle state of the user
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum IdleEvent {
+  /**
+   * No input since `idle_time`
+   */
+  Idle {
+    idle_time: Duration,
+  },
+  Active,
+}
+
+/**
+ * Turn idle times checked from time to time into `IdleEvent`, the user starts active
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdleWatch {
+  threshold: Duration,
+  idle: bool,
+}
+
+impl IdleWatch {
+  pub fn new(threshold: Duration) -> Self {
+    Self {
+      threshold,
+      idle: false,
+    }
+  }
+
+  /**
+   * Return an event when `idle_time` crosses the threshold since the last update
+   */
+  pub fn update(&mut self, idle_time: Duration) -> Option<IdleEvent> {
+    let idle = idle_time.ge(&self.threshold);
+    if idle.eq(&self.idle) {
+      return None;
+    }
+    self.idle = idle;
+    Some(match idle {
+      true => IdleEvent::Idle { idle_time },
+      false => IdleEvent::Active,
+    })
+  }
+
+  pub fn is_idle(&self) -> bool {
+    self.idle
+  }
+}
+
+/**
+ * Field used to group spans in `summarize`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupBy {
+  App,
+  Title,
+  /**
+   * Spans without url are left out
+   */
+  Url,
+}
+
+/**
+ * Time spent on one app, title or url during an interval, in milliseconds
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ActivitySummary {
+  pub key: String,
+  pub start: u64,
+  pub end: u64,
+  pub duration: u64,
+}
+
+/**
+ * Sum the time of `spans` by `group_by` for each `interval` aligned on the UNIX epoch, spans are split at the
+ * interval boundaries. With `Duration::ZERO` a single interval covers all spans.
+ * Summaries are sorted by interval, then by time spent.
+ */
+pub fn summarize(
+  spans: &[ActivitySpan],
+  group_by: GroupBy,
+  interval: Duration,
+) -> Vec<ActivitySummary> {
+  let interval = interval.as_millis() as u64;
+  let (first, last) = match (
+    spans.iter().map(|span| span.start).min(),
+    spans.iter().map(|span| span.end).max(),
+  ) {
+    (Some(first), Some(last)) => (first, last),
+    _ => return vec![],
+  };
+  let mut totals: HashMap<(u64, String), u64> = HashMap::new();
+  for span in spans {
+    let key = match group_by {
+      GroupBy::App => span.app.clone(),
+      GroupBy::Title => span.title.clone(),
+      GroupBy::Url => match &span.url {
+        Some(url) => url.clone(),
+        None => continue,
+      },
+    };
+    let mut start = span.start;
+    while start.lt(&span.end) {
+      let (bucket, end) = match interval {
+        0 => (first, span.end),
+        _ => {
+          let bucket = start - start % interval;
+          (bucket, span.end.min(bucket + interval))
+        }
+      };
+      *totals.entry((bucket, key.clone())).or_insert(0) += end - start;
+      start = end;
+    }
+  }
+  let mut summaries: Vec<ActivitySummary> = totals
+    .into_iter()
+    .map(|((start, key), duration)| ActivitySummary {
+      key,
+      start,
+      end: match interval {
+        0 => last,
+        _ => start + interval,
+      },
+      duration,
+    })
+    .collect();
+  summaries.sort_by(|a, b| {
+    a.start
+      .cmp(&b.start)
+      .then(b.duration.cmp(&a.duration))
+      .then(a.key.cmp(&b.key))
+  });
+  summaries
+}
+
+/**
+ * Milliseconds since the UNIX epoch
+ */
+fn timestamp() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{
+    summarize, ActivitySpan, ActivityTracker, GroupBy, IdleEvent, IdleWatch, ManualIdle,
+  };
+  use crate::common::{
+    api::empty_entity,
+    x_win_struct::{
+      diagnostic_report::SessionType, session_state::SessionState, window_info::WindowInfo,
+    },
+  };
+
+  fn window(id: u32, app: &str, title: &str) -> WindowInfo {
+    let mut window = empty_entity();
+    window.id = id;
+    window.info.name = app.to_owned();
+    window.title = title.to_owned();
+    window
+  }
+
+  fn span(app: &str, url: Option<&str>, start: u64, end: u64) -> ActivitySpan {
+    ActivitySpan {
+      window_id: 1,
+      app: app.to_owned(),
+      title: app.to_owned(),
+      url: url.map(|url| url.to_owned()),
+      start,
+      end,
+    }
+  }
+
+  fn span_of(window: &WindowInfo, url: Option<&str>, start: u64, end: u64) -> ActivitySpan {
+    ActivitySpan {
+      window_id: window.id,
+      app: window.info.name.clone(),
+      title: window.title.clone(),
+      url: url.map(|url| url.to_owned()),
+      start,
+      end,
+    }
+  }
+
+  #[test]
+  fn test_tracker_focus() -> Result<(), Box<dyn std::error::Error>> {
+    let terminal = window(1, "Terminal", "vim");
+    let firefox = window(2, "Firefox", "x-win");
+    let mut tracker = ActivityTracker::new();
+    tracker.focus_at(&terminal, None, 1_000)?;
+    tracker.focus_at(&terminal, None, 2_000)?;
+    tracker.focus_at(&firefox, Some(String::from("https://github.com")), 5_000)?;
+    tracker.tick_at(6_000)?;
+    assert_eq!(tracker.spans(), &[span_of(&terminal, None, 1_000, 5_000)]);
+    assert_eq!(tracker.current().map(|span| span.end), Some(6_000));
+
+    // Same window with another url starts a new span
+    tracker.focus_at(&firefox, Some(String::from("https://docs.rs")), 7_000)?;
+    // No window has the focus
+    tracker.focus_at(&empty_entity(), None, 8_000)?;
+    assert!(tracker.current().is_none());
+    let spans = tracker.take_spans();
+    assert_eq!(spans.len(), 3);
+    assert_eq!(spans[2].url.as_deref(), Some("https://docs.rs"));
+    assert_eq!(spans[2].duration(), 1_000);
+    assert!(tracker.spans().is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn test_tracker_idle() -> Result<(), Box<dyn std::error::Error>> {
+    let idle = ManualIdle::new();
+    let terminal = window(1, "Terminal", "vim");
+    let mut tracker =
+      ActivityTracker::new().with_idle_source(idle.clone(), Duration::from_secs(60));
+    tracker.focus_at(&terminal, None, 0)?;
+
+    // Last input 90s ago, the span ends at 30s
+    idle.set(Duration::from_secs(90));
+    tracker.tick_at(120_000)?;
+    assert!(tracker.is_idle());
+    assert!(tracker.current().is_none());
+    assert_eq!(tracker.spans(), &[span_of(&terminal, None, 0, 30_000)]);
+
+    // Focus changes while idle are kept to resume on the right window
+    let firefox = window(2, "Firefox", "x-win");
+    tracker.focus_at(&firefox, None, 150_000)?;
+    assert!(tracker.current().is_none());
+    idle.set(Duration::from_secs(1));
+    tracker.tick_at(200_000)?;
+    assert!(!tracker.is_idle());
+    tracker.stop_at(260_000);
+    assert_eq!(
+      tracker.spans()[1],
+      span_of(&firefox, None, 199_000, 260_000)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_tracker_session() -> Result<(), Box<dyn std::error::Error>> {
+    let terminal = window(1, "Terminal", "vim");
+    let mut tracker = ActivityTracker::new();
+    tracker.focus_at(&terminal, None, 0)?;
+
+    let locked = SessionState::new(true, true, SessionType::Wayland);
+    tracker.session_changed_at(&locked, 10_000);
+    assert!(tracker.is_away());
+    assert!(tracker.current().is_none());
+    assert_eq!(tracker.spans(), &[span_of(&terminal, None, 0, 10_000)]);
+    tracker.tick_at(20_000)?;
+    assert!(tracker.current().is_none());
+
+    let unlocked = SessionState::new(false, true, SessionType::Wayland);
+    tracker.session_changed_at(&unlocked, 30_000);
+    tracker.stop_at(40_000);
+    assert_eq!(tracker.spans()[1], span_of(&terminal, None, 30_000, 40_000));
+    Ok(())
+  }
+
+  #[test]
+  fn test_idle_watch() {
+    let mut watch = IdleWatch::new(Duration::from_secs(60));
+    assert_eq!(watch.update(Duration::from_secs(10)), None);
+    assert_eq!(
+      watch.update(Duration::from_secs(60)),
+      Some(IdleEvent::Idle {
+        idle_time: Duration::from_secs(60)
+      })
+    );
+    assert!(watch.is_idle());
+    assert_eq!(watch.update(Duration::from_secs(120)), None);
+    assert_eq!(watch.update(Duration::ZERO), Some(IdleEvent::Active));
+    assert!(!watch.is_idle());
+  }
+
+  #[test]
+  fn test_summarize() {
+    let spans = vec![
+      span("Terminal", None, 0, 30_000),
+      span("Firefox", Some("https://github.com"), 30_000, 90_000),
+      span("Terminal", None, 90_000, 100_000),
+    ];
+    let by_app = summarize(&spans, GroupBy::App, Duration::ZERO);
+    assert_eq!(
+      by_app
+        .iter()
+        .map(|summary| (summary.key.as_str(), summary.duration))
+        .collect::<Vec<_>>(),
+      vec![("Firefox", 60_000), ("Terminal", 40_000)]
+    );
+    assert_eq!((by_app[0].start, by_app[0].end), (0, 100_000));
+
+    // Firefox span is split at the minute
+    let per_minute = summarize(&spans, GroupBy::Url, Duration::from_secs(60));
+    assert_eq!(per_minute.len(), 2);
+    assert_eq!(
+      (
+        per_minute[0].start,
+        per_minute[0].end,
+        per_minute[0].duration
+      ),
+      (0, 60_000, 30_000)
+    );
+    assert_eq!(
+      (per_minute[1].start, per_minute[1].duration),
+      (60_000, 30_000)
+    );
+    assert!(summarize(&[], GroupBy::Title, Duration::ZERO).is_empty());
+  }
+}
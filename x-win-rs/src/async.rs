@@ -0,0 +1,222 @@
+#![deny(unused_imports)]
+
+use std::{pin::Pin, time::Duration};
+
+use async_io::Timer;
+use futures_lite::{stream, Stream, StreamExt};
+
+use crate::{
+  activity::{IdleEvent, IdleWatch},
+  common::{
+    error::XWinError,
+    redaction::global_redaction,
+    x_win_struct::{icon_info::IconInfo, window_info::WindowInfo},
+  },
+};
+
+#[cfg(not(target_os = "linux"))]
+use crate::{common::api::Api, XWin};
+
+/**
+ * Error returned by the futures, `Send` to be able to await them from a spawned task
+ */
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/**
+ * Stream of the active window returned by `watch_active_window`
+ */
+pub type WindowStream = Pin<Box<dyn Stream<Item = Result<WindowInfo>> + Send>>;
+
+/**
+ * Stream of idle and active transitions returned by `watch_idle`
+ */
+pub type IdleStream = Pin<Box<dyn Stream<Item = Result<IdleEvent>> + Send>>;
+
+/**
+ * Retrieve information the about currently active window.
+ * Return `WindowInfo` containing details about a specific active window.
+ */
+pub async fn get_active_window() -> Result<WindowInfo> {
+  Ok(redact_active_window(active_window().await?))
+}
+
+/**
+ * Active window of the platform before redaction
+ */
+async fn active_window() -> Result<WindowInfo> {
+  #[cfg(target_os = "linux")]
+  {
+    crate::linux::get_active_window_async().await
+  }
+  #[cfg(not(target_os = "linux"))]
+  {
+    unblock(|| XWin::shared().api().get_active_window()).await
+  }
+}
+
+/**
+ * Retrieve information about the currently open windows.
+ * Return `Vec<WindowInfo>` each containing details about a specific open window.
+ */
+pub async fn get_open_windows() -> Result<Vec<WindowInfo>> {
+  #[cfg(target_os = "linux")]
+  let windows = crate::linux::get_open_windows_async().await?;
+  #[cfg(not(target_os = "linux"))]
+  let windows = unblock(|| XWin::shared().api().get_open_windows()).await?;
+  Ok(match global_redaction() {
+    Some(redaction) => redaction.redact_windows(windows),
+    None => windows,
+  })
+}
+
+/**
+ * Recover icon of window.
+ * Return `IconInfo`
+ */
+pub async fn get_window_icon(window_info: &WindowInfo) -> Result<IconInfo> {
+  let window_info = window_info.clone();
+  unblock(move || crate::get_window_icon(&window_info)).await
+}
+
+/**
+ * Stream the active window each time another window gets the focus or its title changes.
+ * X11 sessions are woken up by the X server, other sessions check the active window every `interval`.
+ */
+pub fn watch_active_window(interval: Duration) -> WindowStream {
+  #[cfg(target_os = "linux")]
+  let windows = crate::linux::watch_active_window(interval);
+  #[cfg(not(target_os = "linux"))]
+  let windows = poll_active_window(interval);
+  Box::pin(windows.map(|window| window.map(redact_active_window)))
+}
+
+/**
+ * Time since the last input of the user.
+ * Return `Duration`
+ */
+pub async fn get_idle_time() -> Result<Duration> {
+  unblock(crate::get_idle_time).await
+}
+
+/**
+ * Stream `IdleEvent::Idle` when the user has not used the keyboard or the mouse for `threshold`
+ * and `IdleEvent::Active` when they come back. The idle time is checked every `interval`.
+ */
+pub fn watch_idle(threshold: Duration, interval: Duration) -> IdleStream {
+  let state: (IdleWatch, bool) = (IdleWatch::new(threshold), true);
+  Box::pin(stream::unfold(
+    state,
+    move |(mut watch, first)| async move {
+      if !first {
+        Timer::after(interval).await;
+      }
+      loop {
+        match get_idle_time().await {
+          Ok(idle_time) => {
+            if let Some(event) = watch.update(idle_time) {
+              return Some((Ok(event), (watch, false)));
+            }
+          }
+          Err(err) => return Some((Err(err), (watch, false))),
+        }
+        Timer::after(interval).await;
+      }
+    },
+  ))
+}
+
+/**
+ * Stream the active window by calling `get_active_window` every `interval`
+ */
+pub(crate) fn poll_active_window(interval: Duration) -> WindowStream {
+  let state: (Option<WindowInfo>, bool) = (None, true);
+  Box::pin(stream::unfold(state, move |(mut last, first)| async move {
+    if !first {
+      Timer::after(interval).await;
+    }
+    loop {
+      match active_window().await {
+        Ok(window) => {
+          if !is_same_window(last.as_ref(), &window) {
+            last = Some(window.clone());
+            return Some((Ok(window), (last, false)));
+          }
+        }
+        Err(err) => return Some((Err(err), (last, false))),
+      }
+      Timer::after(interval).await;
+    }
+  }))
+}
+
+/**
+ * Apply the rules of `set_redaction_rules` to the active window
+ */
+fn redact_active_window(window: WindowInfo) -> WindowInfo {
+  match global_redaction() {
+    Some(redaction) => redaction.redact_active_window(window),
+    None => window,
+  }
+}
+
+/**
+ * Tell if `window` is still the last streamed window, memory usage is ignored as it changes all the time
+ */
+pub(crate) fn is_same_window(last: Option<&WindowInfo>, window: &WindowInfo) -> bool {
+  last
+    .map(|last| last.id.eq(&window.id) && last.title.eq(&window.title))
+    .unwrap_or(false)
+}
+
+/**
+ * Run a blocking call on the thread pool of `blocking` without depending on an async runtime
+ */
+pub(crate) async fn unblock<T: Send + 'static>(
+  call: impl FnOnce() -> crate::common::result::Result<T> + Send + 'static,
+) -> Result<T> {
+  blocking::unblock(move || call().map_err(send_error)).await
+}
+
+/**
+ * Convert an error of the blocking API, `XWinError` stays recoverable with `downcast_ref`
+ */
+pub(crate) fn send_error(err: crate::common::error::Error) -> Error {
+  match err.downcast_ref::<XWinError>() {
+    Some(err) => Box::new(err.clone()),
+    None => err.to_string().into(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{is_same_window, send_error};
+  use crate::common::{api::empty_entity, error::XWinError};
+
+  #[test]
+  fn test_send_error() {
+    let err = send_error(XWinError::Unsupported(String::from("test")).into());
+    assert_eq!(
+      err.downcast_ref::<XWinError>(),
+      Some(&XWinError::Unsupported(String::from("test")))
+    );
+    assert_eq!(send_error("Not typed".into()).to_string(), "Not typed");
+  }
+
+  #[test]
+  fn test_is_same_window() {
+    let mut window = empty_entity();
+    window.id = 1;
+    window.title = String::from("x-win");
+    assert!(!is_same_window(None, &window));
+
+    let mut same = window.clone();
+    same.usage.memory = 1024;
+    assert!(is_same_window(Some(&window), &same));
+
+    let mut renamed = window.clone();
+    renamed.title = String::from("x-win - README.md");
+    assert!(!is_same_window(Some(&window), &renamed));
+  }
+}
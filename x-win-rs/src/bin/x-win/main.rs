@@ -0,0 +1,317 @@
+#![deny(unused_imports)]
+// `cli` feature requires the Rust version of `clap`, newer than the one of x-win
+#![allow(clippy::incompatible_msrv)]
+
+mod output;
+
+use std::{path::PathBuf, process::ExitCode, str::FromStr, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use x_win::{
+  diagnose, disable_extension, enable_extension, extension_status, install_extension_with,
+  uninstall_extension_with, IconFormat, IconOptions, InstallOptions, InstallScope, Result,
+  WindowInfo, XWin,
+};
+
+use output::{print, print_windows, Format, Table};
+
+// Help of the commands is generated from `///` comments
+/// Retrieve information about active and open windows
+#[derive(Debug, Parser)]
+#[command(name = "x-win", version)]
+struct Cli {
+  #[command(flatten)]
+  output: OutputArgs,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Debug, Args)]
+struct OutputArgs {
+  /// Output format, `table` by default and `ndjson` for `watch`
+  #[arg(long, global = true, value_enum)]
+  format: Option<Format>,
+  /// Same as `--format json`
+  #[arg(long, global = true, conflicts_with = "format")]
+  json: bool,
+}
+
+impl OutputArgs {
+  fn format_or(&self, default: Format) -> Format {
+    match self.json {
+      true => Format::Json,
+      false => self.format.unwrap_or(default),
+    }
+  }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Print the active window
+  Active,
+  /// Print the open windows
+  List,
+  /// Print the icon of a window as data URI or write it into a file
+  Icon {
+    /// Window id or `active`
+    id: WindowId,
+    /// File written with the icon, `.webp` and `.rgba` select the format, PNG otherwise
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Wanted size in pixels, the nearest available size is used
+    #[arg(long, default_value_t = 128)]
+    size: u32,
+  },
+  /// Print the url opened in a browser window
+  Url {
+    /// Window id or `active`
+    id: WindowId,
+  },
+  /// Print the active window each time the focus or its title changes
+  Watch {
+    /// Milliseconds between two checks of the active window
+    #[arg(long, default_value_t = 500)]
+    interval: u64,
+  },
+  /// Report the session and the state of the GNOME extension with suggestions
+  Diagnose,
+  /// Manage the GNOME extension used on Wayland
+  #[command(subcommand)]
+  Extension(ExtensionCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum ExtensionCommand {
+  /// Write the extension files, the session must be restarted to load them
+  Install {
+    /// Install for all users, requires root
+    #[arg(long, conflicts_with = "prefix")]
+    system: bool,
+    /// Install into `<prefix>/share/gnome-shell/extensions`
+    #[arg(long)]
+    prefix: Option<PathBuf>,
+    /// Enable the extension on the next session
+    #[arg(long)]
+    enable: bool,
+  },
+  /// Remove the extension files, the session must be restarted to unload them
+  Uninstall {
+    /// Remove the extension installed for all users, requires root
+    #[arg(long, conflicts_with = "prefix")]
+    system: bool,
+    /// Remove the extension from `<prefix>/share/gnome-shell/extensions`
+    #[arg(long)]
+    prefix: Option<PathBuf>,
+  },
+  Enable,
+  Disable,
+  Status,
+}
+
+/// Window given on the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WindowId {
+  Active,
+  Id(u32),
+}
+
+impl FromStr for WindowId {
+  type Err = String;
+
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    match value {
+      "active" => Ok(WindowId::Active),
+      id => id
+        .parse()
+        .map(WindowId::Id)
+        .map_err(|_| format!("\"{id}\" is not a window id or \"active\"")),
+    }
+  }
+}
+
+/**
+ * Result of an `extension` command
+ */
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtensionAction {
+  action: &'static str,
+  success: bool,
+}
+
+/**
+ * Icon written by `icon --out`
+ */
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IconFile {
+  path: PathBuf,
+  mime: String,
+  width: u32,
+  height: u32,
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  match run(cli) {
+    Ok(_) => ExitCode::SUCCESS,
+    Err(err) => {
+      eprintln!("x-win: {err}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn run(cli: Cli) -> Result<()> {
+  let format = cli.output.format_or(Format::Table);
+  match cli.command {
+    Command::Active => {
+      let window = XWin::builder().build()?.get_active_window()?;
+      print(format, &window, |window| {
+        output::windows_table(std::slice::from_ref(window))
+      })
+    }
+    Command::List => print_windows(format, &XWin::builder().build()?.get_open_windows()?),
+    Command::Icon { id, out, size } => {
+      let x_win = XWin::builder().build()?;
+      let window = find_window(&x_win, id)?;
+      match out {
+        Some(path) => {
+          let options = IconOptions::new(size, icon_format(&path), false);
+          let icon = x_win.get_window_icon_data(&window, options)?;
+          if icon.data.is_empty() {
+            return Err(format!("No icon found for window {}", window.id).into());
+          }
+          std::fs::write(&path, &icon.data)?;
+          let file = IconFile {
+            path,
+            mime: icon.mime,
+            width: icon.width,
+            height: icon.height,
+          };
+          print(format, &file, |file| {
+            Table::new(vec!["SIZE", "MIME", "PATH"]).row(vec![
+              format!("{}x{}", file.width, file.height),
+              file.mime.clone(),
+              file.path.display().to_string(),
+            ])
+          })
+        }
+        None => {
+          let options = IconOptions::new(size, IconFormat::Png, true);
+          let icon = x_win.get_window_icon_with(&window, options)?;
+          print(format, &icon, |icon| {
+            Table::new(vec!["SIZE", "DATA"]).row(vec![
+              format!("{}x{}", icon.width, icon.height),
+              icon.data.clone(),
+            ])
+          })
+        }
+      }
+    }
+    Command::Url { id } => {
+      let x_win = XWin::builder().build()?;
+      let window = find_window(&x_win, id)?;
+      print(
+        format,
+        &x_win.get_browser_url(&window)?,
+        output::browser_table,
+      )
+    }
+    Command::Watch { interval } => watch(
+      cli.output.format_or(Format::Ndjson),
+      Duration::from_millis(interval),
+    ),
+    Command::Diagnose => print(format, &diagnose()?, output::diagnostic_table),
+    Command::Extension(command) => extension(format, command),
+  }
+}
+
+fn extension(format: Format, command: ExtensionCommand) -> Result<()> {
+  let (action, success) = match command {
+    ExtensionCommand::Install {
+      system,
+      prefix,
+      enable,
+    } => (
+      "install",
+      install_extension_with(InstallOptions::new(install_scope(system, prefix), enable))?,
+    ),
+    ExtensionCommand::Uninstall { system, prefix } => (
+      "uninstall",
+      uninstall_extension_with(InstallOptions::new(install_scope(system, prefix), false))?,
+    ),
+    ExtensionCommand::Enable => ("enable", enable_extension()?),
+    ExtensionCommand::Disable => ("disable", disable_extension()?),
+    ExtensionCommand::Status => {
+      return print(format, &extension_status()?, output::status_table);
+    }
+  };
+  print(format, &ExtensionAction { action, success }, |result| {
+    Table::new(vec!["ACTION", "SUCCESS"])
+      .row(vec![result.action.to_owned(), result.success.to_string()])
+  })
+}
+
+fn install_scope(system: bool, prefix: Option<PathBuf>) -> InstallScope {
+  match (system, prefix) {
+    (_, Some(prefix)) => InstallScope::Custom(prefix),
+    (true, None) => InstallScope::System,
+    (false, None) => InstallScope::User,
+  }
+}
+
+/**
+ * Active window or open window with `id`
+ */
+fn find_window(x_win: &XWin, id: WindowId) -> Result<WindowInfo> {
+  let active_window = x_win.get_active_window()?;
+  let id = match id {
+    WindowId::Active if active_window.id.eq(&0) => return Err("No active window".into()),
+    WindowId::Active => return Ok(active_window),
+    WindowId::Id(id) if active_window.id.eq(&id) => return Ok(active_window),
+    WindowId::Id(id) => id,
+  };
+  x_win
+    .get_open_windows()?
+    .into_iter()
+    .find(|window| window.id.eq(&id))
+    .ok_or_else(|| format!("No open window with id {id}").into())
+}
+
+fn icon_format(path: &std::path::Path) -> IconFormat {
+  match path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .map(|extension| extension.to_lowercase())
+    .as_deref()
+  {
+    Some("webp") => IconFormat::Webp,
+    Some("rgba") | Some("raw") => IconFormat::RawRgba,
+    _ => IconFormat::Png,
+  }
+}
+
+/**
+ * Poll the active window and print it when another window gets the focus or its title changes
+ */
+fn watch(format: Format, interval: Duration) -> Result<()> {
+  let x_win = XWin::builder().build()?;
+  let mut last: Option<(u32, String)> = None;
+  loop {
+    let window = x_win.get_active_window()?;
+    let current = Some((window.id, window.title.clone()));
+    if last.ne(&current) {
+      last = current;
+      match format {
+        // A JSON array can not be streamed, each change is a document
+        Format::Json | Format::Ndjson => print(Format::Ndjson, &window, |_| Table::default())?,
+        Format::Table => print(format, &window, |window| {
+          Table::new(vec![]).row(output::window_row(window))
+        })?,
+      }
+    }
+    std::thread::sleep(interval);
+  }
+}
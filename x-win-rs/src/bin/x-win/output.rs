@@ -0,0 +1,241 @@
+#![deny(unused_imports)]
+
+use std::io::Write;
+
+use serde::Serialize;
+use x_win::{BrowserInfo, DiagnosticReport, ExtensionStatus, Result, WindowInfo};
+
+/**
+ * Output format selected with `--format` or `--json`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+  /// Aligned columns for humans
+  Table,
+  /// Pretty printed JSON document
+  Json,
+  /// One compact JSON document per line
+  Ndjson,
+}
+
+/**
+ * Columns printed with `Format::Table`
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+  header: Vec<&'static str>,
+  rows: Vec<Vec<String>>,
+}
+
+impl Table {
+  pub fn new(header: Vec<&'static str>) -> Self {
+    Self {
+      header,
+      rows: vec![],
+    }
+  }
+
+  pub fn row(mut self, row: Vec<String>) -> Self {
+    self.rows.push(row);
+    self
+  }
+
+  /**
+   * Pad every column to its widest cell, the last column is not padded to keep titles intact.
+   * The header line is skipped when the table has no header.
+   */
+  pub fn render(&self) -> String {
+    let header: Vec<String> = self.header.iter().map(|cell| cell.to_string()).collect();
+    let rows: Vec<&Vec<String>> = match header.is_empty() {
+      true => self.rows.iter().collect(),
+      false => std::iter::once(&header).chain(&self.rows).collect(),
+    };
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in &rows {
+      for (width, cell) in widths.iter_mut().zip(row.iter()) {
+        *width = (*width).max(cell.chars().count());
+      }
+    }
+    rows
+      .iter()
+      .map(|row| {
+        let line: Vec<String> = row
+          .iter()
+          .zip(&widths)
+          .enumerate()
+          .map(|(index, (cell, width))| match index + 1 == columns {
+            true => cell.clone(),
+            false => format!("{cell:width$}"),
+          })
+          .collect();
+        line.join("  ").trim_end().to_owned()
+      })
+      .collect::<Vec<String>>()
+      .join("\n")
+  }
+}
+
+pub fn windows_table(windows: &[WindowInfo]) -> Table {
+  windows.iter().fold(
+    Table::new(vec!["ID", "PID", "APP", "POSITION", "TITLE"]),
+    |table, window| table.row(window_row(window)),
+  )
+}
+
+/**
+ * Row of a window in `windows_table`, also printed alone by `watch`
+ */
+pub fn window_row(window: &WindowInfo) -> Vec<String> {
+  let position = &window.position;
+  vec![
+    window.id.to_string(),
+    window.info.process_id.to_string(),
+    window.info.name.clone(),
+    format!(
+      "{}x{}+{}+{}{}",
+      position.width,
+      position.height,
+      position.x,
+      position.y,
+      if position.is_full_screen { " full" } else { "" }
+    ),
+    window.title.clone(),
+  ]
+}
+
+pub fn browser_table(browser: &Option<BrowserInfo>) -> Table {
+  browser.iter().fold(
+    Table::new(vec!["BROWSER", "PRIVATE", "TAB", "URL"]),
+    |table, browser| {
+      table.row(vec![
+        format!("{:?}", browser.browser),
+        browser.is_private.to_string(),
+        browser.tab_title.clone(),
+        browser.url.clone(),
+      ])
+    },
+  )
+}
+
+pub fn status_table(status: &ExtensionStatus) -> Table {
+  let status = match status {
+    ExtensionStatus::Error(err) => format!("Error: {err}"),
+    status => format!("{status:?}"),
+  };
+  Table::new(vec!["STATUS"]).row(vec![status])
+}
+
+pub fn diagnostic_table(report: &DiagnosticReport) -> Table {
+  let optional = |value: Option<String>| value.unwrap_or_else(|| String::from("-"));
+  let table = Table::new(vec!["CHECK", "RESULT"])
+    .row(vec![String::from("OS"), report.os.clone()])
+    .row(vec![
+      String::from("Session"),
+      format!("{:?}", report.session_type),
+    ])
+    .row(vec![
+      String::from("Desktop"),
+      optional(report.desktop.clone()),
+    ])
+    .row(vec![
+      String::from("GNOME Shell"),
+      optional(report.gnome_shell_version.clone()),
+    ])
+    .row(vec![
+      String::from("Extension"),
+      optional(
+        report
+          .extension_status
+          .as_ref()
+          .map(|status| status_table(status).rows[0][0].clone()),
+      ),
+    ])
+    .row(vec![
+      String::from("D-Bus responds"),
+      optional(report.dbus_responds.map(|responds| responds.to_string())),
+    ])
+    .row(vec![
+      String::from("D-Bus error"),
+      optional(report.dbus_error.clone()),
+    ]);
+  report.suggestions.iter().fold(table, |table, suggestion| {
+    table.row(vec![String::from("Suggestion"), suggestion.clone()])
+  })
+}
+
+/**
+ * Print `value` in `format`, `table` is only built for `Format::Table`
+ */
+pub fn print<T: Serialize + ?Sized>(
+  format: Format,
+  value: &T,
+  table: impl FnOnce(&T) -> Table,
+) -> Result<()> {
+  let output = match format {
+    Format::Table => table(value).render(),
+    Format::Json => serde_json::to_string_pretty(value)?,
+    Format::Ndjson => serde_json::to_string(value)?,
+  };
+  let mut stdout = std::io::stdout().lock();
+  writeln!(stdout, "{output}")?;
+  // `watch` is read line by line by scripts
+  stdout.flush()?;
+  Ok(())
+}
+
+/**
+ * Print `windows` as one JSON document per window with `Format::Ndjson`
+ */
+pub fn print_windows(format: Format, windows: &[WindowInfo]) -> Result<()> {
+  match format {
+    Format::Ndjson => windows
+      .iter()
+      .try_for_each(|window| print(format, window, |_| Table::default())),
+    _ => print(format, windows, windows_table),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{diagnostic_table, windows_table, Table};
+  use x_win::{empty_entity, DiagnosticReport, ExtensionStatus, SessionType};
+
+  #[test]
+  fn test_render_table() {
+    let table = Table::new(vec!["ID", "TITLE"])
+      .row(vec![String::from("12345"), String::from("Terminal  ")])
+      .row(vec![String::from("7"), String::from("vim")]);
+    assert_eq!(table.render(), "ID     TITLE\n12345  Terminal\n7      vim");
+    assert_eq!(Table::new(vec!["ID", "TITLE"]).render(), "ID  TITLE");
+    let headless = Table::new(vec![]).row(vec![String::from("7"), String::from("vim")]);
+    assert_eq!(headless.render(), "7  vim");
+  }
+
+  #[test]
+  fn test_windows_table() {
+    let mut window = empty_entity();
+    window.id = 42;
+    window.title = String::from("x-win - README.md");
+    window.info.process_id = 1234;
+    window.info.name = String::from("Code");
+    window.position.width = 800;
+    window.position.height = 600;
+    window.position.is_full_screen = true;
+    assert_eq!(
+      windows_table(&[window]).render(),
+      "ID  PID   APP   POSITION          TITLE\n42  1234  Code  800x600+0+0 full  x-win - README.md"
+    );
+  }
+
+  #[test]
+  fn test_diagnostic_table() {
+    let mut report = DiagnosticReport::new(String::from("linux"), SessionType::Wayland);
+    report.extension_status = Some(ExtensionStatus::Error(String::from("Not loaded")));
+    report.suggestions = vec![String::from("Restart the session")];
+    let rendered = diagnostic_table(&report).render();
+    assert!(rendered.contains("Extension       Error: Not loaded"));
+    assert!(rendered.contains("Desktop         -"));
+    assert!(rendered.ends_with("Suggestion      Restart the session"));
+  }
+}
@@ -1,11 +1,24 @@
 #![deny(unused_imports)]
 
+use std::time::Duration;
+
 use super::x_win_struct::{
-  icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+  browser_info::BrowserInfo,
+  capabilities::{Backend, Capabilities},
+  icon_data::IconData,
+  icon_info::IconInfo,
+  icon_options::IconOptions,
+  process_info::ProcessInfo,
+  usage_info::UsageInfo,
+  window_info::WindowInfo,
   window_position::WindowPosition,
 };
 
-use super::result::Result;
+use super::{
+  error::XWinError,
+  icon::{convert_icon_data, icon_info_to_data},
+  result::Result,
+};
 
 pub trait Api {
   /**
@@ -24,9 +37,43 @@ pub trait Api {
   fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo>;
 
   /**
-   * Return a String if the window is a browser and can recover url from it (Work only with Windows 10/11 and Darwin systems)
+   * Return icon from window_info following `options` (size, format and data URI)
+   */
+  fn get_app_icon_with(&self, window_info: &WindowInfo, options: &IconOptions) -> Result<IconInfo> {
+    let icon_data = self.get_app_icon_data(window_info, options)?;
+    Ok(icon_data.to_icon_info(options.as_data_uri))
+  }
+
+  /**
+   * Return raw bytes of the icon from window_info following `options` (size and format)
+   * By default the icon from `get_app_icon` is decoded and converted to the wanted format, `preferred_size` is not used
+   */
+  fn get_app_icon_data(&self, window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
+    let icon_data = icon_info_to_data(&self.get_app_icon(window_info)?)?;
+    convert_icon_data(icon_data, options.format)
+  }
+
+  /**
+   * Return `BrowserInfo` if the window is a browser and its url can be recovered, `None` otherwise
+   * Return an `XWinError::Unsupported` error when the browser or the system does not allow to read the url
    */
-  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<String>;
+  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<Option<BrowserInfo>>;
+
+  /**
+   * Return features available with this backend in the current session
+   * By default no feature is reported, backends tell what works for them
+   */
+  fn capabilities(&self) -> Capabilities {
+    Capabilities::new(Backend::Unsupported)
+  }
+
+  /**
+   * Return the time since the last keyboard or mouse input of the user
+   * Return an `XWinError::Unsupported` error when the backend can not tell it
+   */
+  fn get_idle_time(&self) -> Result<Duration> {
+    Err(XWinError::Unsupported(String::from("Idle time is not available with this backend")).into())
+  }
 }
 
 /**
@@ -71,9 +118,10 @@ pub fn empty_entity() -> WindowInfo {
   }
 }
 
-pub fn empty_icon() -> IconInfo {
-  IconInfo {
-    data: String::from(""),
+pub fn empty_icon_data() -> IconData {
+  IconData {
+    data: vec![],
+    mime: String::from(""),
     height: 0,
     width: 0,
   }
@@ -0,0 +1,174 @@
+#![deny(unused_imports)]
+
+use super::x_win_struct::{
+  browser_info::{BrowserInfo, BrowserKind},
+  window_info::WindowInfo,
+};
+
+/**
+ * Return true if the executable name is a known browser
+ */
+#[cfg(target_os = "windows")]
+pub fn is_browser(browser_name: &str) -> bool {
+  matches!(
+    browser_name,
+    "chrome"
+      | "msedge"
+      | "opera"
+      | "opera_gx"
+      | "brave"
+      | "vivaldi"
+      | "iron"
+      | "epic"
+      | "chromium"
+      | "ucozmedia"
+      | "blisk"
+      | "maxthon"
+      | "beaker"
+      | "beaker browser"
+      | "firefox"
+      | "librewolf"
+  )
+}
+
+/**
+ * Return true if the executable name is a known browser, names of distribution packages and upper case names included
+ */
+#[cfg(target_os = "linux")]
+pub fn is_browser(browser_name: &str) -> bool {
+  matches!(
+    browser_name.to_lowercase().as_str(),
+    "chrome"
+      | "google-chrome"
+      | "msedge"
+      | "microsoft-edge"
+      | "opera"
+      | "opera_gx"
+      | "brave"
+      | "brave-browser"
+      | "vivaldi"
+      | "vivaldi-bin"
+      | "iron"
+      | "epic"
+      | "chromium"
+      | "chromium-browser"
+      | "ucozmedia"
+      | "blisk"
+      | "maxthon"
+      | "beaker"
+      | "beaker browser"
+      | "firefox"
+      | "firefox-bin"
+      | "firefox-esr"
+      | "librewolf"
+  )
+}
+
+/**
+ * Markers added by browsers to the title of private windows
+ */
+const PRIVATE_TITLE_MARKERS: [&str; 3] = ["private browsing", "(incognito)", "inprivate"];
+
+/**
+ * Words used in the browser name appended at the end of window titles
+ */
+const BROWSER_TITLE_SUFFIXES: [&str; 9] = [
+  "chrome",
+  "chromium",
+  "edge",
+  "firefox",
+  "librewolf",
+  "opera",
+  "brave",
+  "vivaldi",
+  "safari",
+];
+
+/**
+ * Return true if the window title looks like a private browsing window
+ */
+pub fn is_private_title(title: &str) -> bool {
+  let title = title.to_lowercase();
+  PRIVATE_TITLE_MARKERS
+    .iter()
+    .any(|marker| title.contains(marker))
+}
+
+/**
+ * Return the title of the active tab by removing the trailing `- Browser name` part of the window title
+ */
+pub fn tab_title(title: &str) -> String {
+  for separator in [" — ", " - ", " – "] {
+    if let Some(index) = title.rfind(separator) {
+      let suffix = title[index + separator.len()..].to_lowercase();
+      if BROWSER_TITLE_SUFFIXES
+        .iter()
+        .any(|name| suffix.contains(name))
+      {
+        return title[..index].trim().to_owned();
+      }
+    }
+  }
+  title.trim().to_owned()
+}
+
+/**
+ * Build `BrowserInfo` of the window, return `None` when no url has been recovered
+ */
+pub fn browser_info(
+  window_info: &WindowInfo,
+  browser: BrowserKind,
+  url: String,
+) -> Option<BrowserInfo> {
+  if url.is_empty() {
+    return None;
+  }
+  Some(BrowserInfo::new(
+    url,
+    browser,
+    is_private_title(&window_info.title),
+    tab_title(&window_info.title),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{is_private_title, tab_title};
+
+  #[test]
+  fn test_tab_title() {
+    assert_eq!(
+      tab_title("GitHub — Mozilla Firefox Private Browsing"),
+      "GitHub"
+    );
+    assert_eq!(tab_title("x-win - Rust - Google Chrome"), "x-win - Rust");
+    assert_eq!(tab_title("Start Page"), "Start Page");
+  }
+
+  #[test]
+  fn test_is_private_title() {
+    assert!(is_private_title(
+      "GitHub — Mozilla Firefox Private Browsing"
+    ));
+    assert!(is_private_title("New tab - [InPrivate] - Microsoft Edge"));
+    assert!(!is_private_title("GitHub — Mozilla Firefox"));
+  }
+
+  #[cfg(target_os = "windows")]
+  #[test]
+  fn test_is_browser() {
+    use super::is_browser;
+    assert!(is_browser("chrome"));
+    assert!(!is_browser("Chrome"));
+    assert!(!is_browser("google-chrome"));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_is_browser() {
+    use super::is_browser;
+    assert!(is_browser("google-chrome"));
+    assert!(is_browser("Firefox"));
+    assert!(!is_browser("code"));
+  }
+}
@@ -1 +1,37 @@
 pub type Error = Box<dyn std::error::Error>;
+
+/**
+ * Typed errors returned by x-win, boxed in `Error` and recoverable with `downcast_ref::<XWinError>()`
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XWinError {
+  /**
+   * Feature is not available on this platform or for this application
+   */
+  Unsupported(String),
+  /**
+   * A file or a folder of the GNOME extension can't be written or removed
+   */
+  ExtensionInstall { path: String, reason: String },
+  /**
+   * Installed GNOME extension exposes another D-Bus schema version than the one expected by this x-win
+   */
+  IncompatibleExtension { found: u32, expected: u32 },
+}
+
+impl std::fmt::Display for XWinError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      XWinError::Unsupported(reason) => write!(f, "Unsupported: {reason}"),
+      XWinError::ExtensionInstall { path, reason } => {
+        write!(f, "Not possible to write \"{path}\": {reason}")
+      }
+      XWinError::IncompatibleExtension { found, expected } => write!(
+        f,
+        "GNOME extension uses schema version {found} but x-win requires version {expected}. Please reinstall the extension and restart the session."
+      ),
+    }
+  }
+}
+
+impl std::error::Error for XWinError {}
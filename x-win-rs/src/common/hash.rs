@@ -0,0 +1,13 @@
+#![deny(unused_imports)]
+
+/**
+ * FNV-1a 64 bits hash, stable between runs and platforms
+ */
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in bytes {
+    hash ^= *byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  hash
+}
@@ -0,0 +1,129 @@
+#![deny(unused_imports)]
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use image::{ImageFormat, RgbaImage};
+
+use super::{
+  api::empty_icon_data,
+  result::Result,
+  x_win_struct::{icon_data::IconData, icon_info::IconInfo, icon_options::IconFormat},
+};
+
+/**
+ * Encode a RGBA buffer to `format`
+ */
+pub fn encode_icon(rgba: Vec<u8>, width: u32, height: u32, format: IconFormat) -> Result<IconData> {
+  let bytes: Vec<u8> = match format {
+    IconFormat::RawRgba => {
+      if rgba.len() != (width as usize) * (height as usize) * 4 {
+        return Err(String::from("Icon buffer does not match icon size!").into());
+      }
+      rgba
+    }
+    IconFormat::Png | IconFormat::Webp => {
+      let image =
+        RgbaImage::from_raw(width, height, rgba).ok_or("Icon buffer does not match icon size!")?;
+      let image_format = match format {
+        IconFormat::Webp => ImageFormat::WebP,
+        _ => ImageFormat::Png,
+      };
+      let mut data: Vec<u8> = Vec::new();
+      image.write_to(&mut std::io::Cursor::new(&mut data), image_format)?;
+      data
+    }
+  };
+
+  Ok(IconData::new(
+    bytes,
+    format.mime().to_owned(),
+    height,
+    width,
+  ))
+}
+
+/**
+ * Decode `data:<mime>;base64,...` string of `IconInfo` to raw bytes
+ */
+pub fn icon_info_to_data(icon_info: &IconInfo) -> Result<IconData> {
+  if icon_info.data.is_empty() {
+    return Ok(empty_icon_data());
+  }
+
+  let (mime, data) = match icon_info.data.split_once(";base64,") {
+    Some((mime, data)) => (mime.trim_start_matches("data:"), data),
+    None => (IconFormat::Png.mime(), icon_info.data.as_str()),
+  };
+  Ok(IconData::new(
+    BASE64_STANDARD.decode(data)?,
+    mime.to_owned(),
+    icon_info.height,
+    icon_info.width,
+  ))
+}
+
+/**
+ * Convert raw bytes of an icon to `format` when the mime type is different
+ */
+pub fn convert_icon_data(icon_data: IconData, format: IconFormat) -> Result<IconData> {
+  if icon_data.data.is_empty() || icon_data.mime.eq(format.mime()) {
+    return Ok(icon_data);
+  }
+
+  let image = if icon_data.mime.eq(IconFormat::RawRgba.mime()) {
+    RgbaImage::from_raw(icon_data.width, icon_data.height, icon_data.data)
+      .ok_or("Icon buffer does not match icon size!")?
+  } else {
+    image::load_from_memory(&icon_data.data)?.to_rgba8()
+  };
+  let (width, height) = image.dimensions();
+  if width == 0 || height == 0 {
+    return Ok(empty_icon_data());
+  }
+  encode_icon(image.into_raw(), width, height, format)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{convert_icon_data, encode_icon, icon_info_to_data};
+  use crate::common::x_win_struct::icon_options::IconFormat;
+
+  #[test]
+  fn test_encode_icon_formats() -> Result<(), Box<dyn std::error::Error>> {
+    let rgba = vec![255u8; 2 * 2 * 4];
+
+    let png = encode_icon(rgba.clone(), 2, 2, IconFormat::Png)?;
+    assert_eq!(png.mime, "image/png");
+    assert!(png.to_data_uri().starts_with("data:image/png;base64,"));
+    assert_eq!((png.width, png.height), (2, 2));
+
+    let webp = encode_icon(rgba.clone(), 2, 2, IconFormat::Webp)?;
+    assert!(webp.to_data_uri().starts_with("data:image/webp;base64,"));
+
+    let raw = encode_icon(rgba.clone(), 2, 2, IconFormat::RawRgba)?;
+    assert_eq!(raw.data, rgba);
+    assert_eq!(raw.to_icon_info(false).data, "/////////////////////w==");
+    Ok(())
+  }
+
+  #[test]
+  fn test_encode_icon_wrong_size() {
+    let rgba = vec![255u8; 3];
+    assert!(encode_icon(rgba.clone(), 2, 2, IconFormat::Png).is_err());
+    assert!(encode_icon(rgba, 2, 2, IconFormat::RawRgba).is_err());
+  }
+
+  #[test]
+  fn test_icon_info_to_data() -> Result<(), Box<dyn std::error::Error>> {
+    let png = encode_icon(vec![0u8; 4 * 4 * 4], 4, 4, IconFormat::Png)?;
+    let icon_data = icon_info_to_data(&png.clone().into())?;
+    assert_eq!(icon_data, png);
+
+    let webp = convert_icon_data(icon_data, IconFormat::Webp)?;
+    assert_eq!(webp.mime, "image/webp");
+    assert_eq!((webp.width, webp.height), (4, 4));
+
+    let raw = convert_icon_data(webp, IconFormat::RawRgba)?;
+    assert_eq!(raw.data, vec![0u8; 4 * 4 * 4]);
+    Ok(())
+  }
+}
@@ -0,0 +1,381 @@
+#![deny(unused_imports)]
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use super::{
+  api::Api,
+  hash::fnv1a_64,
+  result::Result,
+  x_win_struct::{
+    icon_data::IconData,
+    icon_options::{IconFormat, IconOptions},
+    window_info::WindowInfo,
+  },
+};
+
+const ICON_CACHE_FILE_EXTENSION: &str = "xwin-icon";
+
+/**
+ * Options of the icon cache
+ */
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct IconCacheOptions {
+  pub enabled: bool,
+  /**
+   * Maximum number of icons kept in memory
+   */
+  pub capacity: usize,
+  /**
+   * Optional directory where icons are also stored to be reused between runs
+   */
+  pub directory: Option<PathBuf>,
+}
+
+impl IconCacheOptions {
+  pub fn new(enabled: bool, capacity: usize, directory: Option<PathBuf>) -> Self {
+    Self {
+      enabled,
+      capacity,
+      directory,
+    }
+  }
+}
+
+impl Default for IconCacheOptions {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      capacity: 256,
+      directory: None,
+    }
+  }
+}
+
+/**
+ * Icons are shared by the windows of an application, given by its executable and its app id.
+ * Interpreters and browsers run applications with different icons from the same executable.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct IconCacheKey {
+  path: String,
+  /**
+   * WM_CLASS on X11, app id on Wayland
+   */
+  app_id: String,
+  size: u32,
+  format: IconFormat,
+}
+
+impl IconCacheKey {
+  /**
+   * Windows without executable path, like host windows seen from Flatpak, are kept by app id only
+   */
+  fn new(window_info: &WindowInfo, options: &IconOptions) -> Option<Self> {
+    if window_info.info.path.is_empty() && window_info.info.name.is_empty() {
+      return None;
+    }
+    Some(Self {
+      path: window_info.info.path.to_owned(),
+      app_id: window_info.info.name.to_owned(),
+      size: options.preferred_size,
+      format: options.format,
+    })
+  }
+
+  /**
+   * Hash of the key, stable between runs to be used as file name
+   */
+  fn file_name(&self) -> String {
+    let key = format!(
+      "{}\u{0}{}\u{0}{}\u{0}{}",
+      self.path,
+      self.app_id,
+      self.size,
+      self.format.mime()
+    );
+    format!(
+      "{:016x}.{ICON_CACHE_FILE_EXTENSION}",
+      fnv1a_64(key.as_bytes())
+    )
+  }
+}
+
+/**
+ * Least recently used cache of icons, owned by each `XWin` instance
+ */
+pub(crate) struct IconCache {
+  options: IconCacheOptions,
+  entries: HashMap<IconCacheKey, (IconData, u64)>,
+  /**
+   * Keys ordered by last use, the first one is evicted first
+   */
+  recent: BTreeMap<u64, IconCacheKey>,
+  tick: u64,
+}
+
+impl IconCache {
+  pub(crate) fn new(options: IconCacheOptions) -> Self {
+    Self {
+      options,
+      entries: HashMap::new(),
+      recent: BTreeMap::new(),
+      tick: 0,
+    }
+  }
+
+  fn get(&mut self, key: &IconCacheKey) -> Option<IconData> {
+    self.tick += 1;
+    let tick = self.tick;
+    if let Some((icon_data, last_used)) = self.entries.get_mut(key) {
+      if let Some(key) = self.recent.remove(last_used) {
+        self.recent.insert(tick, key);
+      }
+      *last_used = tick;
+      return Some(icon_data.clone());
+    }
+    let icon_data = read_icon_file(self.options.directory.as_ref()?, key)?;
+    self.insert_in_memory(key.clone(), icon_data.clone());
+    Some(icon_data)
+  }
+
+  fn insert(&mut self, key: IconCacheKey, icon_data: IconData) {
+    if let Some(directory) = &self.options.directory {
+      let _ = write_icon_file(directory, &key, &icon_data);
+    }
+    self.insert_in_memory(key, icon_data);
+  }
+
+  fn insert_in_memory(&mut self, key: IconCacheKey, icon_data: IconData) {
+    self.tick += 1;
+    self.recent.insert(self.tick, key.clone());
+    if let Some((_, last_used)) = self.entries.insert(key, (icon_data, self.tick)) {
+      self.recent.remove(&last_used);
+    }
+    self.evict();
+  }
+
+  fn evict(&mut self) {
+    while self.entries.len() > self.options.capacity {
+      let oldest = self.recent.keys().next().copied();
+      match oldest.and_then(|last_used| self.recent.remove(&last_used)) {
+        Some(oldest) => self.entries.remove(&oldest),
+        None => break,
+      };
+    }
+  }
+
+  /**
+   * Change options of the cache, least recently used icons are dropped if capacity is reduced
+   */
+  pub(crate) fn set_options(&mut self, options: IconCacheOptions) {
+    self.options = options;
+    self.evict();
+  }
+
+  /**
+   * Remove all icons from memory and from the cache directory
+   */
+  pub(crate) fn clear(&mut self) -> Result<()> {
+    self.entries.clear();
+    self.recent.clear();
+    if let Some(directory) = &self.options.directory {
+      if directory.is_dir() {
+        for entry in fs::read_dir(directory)? {
+          let path = entry?.path();
+          if path
+            .extension()
+            .map(|extension| extension.eq(ICON_CACHE_FILE_EXTENSION))
+            .unwrap_or(false)
+          {
+            fs::remove_file(path)?;
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/**
+ * Icon file is stored as a `<mime> <width> <height>` line followed by icon bytes.
+ * It is written into a temporary file renamed once complete, other processes sharing the directory never read a partial icon.
+ */
+fn write_icon_file(directory: &Path, key: &IconCacheKey, icon_data: &IconData) -> Result<()> {
+  fs::create_dir_all(directory)?;
+  let mut content: Vec<u8> = format!(
+    "{} {} {}\n",
+    icon_data.mime, icon_data.width, icon_data.height
+  )
+  .into_bytes();
+  content.extend_from_slice(&icon_data.data);
+  let path = directory.join(key.file_name());
+  let temporary_path = path.with_extension(format!("{}.tmp", std::process::id()));
+  fs::write(&temporary_path, content)
+    .and_then(|_| fs::rename(&temporary_path, &path))
+    .map_err(|err| {
+      let _ = fs::remove_file(&temporary_path);
+      err
+    })?;
+  Ok(())
+}
+
+fn read_icon_file(directory: &Path, key: &IconCacheKey) -> Option<IconData> {
+  let content = fs::read(directory.join(key.file_name())).ok()?;
+  let header_end = content.iter().position(|byte| *byte == b'\n')?;
+  let header = std::str::from_utf8(&content[..header_end]).ok()?;
+  let mut header = header.split(' ');
+  let mime = header.next()?.to_owned();
+  let width: u32 = header.next()?.parse().ok()?;
+  let height: u32 = header.next()?.parse().ok()?;
+  Some(IconData::new(
+    content[header_end + 1..].to_vec(),
+    mime,
+    height,
+    width,
+  ))
+}
+
+/**
+ * Return icon from `icon_cache` or recover it with `api` and store it
+ */
+pub(crate) fn get_icon_data<A: Api + ?Sized>(
+  icon_cache: &Mutex<IconCache>,
+  api: &A,
+  window_info: &WindowInfo,
+  options: &IconOptions,
+) -> Result<IconData> {
+  let key = match icon_cache.lock() {
+    Ok(mut icon_cache) if icon_cache.options.enabled && icon_cache.options.capacity > 0 => {
+      IconCacheKey::new(window_info, options).map(|key| (icon_cache.get(&key), key))
+    }
+    _ => None,
+  };
+
+  match key {
+    Some((Some(icon_data), _)) => Ok(icon_data),
+    Some((None, key)) => {
+      let icon_data = api.get_app_icon_data(window_info, options)?;
+      if !icon_data.data.is_empty() {
+        if let Ok(mut icon_cache) = icon_cache.lock() {
+          icon_cache.insert(key, icon_data.clone());
+        }
+      }
+      Ok(icon_data)
+    }
+    None => api.get_app_icon_data(window_info, options),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{read_icon_file, write_icon_file, IconCache, IconCacheKey, IconCacheOptions};
+  use crate::common::{
+    api::empty_entity,
+    x_win_struct::{
+      icon_data::IconData,
+      icon_options::{IconFormat, IconOptions},
+    },
+  };
+
+  fn key(path: &str) -> IconCacheKey {
+    IconCacheKey {
+      path: path.to_owned(),
+      app_id: String::from("app"),
+      size: 32,
+      format: IconFormat::Png,
+    }
+  }
+
+  fn icon(value: u8) -> IconData {
+    IconData::new(vec![value], String::from("image/png"), 1, 1)
+  }
+
+  #[test]
+  fn test_icon_cache_lru() {
+    let mut icon_cache = IconCache::new(IconCacheOptions::new(true, 2, None));
+
+    icon_cache.insert(key("/a"), icon(1));
+    icon_cache.insert(key("/b"), icon(2));
+    assert_eq!(icon_cache.get(&key("/a")), Some(icon(1)));
+
+    icon_cache.insert(key("/c"), icon(3));
+    assert_eq!(icon_cache.get(&key("/b")), None);
+    assert_eq!(icon_cache.get(&key("/a")), Some(icon(1)));
+    assert_eq!(icon_cache.get(&key("/c")), Some(icon(3)));
+
+    // Replacing an icon does not leave its previous use behind
+    icon_cache.insert(key("/a"), icon(4));
+    icon_cache.insert(key("/d"), icon(5));
+    assert_eq!(icon_cache.entries.len(), icon_cache.recent.len());
+    assert_eq!(icon_cache.get(&key("/c")), None);
+    assert_eq!(icon_cache.get(&key("/a")), Some(icon(4)));
+  }
+
+  #[test]
+  fn test_icon_cache_key() {
+    let mut code = empty_entity();
+    code.info.path = String::from("/usr/share/code/code");
+    code.info.name = String::from("code");
+    let mut other = code.clone();
+    other.info.path = String::from("/opt/code/bin/code");
+    let options = IconOptions::default();
+    assert_ne!(
+      IconCacheKey::new(&code, &options),
+      IconCacheKey::new(&other, &options)
+    );
+
+    // Applications sharing an executable keep their own icon
+    let mut idea = empty_entity();
+    idea.info.path = String::from("/usr/lib/jvm/bin/java");
+    idea.info.name = String::from("jetbrains-idea");
+    let mut dbeaver = idea.clone();
+    dbeaver.info.name = String::from("DBeaver");
+    let (idea_key, dbeaver_key) = (
+      IconCacheKey::new(&idea, &options),
+      IconCacheKey::new(&dbeaver, &options),
+    );
+    assert_ne!(idea_key, dbeaver_key);
+    assert_ne!(
+      idea_key.map(|key| key.file_name()),
+      dbeaver_key.map(|key| key.file_name())
+    );
+
+    // Windows without executable path are kept by app id
+    code.info.path = String::new();
+    assert!(IconCacheKey::new(&code, &options).is_some());
+    code.info.name = String::new();
+    assert_eq!(IconCacheKey::new(&code, &options), None);
+  }
+
+  #[test]
+  fn test_icon_cache_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = std::env::temp_dir().join(format!("x-win-icon-cache-{}", std::process::id()));
+    let icon_data = IconData::new(vec![1, 2, 10, 3], String::from("image/webp"), 16, 8);
+    write_icon_file(&directory, &key("/a"), &icon_data)?;
+    // Only the renamed file is left in the directory
+    assert_eq!(std::fs::read_dir(&directory)?.count(), 1);
+    assert_eq!(
+      read_icon_file(&directory, &key("/a")),
+      Some(icon_data.clone())
+    );
+    assert_eq!(read_icon_file(&directory, &key("/b")), None);
+
+    let mut icon_cache = IconCache::new(IconCacheOptions::new(true, 2, Some(directory.clone())));
+    assert_eq!(icon_cache.get(&key("/a")), Some(icon_data));
+    icon_cache.clear()?;
+    assert_eq!(icon_cache.get(&key("/a")), None);
+
+    std::fs::remove_dir_all(directory)?;
+    Ok(())
+  }
+}
@@ -1,6 +1,11 @@
 #![deny(unused_imports)]
 
 pub mod api;
+pub mod browser;
 pub mod error;
+pub mod hash;
+pub mod icon;
+pub mod icon_cache;
+pub mod redaction;
 pub mod result;
 pub mod x_win_struct;
@@ -0,0 +1,590 @@
+#![deny(unused_imports)]
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, RwLock},
+};
+
+use once_cell::sync::Lazy;
+use regex_lite::Regex;
+
+use super::{
+  api::empty_entity,
+  browser::is_private_title,
+  hash::fnv1a_64,
+  result::Result,
+  x_win_struct::{browser_info::BrowserInfo, window_info::WindowInfo},
+};
+
+/**
+ * Title given to windows marked with `RedactionAction::MarkPrivate`, still detected as private browsing
+ */
+pub const PRIVATE_TITLE: &str = "Private browsing";
+
+/**
+ * Maximum number of windows remembered with their original title, the least recently redacted one is forgotten first
+ */
+const ORIGINALS_CAPACITY: usize = 256;
+
+/**
+ * Windows a `RedactionRule` applies to
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RuleMatch {
+  Any,
+  /**
+   * Application name or executable name, case insensitive
+   */
+  App(String),
+  /**
+   * Regular expression searched in the title
+   */
+  Title(String),
+  /**
+   * Private browsing, incognito or InPrivate window guessed from the title
+   */
+  PrivateBrowsing,
+}
+
+/**
+ * What a `RedactionRule` does to the windows it applies to
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RedactionAction {
+  /**
+   * Remove the window from open windows, the active window becomes an empty window with the id `0`
+   */
+  DropWindow,
+  /**
+   * Replace the title with its FNV-1a hash, equal titles keep equal hashes
+   */
+  HashTitle,
+  /**
+   * Keep the first characters of the title
+   */
+  TruncateTitle(usize),
+  /**
+   * Remove the query and the fragment of the browser url
+   */
+  StripUrlQuery,
+  /**
+   * Keep the scheme and the host of the browser url
+   */
+  StripUrlPath,
+  /**
+   * Replace the title with `PRIVATE_TITLE`, the browser url and tab title are emptied and `is_private` is set
+   */
+  MarkPrivate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct RedactionRule {
+  pub matches: RuleMatch,
+  pub action: RedactionAction,
+}
+
+impl RedactionRule {
+  pub fn new(matches: RuleMatch, action: RedactionAction) -> Self {
+    Self { matches, action }
+  }
+}
+
+/**
+ * Titles of the windows returned by a `Redaction`, by window id
+ */
+#[derive(Debug, Default)]
+struct Originals {
+  /**
+   * Last use, redacted title and original title
+   */
+  titles: HashMap<u32, (u64, String, String)>,
+  tick: u64,
+}
+
+impl Originals {
+  fn insert(&mut self, id: u32, redacted: String, original: String) {
+    self.tick += 1;
+    if !self.titles.contains_key(&id) && self.titles.len() >= ORIGINALS_CAPACITY {
+      let oldest = self
+        .titles
+        .iter()
+        .min_by_key(|(_, (last_used, _, _))| *last_used)
+        .map(|(id, _)| *id);
+      if let Some(oldest) = oldest {
+        self.titles.remove(&oldest);
+      }
+    }
+    self.titles.insert(id, (self.tick, redacted, original));
+  }
+
+  fn get(&self, id: u32, redacted: &str) -> Option<&str> {
+    self
+      .titles
+      .get(&id)
+      .filter(|(_, title, _)| title.eq(redacted))
+      .map(|(_, _, original)| original.as_str())
+  }
+}
+
+/**
+ * Rules with their compiled title expressions.
+ * Every rule matching a window is applied in order, rules are matched on the window as recovered from the desktop.
+ * Titles of returned windows are remembered, browser urls of a window are matched on its original title.
+ */
+#[derive(Debug, Clone)]
+pub struct Redaction {
+  rules: Vec<(RedactionRule, Option<Regex>)>,
+  originals: Arc<Mutex<Originals>>,
+}
+
+impl Redaction {
+  /**
+   * Return an error when the expression of a `RuleMatch::Title` is not valid
+   */
+  pub fn new(rules: Vec<RedactionRule>) -> Result<Self> {
+    let rules = rules
+      .into_iter()
+      .map(|rule| {
+        let regex = match &rule.matches {
+          RuleMatch::Title(pattern) => Some(Regex::new(pattern)?),
+          _ => None,
+        };
+        Ok((rule, regex))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Self {
+      rules,
+      originals: Arc::new(Mutex::new(Originals::default())),
+    })
+  }
+
+  pub fn rules(&self) -> Vec<RedactionRule> {
+    self.rules.iter().map(|(rule, _)| rule.clone()).collect()
+  }
+
+  /**
+   * Return the redacted window, `None` when it is dropped
+   */
+  pub fn redact_window(&self, window: WindowInfo) -> Option<WindowInfo> {
+    let mut redacted = window.clone();
+    for action in self.actions(&window) {
+      match action {
+        RedactionAction::DropWindow => return None,
+        RedactionAction::HashTitle => {
+          redacted.title = format!("{:016x}", fnv1a_64(redacted.title.as_bytes()))
+        }
+        RedactionAction::TruncateTitle(length) => {
+          redacted.title = redacted.title.chars().take(*length).collect()
+        }
+        RedactionAction::MarkPrivate => redacted.title = String::from(PRIVATE_TITLE),
+        RedactionAction::StripUrlQuery | RedactionAction::StripUrlPath => {}
+      }
+    }
+    if let Ok(mut originals) = self.originals.lock() {
+      originals.insert(window.id, redacted.title.clone(), window.title);
+    }
+    Some(redacted)
+  }
+
+  /**
+   * `window` with its title before redaction, `None` when this instance did not return a window with this id and title.
+   * Position and usage of the window are not compared, they change between calls.
+   */
+  pub fn original(&self, window: &WindowInfo) -> Option<WindowInfo> {
+    let originals = self.originals.lock().ok()?;
+    let title = originals.get(window.id, &window.title)?;
+    let mut original = window.clone();
+    original.title = title.to_owned();
+    Some(original)
+  }
+
+  /**
+   * Active window with dropped windows replaced by an empty window
+   */
+  pub fn redact_active_window(&self, window: WindowInfo) -> WindowInfo {
+    self.redact_window(window).unwrap_or_else(empty_entity)
+  }
+
+  pub fn redact_windows(&self, windows: Vec<WindowInfo>) -> Vec<WindowInfo> {
+    windows
+      .into_iter()
+      .filter_map(|window| self.redact_window(window))
+      .collect()
+  }
+
+  /**
+   * Return the redacted browser information of `window`, `None` when the window is dropped.
+   * Rules are matched on the original title of `window`. When it is unknown and rules may have changed the title,
+   * every title rule is applied as if it matched.
+   */
+  pub fn redact_browser(&self, window: &WindowInfo, browser: BrowserInfo) -> Option<BrowserInfo> {
+    let original = self.original(window);
+    let actions: Vec<&RedactionAction> = match &original {
+      Some(original) => self.actions(original).collect(),
+      None if self.changes_titles() => self.actions_unknown_title(window).collect(),
+      None => self.actions(window).collect(),
+    };
+    let mut redacted = browser;
+    for action in actions {
+      match action {
+        RedactionAction::DropWindow => return None,
+        RedactionAction::HashTitle => {
+          redacted.tab_title = format!("{:016x}", fnv1a_64(redacted.tab_title.as_bytes()))
+        }
+        RedactionAction::TruncateTitle(length) => {
+          redacted.tab_title = redacted.tab_title.chars().take(*length).collect()
+        }
+        RedactionAction::StripUrlQuery => redacted.url = strip_url_query(&redacted.url),
+        RedactionAction::StripUrlPath => redacted.url = strip_url_path(&redacted.url),
+        RedactionAction::MarkPrivate => {
+          redacted.is_private = true;
+          redacted.url = String::new();
+          redacted.tab_title = String::new();
+        }
+      }
+    }
+    Some(redacted)
+  }
+
+  /**
+   * True when a rule matched on the title changes the title, a redacted title can then differ from the original one
+   */
+  fn changes_titles(&self) -> bool {
+    self.rules.iter().any(|(rule, _)| {
+      matches!(
+        rule.matches,
+        RuleMatch::Title(_) | RuleMatch::PrivateBrowsing
+      ) && matches!(
+        rule.action,
+        RedactionAction::HashTitle
+          | RedactionAction::TruncateTitle(_)
+          | RedactionAction::MarkPrivate
+      )
+    })
+  }
+
+  /**
+   * Actions of `window` when its original title is unknown, rules matched on the title are all applied
+   */
+  fn actions_unknown_title<'a>(
+    &'a self,
+    window: &'a WindowInfo,
+  ) -> impl Iterator<Item = &'a RedactionAction> {
+    self
+      .rules
+      .iter()
+      .filter(move |(rule, regex)| {
+        matches!(
+          rule.matches,
+          RuleMatch::Title(_) | RuleMatch::PrivateBrowsing
+        ) || rule_matches(rule, regex, window)
+      })
+      .map(|(rule, _)| &rule.action)
+  }
+
+  fn actions<'a>(&'a self, window: &'a WindowInfo) -> impl Iterator<Item = &'a RedactionAction> {
+    self
+      .rules
+      .iter()
+      .filter(move |(rule, regex)| rule_matches(rule, regex, window))
+      .map(|(rule, _)| &rule.action)
+  }
+}
+
+fn rule_matches(rule: &RedactionRule, regex: &Option<Regex>, window: &WindowInfo) -> bool {
+  match &rule.matches {
+    RuleMatch::Any => true,
+    RuleMatch::App(name) => {
+      window.info.name.eq_ignore_ascii_case(name)
+        || window.info.exec_name.eq_ignore_ascii_case(name)
+    }
+    RuleMatch::Title(_) => regex
+      .as_ref()
+      .map(|regex| regex.is_match(&window.title))
+      .unwrap_or(false),
+    RuleMatch::PrivateBrowsing => is_private_title(&window.title),
+  }
+}
+
+/**
+ * Remove `?query` and `#fragment`
+ */
+fn strip_url_query(url: &str) -> String {
+  match url.find(['?', '#']) {
+    Some(index) => url[..index].to_owned(),
+    None => url.to_owned(),
+  }
+}
+
+/**
+ * Keep `scheme://host`, urls without scheme like `about:blank` lose their query only
+ */
+fn strip_url_path(url: &str) -> String {
+  match url.find("://") {
+    Some(scheme_end) => {
+      let host_start = scheme_end + 3;
+      match url[host_start..].find(['/', '?', '#']) {
+        Some(index) => url[..host_start + index].to_owned(),
+        None => url.to_owned(),
+      }
+    }
+    None => strip_url_query(url),
+  }
+}
+
+static REDACTION: Lazy<RwLock<Option<Arc<Redaction>>>> = Lazy::new(|| RwLock::new(None));
+
+/**
+ * Apply `rules` to windows and browser urls returned by the free functions and by `XWin` instances built without their own rules.
+ * Rules are cleared with an empty list.
+ */
+pub fn set_redaction_rules(rules: Vec<RedactionRule>) -> Result<()> {
+  let redaction = match rules.is_empty() {
+    true => None,
+    false => Some(Arc::new(Redaction::new(rules)?)),
+  };
+  *REDACTION.write().map_err(|err| err.to_string())? = redaction;
+  Ok(())
+}
+
+/**
+ * Rules set with `set_redaction_rules`
+ */
+pub fn redaction_rules() -> Vec<RedactionRule> {
+  global_redaction()
+    .map(|redaction| redaction.rules())
+    .unwrap_or_default()
+}
+
+pub fn global_redaction() -> Option<Arc<Redaction>> {
+  REDACTION
+    .read()
+    .map(|redaction| redaction.clone())
+    .unwrap_or_else(|err| err.into_inner().clone())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    strip_url_path, strip_url_query, Redaction, RedactionAction, RedactionRule, RuleMatch,
+    ORIGINALS_CAPACITY, PRIVATE_TITLE,
+  };
+  use crate::common::{
+    api::empty_entity,
+    browser::is_private_title,
+    x_win_struct::{
+      browser_info::{BrowserInfo, BrowserKind},
+      window_info::WindowInfo,
+    },
+  };
+
+  fn window(id: u32, name: &str, exec_name: &str, title: &str) -> WindowInfo {
+    let mut window = empty_entity();
+    window.id = id;
+    window.info.name = name.to_owned();
+    window.info.exec_name = exec_name.to_owned();
+    window.title = title.to_owned();
+    window
+  }
+
+  #[test]
+  fn test_redact_window() -> Result<(), Box<dyn std::error::Error>> {
+    let redaction = Redaction::new(vec![
+      RedactionRule::new(
+        RuleMatch::App(String::from("keepassxc")),
+        RedactionAction::DropWindow,
+      ),
+      RedactionRule::new(
+        RuleMatch::Title(String::from(r"(?i)inbox|@\w+\.com")),
+        RedactionAction::HashTitle,
+      ),
+      RedactionRule::new(
+        RuleMatch::App(String::from("Code")),
+        RedactionAction::TruncateTitle(5),
+      ),
+    ])?;
+    let windows = redaction.redact_windows(vec![
+      window(1, "KeePassXC", "keepassxc", "Passwords.kdbx"),
+      window(2, "Thunderbird", "thunderbird", "Inbox - Mail"),
+      window(3, "Code", "code", "x-win - README.md"),
+      window(4, "Terminal", "gnome-terminal", "vim"),
+    ]);
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[0].title.len(), 16);
+    assert_ne!(windows[0].title, "Inbox - Mail");
+    assert_eq!(windows[1].title, "x-win");
+    assert_eq!(windows[2].title, "vim");
+
+    let password = window(1, "KeePassXC", "keepassxc", "Passwords.kdbx");
+    assert_eq!(redaction.redact_active_window(password), empty_entity());
+    assert!(Redaction::new(vec![RedactionRule::new(
+      RuleMatch::Title(String::from("(")),
+      RedactionAction::HashTitle
+    )])
+    .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_redact_browser() -> Result<(), Box<dyn std::error::Error>> {
+    let redaction = Redaction::new(vec![
+      RedactionRule::new(
+        RuleMatch::App(String::from("firefox")),
+        RedactionAction::StripUrlQuery,
+      ),
+      RedactionRule::new(RuleMatch::PrivateBrowsing, RedactionAction::MarkPrivate),
+    ])?;
+    let firefox = window(1, "Firefox", "firefox", "Search — Mozilla Firefox");
+    let browser = BrowserInfo::new(
+      String::from("https://duckduckgo.com/?q=x-win#top"),
+      BrowserKind::Firefox,
+      false,
+      String::from("Search"),
+    );
+    let firefox = redaction
+      .redact_window(firefox)
+      .ok_or("firefox window dropped")?;
+    let redacted = redaction.redact_browser(&firefox, browser.clone());
+    assert_eq!(
+      redacted.map(|browser| browser.url),
+      Some(String::from("https://duckduckgo.com/"))
+    );
+
+    let private = window(
+      2,
+      "Firefox",
+      "firefox",
+      "Bank — Mozilla Firefox Private Browsing",
+    );
+    let redacted = redaction
+      .redact_browser(&private, browser)
+      .ok_or("private window dropped")?;
+    assert!(redacted.is_private && redacted.url.is_empty() && redacted.tab_title.is_empty());
+    let redacted = redaction
+      .redact_window(private)
+      .ok_or("private window dropped")?;
+    assert_eq!(redacted.title, PRIVATE_TITLE);
+    assert!(is_private_title(&redacted.title));
+    Ok(())
+  }
+
+  #[test]
+  fn test_redact_browser_of_redacted_window() -> Result<(), Box<dyn std::error::Error>> {
+    let redaction = Redaction::new(vec![
+      RedactionRule::new(
+        RuleMatch::Title(String::from("Inbox")),
+        RedactionAction::HashTitle,
+      ),
+      RedactionRule::new(
+        RuleMatch::Title(String::from("Inbox")),
+        RedactionAction::StripUrlPath,
+      ),
+    ])?;
+    let mail = window(1, "Firefox", "firefox", "Inbox — Mozilla Firefox");
+    let browser = BrowserInfo::new(
+      String::from("https://mail.example.com/u/0/inbox"),
+      BrowserKind::Firefox,
+      false,
+      String::from("Inbox"),
+    );
+    let redacted = redaction
+      .redact_windows(vec![mail.clone()])
+      .pop()
+      .ok_or("mail window dropped")?;
+    assert_ne!(redacted.title, mail.title);
+    assert_eq!(redaction.original(&redacted), Some(mail.clone()));
+
+    // Usage changes between the listing and the url request
+    let mut polled = redacted.clone();
+    polled.usage.memory = 4096;
+    assert_eq!(
+      redaction
+        .redact_browser(&polled, browser.clone())
+        .map(|browser| browser.url),
+      Some(String::from("https://mail.example.com"))
+    );
+
+    // A window unknown to this instance is matched with every title rule
+    let mut unknown = redacted.clone();
+    unknown.id = 2;
+    assert_eq!(redaction.original(&unknown), None);
+    assert_eq!(
+      redaction
+        .redact_browser(&unknown, browser.clone())
+        .map(|browser| browser.url),
+      Some(String::from("https://mail.example.com"))
+    );
+
+    // Other windows listed since are forgotten first
+    redaction.redact_windows(
+      (10..10 + ORIGINALS_CAPACITY as u32)
+        .map(|id| window(id, "Terminal", "gnome-terminal", "vim"))
+        .collect(),
+    );
+    assert_eq!(redaction.original(&redacted), None);
+    redaction.redact_window(mail.clone());
+    redaction.redact_window(window(10_000, "Terminal", "gnome-terminal", "vim"));
+    assert_eq!(redaction.original(&redacted), Some(mail));
+    Ok(())
+  }
+
+  #[test]
+  fn test_redact_browser_of_unknown_window() -> Result<(), Box<dyn std::error::Error>> {
+    let browser = BrowserInfo::new(
+      String::from("https://example.com/docs?q=1"),
+      BrowserKind::Firefox,
+      false,
+      String::from("Docs"),
+    );
+    let firefox = window(1, "Firefox", "firefox", "Docs — Mozilla Firefox");
+
+    // Titles are never changed, windows are matched as given
+    let redaction = Redaction::new(vec![RedactionRule::new(
+      RuleMatch::Title(String::from("Inbox")),
+      RedactionAction::StripUrlPath,
+    )])?;
+    assert_eq!(
+      redaction.redact_browser(&firefox, browser.clone()),
+      Some(browser.clone())
+    );
+
+    let redaction = Redaction::new(vec![
+      RedactionRule::new(
+        RuleMatch::Title(String::from("Bank")),
+        RedactionAction::DropWindow,
+      ),
+      RedactionRule::new(
+        RuleMatch::Title(String::from("Inbox")),
+        RedactionAction::TruncateTitle(3),
+      ),
+    ])?;
+    assert_eq!(redaction.redact_browser(&firefox, browser.clone()), None);
+    redaction.redact_window(firefox.clone());
+    assert_eq!(
+      redaction.redact_browser(&firefox, browser.clone()),
+      Some(browser)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_strip_url() {
+    assert_eq!(
+      strip_url_path("https://mail.example.com/u/0/inbox?id=1"),
+      "https://mail.example.com"
+    );
+    assert_eq!(strip_url_path("https://example.com"), "https://example.com");
+    assert_eq!(strip_url_path("about:blank?x=1"), "about:blank");
+    assert_eq!(
+      strip_url_query("https://example.com/docs#private"),
+      "https://example.com/docs"
+    );
+  }
+}
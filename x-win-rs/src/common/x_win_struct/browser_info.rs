@@ -0,0 +1,91 @@
+#![deny(unused_imports)]
+
+/**
+ * Family of the browser owning a window
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrowserKind {
+  Chrome,
+  Chromium,
+  Edge,
+  Firefox,
+  Safari,
+  Opera,
+  Brave,
+  Vivaldi,
+  Arc,
+  /**
+   * Any other known browser, with its executable name or bundle identifier
+   */
+  Other(String),
+}
+
+impl BrowserKind {
+  /**
+   * Find browser from executable name (Windows and Linux)
+   */
+  pub fn from_exec_name(exec_name: &str) -> Self {
+    match exec_name.to_lowercase().as_str() {
+      "chrome" | "google-chrome" => BrowserKind::Chrome,
+      "chromium" | "chromium-browser" => BrowserKind::Chromium,
+      "msedge" | "microsoft-edge" => BrowserKind::Edge,
+      "firefox" | "firefox-bin" | "firefox-esr" => BrowserKind::Firefox,
+      "opera" | "opera_gx" => BrowserKind::Opera,
+      "brave" | "brave-browser" => BrowserKind::Brave,
+      "vivaldi" | "vivaldi-bin" => BrowserKind::Vivaldi,
+      _ => BrowserKind::Other(exec_name.to_owned()),
+    }
+  }
+
+  /**
+   * Find browser from application bundle identifier (Darwin)
+   */
+  pub fn from_bundle_id(bundle_id: &str) -> Self {
+    match bundle_id {
+      x if x.starts_with("com.apple.Safari") => BrowserKind::Safari,
+      x if x.starts_with("com.google.Chrome") => BrowserKind::Chrome,
+      x if x.starts_with("org.chromium.") => BrowserKind::Chromium,
+      x if x.starts_with("com.microsoft.edgemac") => BrowserKind::Edge,
+      x if x.starts_with("org.mozilla.firefox") => BrowserKind::Firefox,
+      x if x.starts_with("com.operasoftware.") => BrowserKind::Opera,
+      x if x.starts_with("com.brave.Browser") => BrowserKind::Brave,
+      "com.vivaldi.Vivaldi" => BrowserKind::Vivaldi,
+      "company.thebrowser.Browser" => BrowserKind::Arc,
+      _ => BrowserKind::Other(bundle_id.to_owned()),
+    }
+  }
+}
+
+/**
+ * Struct to store information about the page displayed by a browser window
+ */
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct BrowserInfo {
+  pub url: String,
+  pub browser: BrowserKind,
+  /**
+   * Guessed from the window title (private browsing, incognito or InPrivate window)
+   */
+  pub is_private: bool,
+  /**
+   * Title of the active tab, the window title without the browser name
+   */
+  pub tab_title: String,
+}
+
+impl BrowserInfo {
+  pub fn new(url: String, browser: BrowserKind, is_private: bool, tab_title: String) -> Self {
+    Self {
+      url,
+      browser,
+      is_private,
+      tab_title,
+    }
+  }
+}
@@ -0,0 +1,92 @@
+#![deny(unused_imports)]
+
+/**
+ * Backend used to recover windows
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backend {
+  Win32,
+  MacOS,
+  X11,
+  /**
+   * GNOME Shell < 41 evaluating scripts over D-Bus
+   */
+  GnomeShellEval,
+  /**
+   * GNOME Shell >= 41 with the "x-win@miniben90.org" extension
+   */
+  GnomeShellExtension,
+  /**
+   * `FakeApi` of the `testing` feature playing a timeline
+   */
+  Fake,
+  /**
+   * No backend can recover windows in this session, like a Wayland compositor other than GNOME Shell
+   */
+  Unsupported,
+}
+
+/**
+ * Features available with the detected backend
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Capabilities {
+  pub backend: Backend,
+  pub active_window: bool,
+  pub open_windows: bool,
+  pub icons: bool,
+  pub browser_url: bool,
+  /**
+   * Changes of windows are notified by the system instead of being polled
+   */
+  pub events: bool,
+  /**
+   * Windows can be focused, moved or closed
+   */
+  pub window_control: bool,
+  /**
+   * Workspace of windows is known
+   */
+  pub workspaces: bool,
+  /**
+   * The backend needs the GNOME extension and `install_extension` can install it
+   */
+  pub extension_install: bool,
+}
+
+impl Capabilities {
+  /**
+   * Capabilities of `backend` with every feature disabled
+   */
+  pub fn new(backend: Backend) -> Self {
+    Self {
+      backend,
+      active_window: false,
+      open_windows: false,
+      icons: false,
+      browser_url: false,
+      events: false,
+      window_control: false,
+      workspaces: false,
+      extension_install: false,
+    }
+  }
+
+  /**
+   * Capabilities of `backend` able to recover windows and their icons
+   */
+  pub fn with_windows(backend: Backend) -> Self {
+    Self {
+      active_window: true,
+      open_windows: true,
+      icons: true,
+      ..Self::new(backend)
+    }
+  }
+}
@@ -0,0 +1,68 @@
+#![deny(unused_imports)]
+
+use super::extension_status::ExtensionStatus;
+
+/**
+ * Kind of graphical session used to recover windows
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionType {
+  X11,
+  Wayland,
+  /**
+   * Window server of Windows and macOS
+   */
+  Native,
+  /**
+   * No graphical session was detected
+   */
+  Unknown,
+}
+
+/**
+ * Report of the environment used to recover windows, with suggestions to fix a broken setup
+ */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct DiagnosticReport {
+  pub os: String,
+  pub session_type: SessionType,
+  /**
+   * Desktop environment from `XDG_CURRENT_DESKTOP`
+   */
+  pub desktop: Option<String>,
+  pub gnome_shell_version: Option<String>,
+  /**
+   * State of the GNOME extension, `None` when the desktop does not need it
+   */
+  pub extension_status: Option<ExtensionStatus>,
+  /**
+   * Whether the D-Bus object used to recover windows answers, `None` when D-Bus is not used
+   */
+  pub dbus_responds: Option<bool>,
+  /**
+   * First error returned by GNOME Shell over D-Bus
+   */
+  pub dbus_error: Option<String>,
+  pub suggestions: Vec<String>,
+}
+
+impl DiagnosticReport {
+  pub fn new(os: String, session_type: SessionType) -> Self {
+    Self {
+      os,
+      session_type,
+      desktop: None,
+      gnome_shell_version: None,
+      extension_status: None,
+      dbus_responds: None,
+      dbus_error: None,
+      suggestions: vec![],
+    }
+  }
+}
@@ -0,0 +1,27 @@
+#![deny(unused_imports)]
+
+/**
+ * State of the "x-win@miniben90.org" GNOME extension
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtensionStatus {
+  NotInstalled,
+  /**
+   * Installed files were written by another version of x-win
+   */
+  Outdated,
+  /**
+   * Files are installed but GNOME Shell has not loaded them yet, the user session needs to be restarted
+   */
+  InstalledNeedsRestart,
+  /**
+   * Loaded by GNOME Shell but not enabled
+   */
+  Disabled,
+  Enabled,
+  /**
+   * GNOME Shell failed to load the extension, with the error reported in the journal
+   */
+  Error(String),
+}
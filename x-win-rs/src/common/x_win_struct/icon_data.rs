@@ -0,0 +1,70 @@
+#![deny(unused_imports)]
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+
+use super::icon_info::IconInfo;
+
+/**
+ * Struct to store raw bytes of an icon with its mime type
+ */
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct IconData {
+  pub data: Vec<u8>,
+  pub mime: String,
+  pub height: u32,
+  pub width: u32,
+}
+
+impl IconData {
+  pub fn new(data: Vec<u8>, mime: String, height: u32, width: u32) -> Self {
+    Self {
+      data,
+      mime,
+      height,
+      width,
+    }
+  }
+
+  /**
+   * Return data as `data:<mime>;base64,...` string, or an empty string when there is no data
+   */
+  pub fn to_data_uri(&self) -> String {
+    if self.data.is_empty() {
+      return String::from("");
+    }
+    format!("data:{};base64,{}", self.mime, self.to_base64())
+  }
+
+  /**
+   * Return data as a plain base64 string
+   */
+  pub fn to_base64(&self) -> String {
+    BASE64_STANDARD.encode(&self.data)
+  }
+
+  /**
+   * Convert to `IconInfo` with `data` as data URI or as plain base64 string
+   */
+  pub fn to_icon_info(&self, as_data_uri: bool) -> IconInfo {
+    IconInfo {
+      data: if as_data_uri {
+        self.to_data_uri()
+      } else {
+        self.to_base64()
+      },
+      height: self.height,
+      width: self.width,
+    }
+  }
+}
+
+impl From<IconData> for IconInfo {
+  fn from(value: IconData) -> Self {
+    value.to_icon_info(true)
+  }
+}
@@ -4,6 +4,11 @@
  * Struct to store Icon information
  */
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 #[repr(C)]
 pub struct IconInfo {
   pub data: String,
@@ -0,0 +1,67 @@
+#![deny(unused_imports)]
+
+/**
+ * Output format of the icon
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IconFormat {
+  Png,
+  Webp,
+  RawRgba,
+}
+
+impl IconFormat {
+  /**
+   * Mime type used when the icon is returned as data URI
+   */
+  pub fn mime(&self) -> &'static str {
+    match self {
+      IconFormat::Png => "image/png",
+      IconFormat::Webp => "image/webp",
+      IconFormat::RawRgba => "application/octet-stream",
+    }
+  }
+}
+
+/**
+ * Struct to store options used to recover icon of a window
+ */
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct IconOptions {
+  /**
+   * Wanted size (in pixels) of the icon, the nearest available size will be used.
+   * Only Linux backends choose between sizes, Windows and macOS return the icon at its own size.
+   */
+  pub preferred_size: u32,
+  pub format: IconFormat,
+  /**
+   * Return `data` as `data:<mime>;base64,...` instead of a plain base64 string
+   */
+  pub as_data_uri: bool,
+}
+
+impl IconOptions {
+  pub fn new(preferred_size: u32, format: IconFormat, as_data_uri: bool) -> Self {
+    Self {
+      preferred_size,
+      format,
+      as_data_uri,
+    }
+  }
+}
+
+impl Default for IconOptions {
+  fn default() -> Self {
+    Self {
+      preferred_size: 128,
+      format: IconFormat::Png,
+      as_data_uri: true,
+    }
+  }
+}
@@ -0,0 +1,59 @@
+#![deny(unused_imports)]
+
+use std::path::PathBuf;
+
+/**
+ * Where the Gnome extension is installed
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InstallScope {
+  /**
+   * `$HOME/.local/share/gnome-shell/extensions`, for the current user only
+   */
+  User,
+  /**
+   * `/usr/share/gnome-shell/extensions`, for all users (root permissions are needed)
+   */
+  System,
+  /**
+   * `<prefix>/share/gnome-shell/extensions`, to install into another prefix like `/usr/local` or a packaging root
+   */
+  Custom(PathBuf),
+}
+
+/**
+ * Struct to store options used to install the Gnome extension
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct InstallOptions {
+  pub scope: InstallScope,
+  /**
+   * Add the extension to `org.gnome.shell enabled-extensions` so it's enabled on next session
+   * without calling `enable_extension`
+   */
+  pub enable_by_default: bool,
+}
+
+impl InstallOptions {
+  pub fn new(scope: InstallScope, enable_by_default: bool) -> Self {
+    Self {
+      scope,
+      enable_by_default,
+    }
+  }
+}
+
+impl Default for InstallOptions {
+  fn default() -> Self {
+    Self {
+      scope: InstallScope::User,
+      enable_by_default: false,
+    }
+  }
+}
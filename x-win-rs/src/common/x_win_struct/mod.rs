@@ -1,7 +1,109 @@
 #![deny(unused_imports)]
 
+pub mod browser_info;
+pub mod capabilities;
+pub mod diagnostic_report;
+pub mod extension_status;
+pub mod icon_data;
 pub mod icon_info;
+pub mod icon_options;
+pub mod install_options;
 pub mod process_info;
+pub mod sandbox_info;
+pub mod session_state;
 pub mod usage_info;
 pub mod window_info;
 pub mod window_position;
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::{
+    browser_info::{BrowserInfo, BrowserKind},
+    capabilities::{Backend, Capabilities},
+    diagnostic_report::SessionType,
+    extension_status::ExtensionStatus,
+    icon_data::IconData,
+    icon_info::IconInfo,
+    icon_options::{IconFormat, IconOptions},
+    install_options::{InstallOptions, InstallScope},
+    process_info::ProcessInfo,
+    session_state::SessionState,
+    usage_info::UsageInfo,
+    window_info::WindowInfo,
+    window_position::WindowPosition,
+  };
+
+  fn round_trip<T>(value: &T) -> Result<T, serde_json::Error>
+  where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+  {
+    serde_json::from_str(&serde_json::to_string(value)?)
+  }
+
+  fn window_info() -> WindowInfo {
+    WindowInfo::new(
+      42,
+      String::from("linux"),
+      String::from("x-win - Visual Studio Code"),
+      WindowPosition::new(-10, 20, 1920, 1080, true),
+      ProcessInfo::new(
+        4242,
+        String::from("/usr/share/code/code"),
+        String::from("Code"),
+        String::from("code"),
+      ),
+      UsageInfo::new(1024),
+    )
+  }
+
+  #[test]
+  fn test_window_info_field_names() -> Result<(), serde_json::Error> {
+    let value = serde_json::to_value(window_info())?;
+    assert_eq!(value["position"]["isFullScreen"], true);
+    assert_eq!(value["info"]["processId"], 4242);
+    assert_eq!(value["info"]["execName"], "code");
+    assert_eq!(value["usage"]["memory"], 1024);
+    let icon_info = serde_json::to_value(IconInfo::new(String::from("data"), 32, 16))?;
+    assert_eq!(icon_info["height"], 32);
+    Ok(())
+  }
+
+  #[test]
+  fn test_round_trip() -> Result<(), serde_json::Error> {
+    let window_info = window_info();
+    assert_eq!(round_trip(&window_info)?, window_info);
+
+    let icon_info = IconInfo::new(String::from("data:image/png;base64,AAAA"), 32, 32);
+    assert_eq!(round_trip(&icon_info)?, icon_info);
+
+    let icon_data = IconData::new(vec![1, 2, 3], String::from("image/png"), 16, 16);
+    assert_eq!(round_trip(&icon_data)?, icon_data);
+
+    let icon_options = IconOptions::default();
+    assert_eq!(round_trip(&icon_options)?, icon_options);
+    assert_eq!(round_trip(&IconFormat::Png)?, IconFormat::Png);
+
+    let browser_info = BrowserInfo::new(
+      String::from("https://github.com"),
+      BrowserKind::Other(String::from("falkon")),
+      false,
+      String::from("GitHub"),
+    );
+    assert_eq!(round_trip(&browser_info)?, browser_info);
+
+    let status = ExtensionStatus::Error(String::from("SyntaxError"));
+    assert_eq!(round_trip(&status)?, status);
+
+    let install_options = InstallOptions::new(InstallScope::Custom(PathBuf::from("/opt")), true);
+    assert_eq!(round_trip(&install_options)?, install_options);
+
+    let capabilities = Capabilities::with_windows(Backend::X11);
+    assert_eq!(round_trip(&capabilities)?, capabilities);
+
+    let session_state = SessionState::new(true, true, SessionType::Wayland);
+    assert_eq!(round_trip(&session_state)?, session_state);
+    Ok(())
+  }
+}
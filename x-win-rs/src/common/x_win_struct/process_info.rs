@@ -4,6 +4,11 @@
  * Struct to store process information of the window
  */
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 #[repr(C)]
 pub struct ProcessInfo {
   pub process_id: u32,
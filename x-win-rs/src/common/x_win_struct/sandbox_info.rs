@@ -0,0 +1,54 @@
+#![deny(unused_imports)]
+
+/**
+ * What x-win can reach from the sandbox it runs in
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct SandboxInfo {
+  /**
+   * Application id when running inside a Flatpak sandbox
+   */
+  pub flatpak_id: Option<String>,
+  /**
+   * Commands can be run on the host with `flatpak-spawn --host` (`--talk-name=org.freedesktop.Flatpak`)
+   */
+  pub host_commands: bool,
+  /**
+   * Path of host processes can be read, it is empty otherwise.
+   * Memory of host processes is not read from a sandbox, it is always `0`.
+   */
+  pub process_info: bool,
+  /**
+   * Last failure to read the path of a host process
+   */
+  pub process_info_error: Option<String>,
+  /**
+   * GNOME extensions folder of the user is visible (`--filesystem=xdg-data/gnome-shell/extensions:create`)
+   */
+  pub extension_install: bool,
+  /**
+   * GNOME Shell is reachable on the session bus (`--talk-name=org.gnome.Shell`)
+   */
+  pub gnome_shell_bus: bool,
+}
+
+impl SandboxInfo {
+  /**
+   * Nothing is restricted outside of a sandbox
+   */
+  pub fn unsandboxed() -> Self {
+    Self {
+      flatpak_id: None,
+      host_commands: true,
+      process_info: true,
+      process_info_error: None,
+      extension_install: true,
+      gnome_shell_bus: true,
+    }
+  }
+}
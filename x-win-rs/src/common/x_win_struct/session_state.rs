@@ -0,0 +1,41 @@
+#![deny(unused_imports)]
+
+use super::diagnostic_report::SessionType;
+
+/**
+ * Lock and activity state of the session x-win runs in
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct SessionState {
+  /**
+   * Screen is locked or the screen saver is shown
+   */
+  pub locked: bool,
+  /**
+   * Session is in the foreground of its seat, false when another user or a TTY switched in
+   */
+  pub active: bool,
+  pub session_type: SessionType,
+}
+
+impl SessionState {
+  pub fn new(locked: bool, active: bool, session_type: SessionType) -> Self {
+    Self {
+      locked,
+      active,
+      session_type,
+    }
+  }
+
+  /**
+   * Nobody can use the windows of the session, time should not be attributed to the focused window
+   */
+  pub fn is_away(&self) -> bool {
+    self.locked || !self.active
+  }
+}
@@ -4,6 +4,11 @@
  * Struct to store usage data of the window
  */
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 pub struct UsageInfo {
   pub memory: u32,
 }
@@ -6,6 +6,11 @@ use super::{process_info::ProcessInfo, usage_info::UsageInfo, window_position::W
  * Struct to store all informations of the window
  */
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 pub struct WindowInfo {
   pub id: u32,
   pub os: String,
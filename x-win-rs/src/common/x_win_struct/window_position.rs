@@ -4,6 +4,11 @@
  * Struct to store position and size of the window
  */
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 pub struct WindowPosition {
   pub x: i32,
   pub y: i32,
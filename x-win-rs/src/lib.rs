@@ -10,6 +10,7 @@ extern crate objc2;
 extern crate core;
 
 mod common;
+mod x_win;
 
 #[cfg(target_os = "windows")]
 mod win32;
@@ -20,14 +21,27 @@ mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
 
+/// Time spent per application, title and url built from focus changes
+pub mod activity;
+
+#[cfg(feature = "async")]
+/// Futures and streams of x-win working with any async runtime
+/// To use this module you need to add `async` feature
+pub mod r#async;
+
+#[cfg(feature = "testing")]
+/// Fake backend playing a timeline of windows to test applications without display
+/// To use this module you need to add `testing` feature
+pub mod testing;
+
 #[cfg(target_os = "windows")]
-use win32::init_platform_api;
+use win32::{init_platform_api, init_platform_api_for};
 
 #[cfg(target_os = "linux")]
-use linux::init_platform_api;
+use linux::{init_platform_api, init_platform_api_for};
 
 #[cfg(target_os = "macos")]
-use macos::init_platform_api;
+use macos::{init_platform_api, init_platform_api_for};
 
 #[cfg(all(feature = "macos_permission", target_os = "macos"))]
 /// Handle screen record permission
@@ -35,11 +49,33 @@ use macos::init_platform_api;
 /// To use this function you need to add `macos_permission` feature
 pub use macos::permission;
 
+use std::{sync::mpsc::Receiver, time::Duration};
+
+pub use x_win::{XWin, XWinBuilder};
+
 pub use common::{
   api::{empty_entity, os_name, Api},
+  error::XWinError,
+  icon_cache::IconCacheOptions,
+  redaction::{
+    redaction_rules, set_redaction_rules, Redaction, RedactionAction, RedactionRule, RuleMatch,
+    PRIVATE_TITLE,
+  },
   result::Result,
   x_win_struct::{
-    icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+    browser_info::{BrowserInfo, BrowserKind},
+    capabilities::{Backend, Capabilities},
+    diagnostic_report::{DiagnosticReport, SessionType},
+    extension_status::ExtensionStatus,
+    icon_data::IconData,
+    icon_info::IconInfo,
+    icon_options::{IconFormat, IconOptions},
+    install_options::{InstallOptions, InstallScope},
+    process_info::ProcessInfo,
+    sandbox_info::SandboxInfo,
+    session_state::SessionState,
+    usage_info::UsageInfo,
+    window_info::WindowInfo,
     window_position::WindowPosition,
   },
 };
@@ -49,19 +85,46 @@ pub use common::{
  * Return `IconInfo`
  */
 pub fn get_window_icon(window_info: &WindowInfo) -> Result<IconInfo> {
-  let api = init_platform_api();
-  let icon_info = api.get_app_icon(window_info)?;
-  Ok(icon_info)
+  XWin::shared().get_window_icon(window_info)
+}
+
+/**
+ * Recover icon of window with a preferred size and output format.
+ * Return `IconInfo`
+ */
+pub fn get_window_icon_with(window_info: &WindowInfo, options: IconOptions) -> Result<IconInfo> {
+  XWin::shared().get_window_icon_with(window_info, options)
+}
+
+/**
+ * Recover raw bytes of the icon of window with a preferred size and output format.
+ * Return `IconData`
+ */
+pub fn get_window_icon_data(window_info: &WindowInfo, options: IconOptions) -> Result<IconData> {
+  XWin::shared().get_window_icon_data(window_info, options)
+}
+
+/**
+ * Change options of the icon cache used by free functions, least recently used icons are dropped if capacity is reduced
+ */
+pub fn set_icon_cache_options(options: IconCacheOptions) -> Result<()> {
+  XWin::shared().set_icon_cache_options(options)
+}
+
+/**
+ * Remove all icons from the icon cache used by free functions and from its cache directory
+ */
+pub fn clear_icon_cache() -> Result<()> {
+  XWin::shared().clear_icon_cache()
 }
 
 /**
  * Recover browser url of window.
- * Return `String`
+ * Return `Some(BrowserInfo)` when the window is a browser and its url has been recovered, `None` otherwise.
+ * An `XWinError::Unsupported` error is returned when the url can't be read on this platform or browser.
  */
-pub fn get_browser_url(window_info: &WindowInfo) -> Result<String> {
-  let api = init_platform_api();
-  let browser_url = api.get_browser_url(window_info)?;
-  Ok(browser_url)
+pub fn get_browser_url(window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+  XWin::shared().get_browser_url(window_info)
 }
 
 /**
@@ -69,9 +132,7 @@ pub fn get_browser_url(window_info: &WindowInfo) -> Result<String> {
  * Return `WindowInfo` containing details about a specific active window.
  */
 pub fn get_active_window() -> Result<WindowInfo> {
-  let api = init_platform_api();
-  let active_window = api.get_active_window()?;
-  Ok(active_window)
+  XWin::shared().get_active_window()
 }
 
 /**
@@ -79,9 +140,23 @@ pub fn get_active_window() -> Result<WindowInfo> {
  * Return `Vec<WindowInfo>` each containing details about a specific open window.
  */
 pub fn get_open_windows() -> Result<Vec<WindowInfo>> {
-  let api = init_platform_api();
-  let open_windows = api.get_open_windows()?;
-  Ok(open_windows)
+  XWin::shared().get_open_windows()
+}
+
+/**
+ * Return the features available with the backend detected for the current session.
+ * Useful to hide features that can't work, like browser urls without accessibility bus or windows before the gnome extension is enabled.
+ */
+pub fn capabilities() -> Capabilities {
+  XWin::shared().capabilities()
+}
+
+/**
+ * Return the time since the last keyboard or mouse input of the user.
+ * Linux uses the MIT-SCREEN-SAVER extension on X11, the idle monitor of Mutter on GNOME and `ext-idle-notify-v1` on other Wayland compositors.
+ */
+pub fn get_idle_time() -> Result<Duration> {
+  XWin::shared().get_idle_time()
 }
 
 /**
@@ -90,13 +165,23 @@ pub fn get_open_windows() -> Result<Vec<WindowInfo>> {
  * **Restart session will be require to install the gnome extension.**
  */
 pub fn install_extension() -> Result<bool> {
+  install_extension_with(InstallOptions::default())
+}
+
+/**
+ * Install "@mininben90/x-win" Gnome extension for the current user, for all users or into a custom prefix.
+ * With `enable_by_default` the extension is added to `org.gnome.shell enabled-extensions` to be enabled on next session.
+ * **Restart session will be require to install the gnome extension.**
+ */
+pub fn install_extension_with(options: InstallOptions) -> Result<bool> {
   #[cfg(not(target_os = "linux"))]
   {
+    let _ = options;
     Ok(false)
   }
   #[cfg(target_os = "linux")]
   {
-    linux::gnome_install_extension()
+    linux::gnome_install_extension_with(&options)
   }
 }
 
@@ -106,13 +191,24 @@ pub fn install_extension() -> Result<bool> {
  * **Restart session will be require to remove the gnome extension.**
  */
 pub fn uninstall_extension() -> Result<bool> {
+  uninstall_extension_with(InstallOptions::default())
+}
+
+/**
+ * Uninstall "@mininben90/x-win" Gnome extension installed for the current user, for all users or into a custom prefix.
+ * The schema override written with `enable_by_default` is removed from system and custom prefixes,
+ * for the current user `enable_by_default` removes the extension from `org.gnome.shell enabled-extensions`.
+ * **Restart session will be require to remove the gnome extension.**
+ */
+pub fn uninstall_extension_with(options: InstallOptions) -> Result<bool> {
   #[cfg(not(target_os = "linux"))]
   {
+    let _ = options;
     Ok(false)
   }
   #[cfg(target_os = "linux")]
   {
-    linux::gnome_uninstall_extension()
+    linux::gnome_uninstall_extension_with(&options)
   }
 }
 
@@ -176,6 +272,81 @@ pub fn is_installed_extension() -> Result<bool> {
   }
 }
 
+/**
+ * Return the state of the gnome extension for Linux using Gnome > 41.
+ * Tell if the extension is not installed, written by another x-win version, waiting a session restart, enabled or in error. Working only with Wayland windows manager.
+ */
+pub fn extension_status() -> Result<ExtensionStatus> {
+  #[cfg(not(target_os = "linux"))]
+  {
+    Ok(ExtensionStatus::NotInstalled)
+  }
+  #[cfg(target_os = "linux")]
+  {
+    linux::gnome_extension_status()
+  }
+}
+
+/**
+ * Report the session type, desktop, GNOME Shell version and the state of the gnome extension with suggestions to fix them.
+ * Useful to understand why windows can not be recovered on Linux.
+ */
+pub fn diagnose() -> Result<DiagnosticReport> {
+  #[cfg(not(target_os = "linux"))]
+  {
+    Ok(DiagnosticReport::new(os_name(), SessionType::Native))
+  }
+  #[cfg(target_os = "linux")]
+  {
+    Ok(linux::diagnose())
+  }
+}
+
+/**
+ * Tell if x-win runs inside a Flatpak sandbox and what it can reach from there.
+ * Outside of a sandbox and on Windows and macOS nothing is restricted.
+ */
+pub fn sandbox_info() -> Result<SandboxInfo> {
+  #[cfg(not(target_os = "linux"))]
+  {
+    Ok(SandboxInfo::unsandboxed())
+  }
+  #[cfg(target_os = "linux")]
+  {
+    Ok(linux::sandbox_info())
+  }
+}
+
+/**
+ * Tell if the session is locked or inactive, to stop attributing time to the focused window meanwhile.
+ * Read from `org.freedesktop.login1` and from `org.gnome.ScreenSaver` or `org.freedesktop.ScreenSaver`, Linux only.
+ */
+pub fn session_state() -> Result<SessionState> {
+  #[cfg(not(target_os = "linux"))]
+  {
+    Err(XWinError::Unsupported(String::from("Session state is only available on Linux")).into())
+  }
+  #[cfg(target_os = "linux")]
+  {
+    linux::session_state()
+  }
+}
+
+/**
+ * Receive the current `SessionState`, then a new one each time the session is locked, unlocked, activated or deactivated.
+ * Changes come from logind `PropertiesChanged` and screen saver `ActiveChanged` signals, Linux only.
+ */
+pub fn watch_session_state() -> Result<Receiver<SessionState>> {
+  #[cfg(not(target_os = "linux"))]
+  {
+    Err(XWinError::Unsupported(String::from("Session state is only available on Linux")).into())
+  }
+  #[cfg(target_os = "linux")]
+  {
+    linux::watch_session_state()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -352,8 +523,9 @@ mod tests {
     let _context: TestContext = TestContext::setup();
     match get_active_window() {
       Ok(window_info) => match get_browser_url(&window_info) {
-        Ok(url) => {
-          assert!(url.starts_with("http"));
+        Ok(browser_info) => {
+          let browser_info = browser_info.ok_or("No url recovered from the browser")?;
+          assert!(browser_info.url.starts_with("http"));
           Ok(())
         }
         Err(err) => Err(err),
@@ -373,8 +545,9 @@ mod tests {
         assert_ne!(open_windows.len(), 0);
         match open_windows.first() {
           Some(window_info) => match get_browser_url(window_info) {
-            Ok(url) => {
-              assert!(url.starts_with("http"));
+            Ok(browser_info) => {
+              let browser_info = browser_info.ok_or("No url recovered from the browser")?;
+              assert!(browser_info.url.starts_with("http"));
               Ok(())
             }
             Err(err) => Err(err),
@@ -386,44 +559,43 @@ mod tests {
     }
   }
 
+  /**
+   * Browsers must give their url, other windows none, the url can't be read without accessibility bus
+   */
   #[cfg(target_os = "linux")]
-  #[test]
-  #[ignore = "Not working on ci/cd"]
-  fn test_get_browser_url_from_active_window() -> Result<()> {
-    #[allow(unused)]
-    match get_active_window() {
-      Ok(window_info) => match get_browser_url(&window_info) {
-        Ok(url) => {
-          assert!(url.eq("URL recovery not supported on Linux distribution!"));
-          Ok(())
+  fn assert_browser_url(window_info: &WindowInfo) -> Result<()> {
+    match get_browser_url(window_info) {
+      Ok(browser_info) => {
+        match BrowserKind::from_exec_name(&window_info.info.exec_name) {
+          BrowserKind::Other(_) => {}
+          _ => {
+            let browser_info = browser_info.ok_or("No url recovered from the browser")?;
+            assert!(browser_info.url.contains('.'));
+          }
         }
-        Err(err) => Err(err),
+        Ok(())
+      }
+      Err(err) => match err.downcast_ref::<XWinError>() {
+        Some(XWinError::Unsupported(_)) => Ok(()),
+        _ => Err(err),
       },
-      Err(err) => Err(err),
     }
   }
 
+  #[cfg(target_os = "linux")]
+  #[test]
+  #[ignore = "Not working on ci/cd"]
+  fn test_get_browser_url_from_active_window() -> Result<()> {
+    assert_browser_url(&get_active_window()?)
+  }
+
   #[cfg(target_os = "linux")]
   #[test]
   #[ignore = "Not working on ci/cd"]
   fn test_get_browser_url_from_open_windows() -> Result<()> {
-    #[allow(unused)]
-    match get_open_windows() {
-      Ok(open_windows) => {
-        assert_ne!(open_windows.len(), 0);
-        match open_windows.first() {
-          Some(window_info) => match get_browser_url(window_info) {
-            Ok(url) => {
-              assert!(url.eq("URL recovery not supported on Linux distribution!"));
-              Ok(())
-            }
-            Err(err) => Err(err),
-          },
-          None => Err("No open window".into()),
-        }
-      }
-      Err(err) => Err(err),
-    }
+    let open_windows = get_open_windows()?;
+    assert_ne!(open_windows.len(), 0);
+    open_windows.iter().try_for_each(assert_browser_url)
   }
 
   #[cfg(all(feature = "macos_permission", target_os = "macos"))]
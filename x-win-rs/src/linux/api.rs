@@ -1,30 +1,92 @@
+#[cfg(feature = "async")]
+mod async_api;
+mod atspi_api;
 mod common_api;
+mod diagnostic;
+mod flatpak;
+mod gnome_dbus;
+mod gnome_extension;
 mod gnome_shell;
+mod idle_api;
+mod session_api;
 mod wayland_api;
 mod wayland_eval_api;
 mod wayland_extension_api;
 mod x11_api;
 
-use common_api::is_wayland_desktop;
+#[cfg(test)]
+mod test_dbus;
+#[cfg(test)]
+mod test_x11;
+
+#[cfg(feature = "async")]
+pub use async_api::{get_active_window, get_open_windows, watch_active_window};
+pub use diagnostic::diagnose;
+pub use flatpak::sandbox_info;
+pub use session_api::{session_state, watch_session_state};
+
+use std::time::Duration;
+
+use common_api::{is_gnome_desktop, is_wayland_desktop};
 use wayland_api::WaylandApi;
 use x11_api::X11Api;
 
 use crate::common::{
   api::Api,
+  error::XWinError,
   result::Result,
-  x_win_struct::{icon_info::IconInfo, window_info::WindowInfo},
+  x_win_struct::{
+    browser_info::BrowserInfo,
+    capabilities::{Backend, Capabilities},
+    extension_status::ExtensionStatus,
+    icon_data::IconData,
+    icon_info::IconInfo,
+    icon_options::IconOptions,
+    install_options::{InstallOptions, InstallScope},
+    window_info::WindowInfo,
+  },
 };
 
 pub trait APIGnome {
-  fn install_extension() -> Result<bool>;
-  fn uninstall_extension() -> Result<bool>;
+  fn install_extension_with(options: &InstallOptions) -> Result<bool>;
+  fn uninstall_extension_with(options: &InstallOptions) -> Result<bool>;
   fn enable_extension() -> Result<bool>;
   fn disable_extension() -> Result<bool>;
   fn is_installed_extension() -> Result<bool>;
   fn is_enabled_extension() -> Result<bool>;
+  fn extension_status() -> Result<ExtensionStatus>;
+}
+
+/**
+ * Backend choosing on each call between X11 and GNOME Shell from the session
+ */
+#[derive(Default)]
+pub struct LinuxAPI {
+  x11: X11Api,
+  wayland: WaylandApi,
+}
+
+impl LinuxAPI {
+  pub fn new() -> Self {
+    Self::default()
+  }
 }
 
-pub struct LinuxAPI {}
+/**
+ * Return the api of a backend chosen by the application instead of the session
+ */
+pub fn api_for(backend: &Backend) -> Result<Box<dyn Api + Send + Sync>> {
+  Ok(match backend {
+    Backend::X11 => Box::new(X11Api::new()),
+    Backend::GnomeShellEval => Box::new(WaylandApi::with_eval(true)),
+    Backend::GnomeShellExtension => Box::new(WaylandApi::with_eval(false)),
+    backend => {
+      return Err(
+        XWinError::Unsupported(format!("{backend:?} backend is not available on Linux")).into(),
+      )
+    }
+  })
+}
 
 /**
  * Impl. for windows system
@@ -32,45 +94,80 @@ pub struct LinuxAPI {}
 impl Api for LinuxAPI {
   fn get_active_window(&self) -> crate::common::result::Result<WindowInfo> {
     Ok(match is_wayland_desktop() {
-      true => (WaylandApi {}).get_active_window()?,
-      false => (X11Api {}).get_active_window()?,
+      true => self.wayland.get_active_window()?,
+      false => self.x11.get_active_window()?,
     })
   }
 
   fn get_open_windows(&self) -> crate::common::result::Result<Vec<WindowInfo>> {
     Ok(match is_wayland_desktop() {
-      true => (WaylandApi {}).get_open_windows()?,
-      false => (X11Api {}).get_open_windows()?,
+      true => self.wayland.get_open_windows()?,
+      false => self.x11.get_open_windows()?,
     })
   }
 
   fn get_app_icon(&self, window_info: &WindowInfo) -> crate::common::result::Result<IconInfo> {
     Ok(match is_wayland_desktop() {
-      true => (WaylandApi {}).get_app_icon(window_info)?,
-      false => (X11Api {}).get_app_icon(window_info)?,
+      true => self.wayland.get_app_icon(window_info)?,
+      false => self.x11.get_app_icon(window_info)?,
+    })
+  }
+
+  fn get_app_icon_data(
+    &self,
+    window_info: &WindowInfo,
+    options: &IconOptions,
+  ) -> crate::common::result::Result<IconData> {
+    Ok(match is_wayland_desktop() {
+      true => self.wayland.get_app_icon_data(window_info, options)?,
+      false => self.x11.get_app_icon_data(window_info, options)?,
     })
   }
 
-  fn get_browser_url(&self, window_info: &WindowInfo) -> crate::common::result::Result<String> {
+  fn get_browser_url(
+    &self,
+    window_info: &WindowInfo,
+  ) -> crate::common::result::Result<Option<BrowserInfo>> {
     Ok(match is_wayland_desktop() {
-      true => (WaylandApi {}).get_browser_url(window_info)?,
-      false => (X11Api {}).get_browser_url(window_info)?,
+      true => self.wayland.get_browser_url(window_info)?,
+      false => self.x11.get_browser_url(window_info)?,
     })
   }
+
+  fn get_idle_time(&self) -> Result<Duration> {
+    match (is_wayland_desktop(), is_gnome_desktop()) {
+      (true, true) => self.wayland.get_idle_time(),
+      // Other compositors have no D-Bus idle monitor
+      (true, false) => idle_api::wayland_idle_time(),
+      (false, _) => self.x11.get_idle_time(),
+    }
+  }
+
+  fn capabilities(&self) -> Capabilities {
+    if !is_wayland_desktop() && std::env::var_os("DISPLAY").is_none() {
+      return Capabilities::new(Backend::Unsupported);
+    }
+    match is_wayland_desktop() {
+      true => self.wayland.capabilities(),
+      false => self.x11.capabilities(),
+    }
+  }
 }
 
 impl APIGnome for LinuxAPI {
-  fn install_extension() -> Result<bool> {
-    if is_wayland_desktop() {
-      WaylandApi::install_extension()
+  fn install_extension_with(options: &InstallOptions) -> Result<bool> {
+    // Installation for all users can be done outside of a graphical session
+    if is_wayland_desktop() || options.scope.ne(&InstallScope::User) {
+      WaylandApi::install_extension_with(options)
     } else {
       Ok(false)
     }
   }
 
-  fn uninstall_extension() -> Result<bool> {
-    if is_wayland_desktop() {
-      WaylandApi::uninstall_extension()
+  fn uninstall_extension_with(options: &InstallOptions) -> Result<bool> {
+    // Like the installation, extensions of all users can be removed outside of a graphical session
+    if is_wayland_desktop() || options.scope.ne(&InstallScope::User) {
+      WaylandApi::uninstall_extension_with(options)
     } else {
       Ok(false)
     }
@@ -107,4 +204,12 @@ impl APIGnome for LinuxAPI {
       Ok(false)
     }
   }
+
+  fn extension_status() -> Result<ExtensionStatus> {
+    if is_wayland_desktop() {
+      WaylandApi::extension_status()
+    } else {
+      Ok(ExtensionStatus::NotInstalled)
+    }
+  }
 }
@@ -0,0 +1,203 @@
+#![deny(unused_imports)]
+
+use std::{
+  os::fd::{AsFd, AsRawFd, BorrowedFd},
+  time::Duration,
+};
+
+use async_io::Async;
+use futures_lite::stream;
+use xcb::{x, Xid};
+
+use crate::{
+  common::{api::Api, x_win_struct::window_info::WindowInfo},
+  r#async::{is_same_window, poll_active_window, unblock, Result, WindowStream},
+};
+
+use super::{
+  common_api::is_wayland_desktop,
+  gnome_dbus::call_extension_async,
+  wayland_api::{gnome_use_eval, WaylandApi},
+  x11_api::{self, X11Api},
+};
+
+/**
+ * Tell if windows are recovered with the D-Bus interface of the gnome extension
+ */
+async fn use_extension() -> Result<bool> {
+  if !is_wayland_desktop() {
+    return Ok(false);
+  }
+  // GNOME Shell version is checked again on D-Bus from time to time
+  unblock(|| Ok(!gnome_use_eval())).await
+}
+
+pub async fn get_active_window() -> Result<WindowInfo> {
+  if !use_extension().await? {
+    return unblock(|| match is_wayland_desktop() {
+      true => WaylandApi::default().get_active_window(),
+      false => X11Api::new().get_active_window(),
+    })
+    .await;
+  }
+  let window = call_extension_async(|proxy| async move { proxy.get_active_window().await }).await?;
+  Ok(window.into())
+}
+
+pub async fn get_open_windows() -> Result<Vec<WindowInfo>> {
+  if !use_extension().await? {
+    return unblock(|| match is_wayland_desktop() {
+      true => WaylandApi::default().get_open_windows(),
+      false => X11Api::new().get_open_windows(),
+    })
+    .await;
+  }
+  let windows = call_extension_async(|proxy| async move { proxy.get_open_windows().await }).await?;
+  Ok(windows.into_iter().map(|window| window.into()).collect())
+}
+
+pub fn watch_active_window(interval: Duration) -> WindowStream {
+  if is_wayland_desktop() {
+    return poll_active_window(interval);
+  }
+  match X11Watch::new() {
+    Ok(watch) => watch.into_stream(),
+    Err(err) => Box::pin(stream::once(Err(err))),
+  }
+}
+
+/**
+ * X connection registered in the reactor of `async-io` to wait until the X server sends events
+ */
+struct XcbConnection(xcb::Connection);
+
+// `async` feature requires the Rust version of `async-io`, newer than the one of x-win
+#[allow(clippy::incompatible_msrv)]
+impl AsFd for XcbConnection {
+  fn as_fd(&self) -> BorrowedFd<'_> {
+    // The socket is owned by the connection and stays opened until it is dropped
+    unsafe { BorrowedFd::borrow_raw(self.0.as_raw_fd()) }
+  }
+}
+
+/**
+ * Follow `_NET_ACTIVE_WINDOW` of the root window and the title of the active window
+ */
+struct X11Watch {
+  conn: Async<XcbConnection>,
+  active_window_atom: x::Atom,
+  net_wm_name_atom: x::Atom,
+  watched: Option<x::Window>,
+  last: Option<WindowInfo>,
+  changed: bool,
+  closed: bool,
+}
+
+impl X11Watch {
+  fn new() -> Result<Self> {
+    let conn = x11_api::connection().map_err(crate::r#async::send_error)?;
+    let root = conn
+      .get_setup()
+      .roots()
+      .next()
+      .map(|screen| screen.root())
+      .ok_or("Something got wrong, not possible to get access of X Server!")?;
+    let active_window_atom = x11_api::get_active_window_atom(&conn);
+    let net_wm_name_atom = x11_api::get_atom(&conn, b"_NET_WM_NAME", true);
+    watch_properties(&conn, root)?;
+    Ok(Self {
+      conn: Async::new(XcbConnection(conn))?,
+      active_window_atom,
+      net_wm_name_atom,
+      watched: None,
+      last: None,
+      changed: true,
+      closed: false,
+    })
+  }
+
+  fn into_stream(self) -> WindowStream {
+    Box::pin(stream::unfold(self, |mut watch| async move {
+      let window = watch.next().await?;
+      Some((window, watch))
+    }))
+  }
+
+  async fn next(&mut self) -> Option<Result<WindowInfo>> {
+    if self.closed {
+      return None;
+    }
+    loop {
+      if self.changed {
+        self.changed = false;
+        match self.active_window() {
+          Ok(Some(window)) => return Some(Ok(window)),
+          Ok(None) => {}
+          Err(err) => return Some(Err(err)),
+        }
+      }
+      // Replies may have queued events, they are read before waiting for the socket
+      if let Err(err) = self.read_events() {
+        self.closed = true;
+        return Some(Err(err));
+      }
+      if !self.changed {
+        if let Err(err) = self.conn.readable().await {
+          self.closed = true;
+          return Some(Err(err.into()));
+        }
+      }
+    }
+  }
+
+  /**
+   * Return the active window when it is not the last streamed one
+   */
+  fn active_window(&mut self) -> Result<Option<WindowInfo>> {
+    let conn = &self.conn.get_ref().0;
+    let window = x11_api::active_window(conn).map_err(crate::r#async::send_error)?;
+    let window_id: x::Window = xcb::XidNew::new(window.id);
+    // Title changes are sent by the window itself, it may already be destroyed
+    if !window_id.is_none()
+      && self.watched.ne(&Some(window_id))
+      && watch_properties(conn, window_id).is_ok()
+    {
+      // Titles of windows which lost the focus are not followed anymore, they may already be destroyed
+      if let Some(previous) = self.watched.replace(window_id) {
+        let _ = set_event_mask(conn, previous, x::EventMask::empty());
+      }
+    }
+    if is_same_window(self.last.as_ref(), &window) {
+      return Ok(None);
+    }
+    self.last = Some(window.clone());
+    Ok(Some(window))
+  }
+
+  fn read_events(&mut self) -> Result<()> {
+    while let Some(event) = self.conn.get_ref().0.poll_for_event()? {
+      if let xcb::Event::X(x::Event::PropertyNotify(event)) = event {
+        let atom = event.atom();
+        if atom.eq(&self.active_window_atom)
+          || atom.eq(&self.net_wm_name_atom)
+          || atom.eq(&x::ATOM_WM_NAME)
+        {
+          self.changed = true;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+fn watch_properties(conn: &xcb::Connection, window: x::Window) -> Result<()> {
+  set_event_mask(conn, window, x::EventMask::PROPERTY_CHANGE)
+}
+
+fn set_event_mask(conn: &xcb::Connection, window: x::Window, mask: x::EventMask) -> Result<()> {
+  conn.send_and_check_request(&x::ChangeWindowAttributes {
+    window,
+    value_list: &[x::Cw::EventMask(mask)],
+  })?;
+  Ok(())
+}
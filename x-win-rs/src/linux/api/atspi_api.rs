@@ -0,0 +1,316 @@
+#![deny(unused_imports)]
+
+use std::collections::VecDeque;
+
+use zbus::{
+  blocking::{connection, Connection},
+  zvariant::{OwnedObjectPath, OwnedValue},
+};
+
+use crate::common::{
+  browser::{browser_info, is_browser},
+  error::XWinError,
+  result::Result,
+  x_win_struct::{
+    browser_info::{BrowserInfo, BrowserKind},
+    window_info::WindowInfo,
+  },
+};
+
+use super::gnome_dbus::with_session;
+
+static A11Y_BUS_DESTINATION: &str = "org.a11y.Bus";
+static A11Y_BUS_PATH: &str = "/org/a11y/bus";
+static REGISTRY_DESTINATION: &str = "org.a11y.atspi.Registry";
+static REGISTRY_ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+static ACCESSIBLE_IFACE: &str = "org.a11y.atspi.Accessible";
+static TEXT_IFACE: &str = "org.a11y.atspi.Text";
+
+/// `ATSPI_ROLE_FRAME`
+const ROLE_FRAME: u32 = 23;
+/// `ATSPI_ROLE_ENTRY`
+const ROLE_ENTRY: u32 = 79;
+/// `ATSPI_ROLE_DOCUMENT_WEB`
+const ROLE_DOCUMENT_WEB: u32 = 95;
+
+/// Maximum number of accessible nodes visited to find the address bar
+const MAX_VISITED_NODES: usize = 5000;
+
+type AccessibleRef = (String, OwnedObjectPath);
+
+/**
+ * Recover url from the address bar of Firefox and Chromium-family browsers using AT-SPI2 accessibility tree.
+ * Return `None` when the window is not a browser or when the address bar has not been found.
+ */
+pub fn get_browser_url(window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+  if window_info.info.exec_name.is_empty() || !is_browser(&window_info.info.exec_name) {
+    return Ok(None);
+  }
+  let connection = a11y_connection()?;
+  let url = find_browser_url(&connection, window_info.info.process_id, &window_info.title)?;
+  Ok(browser_info(
+    window_info,
+    BrowserKind::from_exec_name(&window_info.info.exec_name),
+    url,
+  ))
+}
+
+/**
+ * Tell if the accessibility bus can be reached to read urls of browsers
+ */
+pub fn is_available() -> bool {
+  with_session(a11y_bus_address).is_ok()
+}
+
+/**
+ * Open a connection to the accessibility bus given by `org.a11y.Bus`
+ */
+fn a11y_connection() -> Result<Connection> {
+  let address = with_session(a11y_bus_address).map_err(|err| {
+    XWinError::Unsupported(format!(
+      "Unable to reach the accessibility bus (org.a11y.Bus): {err}"
+    ))
+  })?;
+  Ok(connection::Builder::address(address.as_str())?.build()?)
+}
+
+fn a11y_bus_address(session: &Connection) -> Result<String> {
+  let response = session.call_method(
+    Some(A11Y_BUS_DESTINATION),
+    A11Y_BUS_PATH,
+    Some(A11Y_BUS_DESTINATION),
+    "GetAddress",
+    &(),
+  )?;
+  Ok(response.body().deserialize()?)
+}
+
+/**
+ * Find the application of `process_id` registered on the accessibility bus and read its address bar
+ */
+fn find_browser_url(connection: &Connection, process_id: u32, title: &str) -> Result<String> {
+  let applications = get_children(
+    connection,
+    &(
+      REGISTRY_DESTINATION.to_owned(),
+      OwnedObjectPath::try_from(REGISTRY_ROOT_PATH)?,
+    ),
+  )?;
+
+  for application in applications {
+    if get_process_id(connection, &application.0).ok() != Some(process_id) {
+      continue;
+    }
+    let frames = get_children(connection, &application)?;
+    // Other windows of the browser belong to the same application, their url is not the one of this window
+    let frame = frames.iter().find(|frame| {
+      get_role(connection, frame).ok() == Some(ROLE_FRAME)
+        && get_name(connection, frame).ok().as_deref() == Some(title)
+    });
+    if let Some(frame) = frame {
+      if let Some(url) = find_address_bar_text(connection, frame)? {
+        return Ok(url);
+      }
+    }
+  }
+
+  Ok(String::from(""))
+}
+
+/**
+ * Walk the tree of the browser window breadth first and return text of the first entry outside web documents
+ */
+fn find_address_bar_text(connection: &Connection, frame: &AccessibleRef) -> Result<Option<String>> {
+  let mut queue: VecDeque<AccessibleRef> = VecDeque::from([frame.clone()]);
+  let mut visited: usize = 0;
+
+  while let Some(node) = queue.pop_front() {
+    visited += 1;
+    if visited > MAX_VISITED_NODES {
+      break;
+    }
+    match get_role(connection, &node) {
+      Ok(ROLE_DOCUMENT_WEB) => continue,
+      Ok(ROLE_ENTRY) => {
+        let text = get_text(connection, &node).unwrap_or_default();
+        if !text.trim().is_empty() {
+          return Ok(Some(text.trim().to_owned()));
+        }
+        continue;
+      }
+      Ok(_) => {}
+      Err(_) => continue,
+    }
+    if let Ok(children) = get_children(connection, &node) {
+      queue.extend(children);
+    }
+  }
+
+  Ok(None)
+}
+
+fn get_process_id(connection: &Connection, bus_name: &str) -> Result<u32> {
+  let response = connection.call_method(
+    Some("org.freedesktop.DBus"),
+    "/org/freedesktop/DBus",
+    Some("org.freedesktop.DBus"),
+    "GetConnectionUnixProcessID",
+    &bus_name,
+  )?;
+  Ok(response.body().deserialize()?)
+}
+
+fn get_children(connection: &Connection, node: &AccessibleRef) -> Result<Vec<AccessibleRef>> {
+  let response = connection.call_method(
+    Some(node.0.as_str()),
+    node.1.as_str(),
+    Some(ACCESSIBLE_IFACE),
+    "GetChildren",
+    &(),
+  )?;
+  Ok(response.body().deserialize()?)
+}
+
+fn get_role(connection: &Connection, node: &AccessibleRef) -> Result<u32> {
+  let response = connection.call_method(
+    Some(node.0.as_str()),
+    node.1.as_str(),
+    Some(ACCESSIBLE_IFACE),
+    "GetRole",
+    &(),
+  )?;
+  Ok(response.body().deserialize()?)
+}
+
+fn get_name(connection: &Connection, node: &AccessibleRef) -> Result<String> {
+  let response = connection.call_method(
+    Some(node.0.as_str()),
+    node.1.as_str(),
+    Some("org.freedesktop.DBus.Properties"),
+    "Get",
+    &(ACCESSIBLE_IFACE, "Name"),
+  )?;
+  let value: OwnedValue = response.body().deserialize()?;
+  Ok(String::try_from(value)?)
+}
+
+fn get_text(connection: &Connection, node: &AccessibleRef) -> Result<String> {
+  let response = connection.call_method(
+    Some(node.0.as_str()),
+    node.1.as_str(),
+    Some(TEXT_IFACE),
+    "GetText",
+    &(0i32, -1i32),
+  )?;
+  Ok(response.body().deserialize()?)
+}
+
+#[cfg(test)]
+mod tests {
+  use zbus::{blocking::connection, interface, zvariant::OwnedObjectPath};
+
+  use super::{find_browser_url, REGISTRY_DESTINATION, REGISTRY_ROOT_PATH};
+  use crate::linux::api::test_dbus::PrivateBus;
+
+  struct MockAccessible {
+    role: u32,
+    name: String,
+    children: Vec<&'static str>,
+  }
+
+  #[interface(name = "org.a11y.atspi.Accessible")]
+  impl MockAccessible {
+    fn get_role(&self) -> u32 {
+      self.role
+    }
+
+    fn get_children(&self) -> Vec<(String, OwnedObjectPath)> {
+      self
+        .children
+        .iter()
+        .map(|path| {
+          (
+            REGISTRY_DESTINATION.to_owned(),
+            OwnedObjectPath::try_from(*path).unwrap(),
+          )
+        })
+        .collect()
+    }
+
+    #[zbus(property)]
+    fn name(&self) -> String {
+      self.name.clone()
+    }
+  }
+
+  struct MockText {
+    text: String,
+  }
+
+  #[interface(name = "org.a11y.atspi.Text")]
+  impl MockText {
+    fn get_text(&self, _start: i32, _end: i32) -> String {
+      self.text.clone()
+    }
+  }
+
+  fn node(role: u32, name: &str, children: Vec<&'static str>) -> MockAccessible {
+    MockAccessible {
+      role,
+      name: name.to_owned(),
+      children,
+    }
+  }
+
+  #[test]
+  fn test_find_browser_url() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+
+    // root > app > [frame > [document > search entry, toolbar > url entry], other frame]
+    let _service = connection::Builder::address(bus.address.as_str())?
+      .name(REGISTRY_DESTINATION)?
+      .serve_at(REGISTRY_ROOT_PATH, node(14, "", vec!["/app"]))?
+      .serve_at("/app", node(75, "Firefox", vec!["/frame", "/other"]))?
+      .serve_at("/other", node(23, "Other — Mozilla Firefox", vec![]))?
+      .serve_at(
+        "/frame",
+        node(
+          23,
+          "GitHub — Mozilla Firefox",
+          vec!["/document", "/toolbar"],
+        ),
+      )?
+      .serve_at("/document", node(95, "GitHub", vec!["/search"]))?
+      .serve_at("/search", node(79, "Search", vec![]))?
+      .serve_at(
+        "/search",
+        MockText {
+          text: String::from("not the url"),
+        },
+      )?
+      .serve_at("/toolbar", node(63, "Navigation", vec!["/url"]))?
+      .serve_at("/url", node(79, "Search or enter address", vec![]))?
+      .serve_at(
+        "/url",
+        MockText {
+          text: String::from("https://github.com/miniben-90/x-win"),
+        },
+      )?
+      .build()?;
+
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+    let process_id = std::process::id();
+
+    let url = find_browser_url(&client, process_id, "GitHub — Mozilla Firefox")?;
+    assert_eq!(url, "https://github.com/miniben-90/x-win");
+
+    let url = find_browser_url(&client, process_id + 1, "GitHub — Mozilla Firefox")?;
+    assert_eq!(url, "");
+
+    // Url of another window of the browser is not returned
+    let url = find_browser_url(&client, process_id, "Closed — Mozilla Firefox")?;
+    assert_eq!(url, "");
+
+    Ok(())
+  }
+}
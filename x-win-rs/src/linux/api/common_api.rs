@@ -1,14 +1,23 @@
 #![deny(unused_imports)]
 
-use std::{
-  fs::{read_link, File},
-  io::Read,
-};
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
 
-use std::process::Command;
+use once_cell::sync::Lazy;
 
 use crate::common::{api::empty_entity, result::Result, x_win_struct::window_info::WindowInfo};
 
+use super::flatpak::{host_command, is_flatpak, read_host_link, set_host_process_error};
+
+/// Maximum number of host processes whose executable path is remembered inside a sandbox
+const HOST_EXECUTABLE_PATHS_CAPACITY: usize = 1024;
+
+/**
+ * Executable paths of host processes by pid, each read from a sandbox costs a `flatpak-spawn --host` process.
+ * Failed reads are kept as an empty path to not spawn again on each call.
+ */
+static HOST_EXECUTABLE_PATHS: Lazy<Mutex<HashMap<u32, PathBuf>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
 /**
  * To know the os
  */
@@ -26,12 +35,23 @@ pub fn is_wayland_desktop() -> bool {
 }
 
 /**
- * Get usage memory of window from proc
+ * To known if the desktop is GNOME from `XDG_CURRENT_DESKTOP`, like "GNOME" or "ubuntu:GNOME"
+ */
+pub fn is_gnome_desktop() -> bool {
+  std::env::var("XDG_CURRENT_DESKTOP")
+    .map(|desktop| desktop.to_uppercase().contains("GNOME"))
+    .unwrap_or(false)
+}
+
+/**
+ * Get usage memory of window from proc.
+ * Memory changes on each call and is not read from a sandbox, it would spawn a host process per window.
  */
 pub fn get_window_memory_usage(pid: u32) -> Result<u32> {
-  let mut statm_file = File::open(format!("/proc/{pid}/statm"))?;
-  let mut statm_content = String::new();
-  statm_file.read_to_string(&mut statm_content)?;
+  if is_flatpak() {
+    return Ok(0);
+  }
+  let statm_content = fs::read_to_string(format!("/proc/{pid}/statm"))?;
   let statm_parts: Vec<&str> = statm_content.split(" ").collect();
   let memory_usage = statm_parts[0].parse()?;
   Ok(memory_usage)
@@ -41,7 +61,13 @@ pub fn get_window_memory_usage(pid: u32) -> Result<u32> {
  * Recover path and name of application from proc
  */
 pub fn get_window_path_name(pid: u32) -> Result<(String, String)> {
-  let executable_path = read_link(format!("/proc/{pid}/exe"))?;
+  let executable_path = match is_flatpak() {
+    true => host_executable_path(pid, || read_host_link(&format!("/proc/{pid}/exe"))),
+    false => fs::read_link(format!("/proc/{pid}/exe"))?,
+  };
+  if executable_path.as_os_str().is_empty() {
+    return Ok((String::new(), String::new()));
+  }
   let path = executable_path.display().to_string();
   let name = match executable_path.file_name() {
     Some(file_name) => file_name.to_string_lossy().to_string(),
@@ -50,23 +76,108 @@ pub fn get_window_path_name(pid: u32) -> Result<(String, String)> {
   Ok((path, name))
 }
 
+/**
+ * Executable path of a host process read once with `read`, failures are reported by `sandbox_info()`
+ */
+fn host_executable_path(pid: u32, read: impl FnOnce() -> Result<PathBuf>) -> PathBuf {
+  if let Some(path) = HOST_EXECUTABLE_PATHS
+    .lock()
+    .ok()
+    .and_then(|paths| paths.get(&pid).cloned())
+  {
+    return path;
+  }
+  let path = match read() {
+    Ok(path) => {
+      set_host_process_error(None);
+      path
+    }
+    Err(err) => {
+      set_host_process_error(Some(format!(
+        "Unable to read /proc/{pid}/exe on the host: {err}"
+      )));
+      PathBuf::new()
+    }
+  };
+  if let Ok(mut paths) = HOST_EXECUTABLE_PATHS.lock() {
+    if paths.len() >= HOST_EXECUTABLE_PATHS_CAPACITY {
+      // Pids of closed processes are dropped with the others, paths of open windows are read again once
+      paths.clear();
+    }
+    paths.insert(pid, path.clone());
+  }
+  path
+}
+
 pub fn init_entity() -> WindowInfo {
   let mut window_info: WindowInfo = empty_entity();
   window_info.os = os_name();
   window_info
 }
 
-pub fn get_gnome_version() -> String {
-  if let Ok(output) = Command::new("gnome-shell").arg("--version").output() {
-    if output.status.success() {
-      let stdout = String::from_utf8_lossy(&output.stdout);
-      let version = stdout.split_whitespace().nth(2).unwrap_or("999");
-      return version.to_owned();
-    }
+/**
+ * Version of GNOME Shell from `gnome-shell --version`, not available inside sandboxes and containers
+ */
+pub fn gnome_shell_binary_version() -> Option<String> {
+  let output = host_command("gnome-shell").arg("--version").output().ok()?;
+  if !output.status.success() {
+    return None;
   }
-  "999".into()
+  parse_gnome_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/**
+ * Extract the version from an output like "GNOME Shell 45.2"
+ */
+fn parse_gnome_version(output: &str) -> Option<String> {
+  output
+    .split_whitespace()
+    .find(|part| part.starts_with(|c: char| c.is_ascii_digit()))
+    .map(|version| version.to_owned())
 }
 
-pub fn get_browser_url() -> String {
-  String::from("URL recovery not supported on Linux distribution!")
+/**
+ * Major version of a GNOME Shell version like "45.2"
+ */
+pub fn gnome_major_version(version: &str) -> Option<u32> {
+  version.split('.').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::{gnome_major_version, host_executable_path, parse_gnome_version};
+  use crate::linux::api::flatpak::host_process_error;
+
+  #[test]
+  fn test_host_executable_path() {
+    let path = host_executable_path(u32::MAX, || Ok(PathBuf::from("/usr/bin/code")));
+    assert_eq!(path, PathBuf::from("/usr/bin/code"));
+    // Path is not read again for the same process
+    let path = host_executable_path(u32::MAX, || Err("not called".into()));
+    assert_eq!(path, PathBuf::from("/usr/bin/code"));
+
+    let path = host_executable_path(u32::MAX - 1, || Err("permission denied".into()));
+    assert_eq!(path, PathBuf::new());
+    assert!(host_process_error()
+      .map(|err| err.contains("permission denied"))
+      .unwrap_or(false));
+  }
+
+  #[test]
+  fn test_parse_gnome_version() {
+    assert_eq!(
+      parse_gnome_version("GNOME Shell 45.2\n"),
+      Some(String::from("45.2"))
+    );
+    assert_eq!(
+      parse_gnome_version("GNOME Shell 46.rc"),
+      Some(String::from("46.rc"))
+    );
+    assert_eq!(parse_gnome_version("GNOME Shell"), None);
+    assert_eq!(gnome_major_version("45.2"), Some(45));
+    assert_eq!(gnome_major_version("40"), Some(40));
+    assert_eq!(gnome_major_version("unknown"), None);
+  }
 }
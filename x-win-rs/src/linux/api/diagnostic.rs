@@ -0,0 +1,206 @@
+#![deny(unused_imports)]
+
+use std::env;
+
+use crate::common::{
+  result::Result,
+  x_win_struct::{
+    diagnostic_report::{DiagnosticReport, SessionType},
+    extension_status::ExtensionStatus,
+  },
+};
+
+use super::{
+  common_api::{gnome_major_version, is_wayland_desktop, os_name},
+  gnome_dbus::{extension_proxy, shell_proxy, with_session},
+  gnome_shell::gnome_shell_version,
+  wayland_extension_api,
+};
+
+/**
+ * Collect the state of the session, GNOME Shell and its extension
+ */
+pub fn diagnose() -> DiagnosticReport {
+  let mut report = DiagnosticReport::new(os_name(), session_type());
+  report.desktop = env::var("XDG_CURRENT_DESKTOP")
+    .ok()
+    .filter(|desktop| !desktop.is_empty());
+  report.gnome_shell_version = gnome_shell_version();
+
+  if report.session_type.eq(&SessionType::Wayland) && is_gnome(&report) {
+    let use_eval = report
+      .gnome_shell_version
+      .as_deref()
+      .and_then(gnome_major_version)
+      .map(|version| version < 41)
+      .unwrap_or(false);
+    let mut errors = vec![];
+    if !use_eval {
+      report.extension_status = wayland_extension_api::extension_status()
+        .map_err(|err| errors.push(err.to_string()))
+        .ok();
+    }
+    let ping = if use_eval {
+      ping_eval()
+    } else {
+      ping_extension()
+    };
+    report.dbus_responds = Some(ping.is_ok());
+    if let Err(err) = ping {
+      errors.push(err.to_string());
+    }
+    report.dbus_error = errors.into_iter().next();
+  }
+
+  report.suggestions = suggestions(&report);
+  report
+}
+
+pub fn session_type() -> SessionType {
+  match env::var("XDG_SESSION_TYPE").as_deref() {
+    Ok("wayland") => SessionType::Wayland,
+    Ok("x11") => SessionType::X11,
+    _ if is_wayland_desktop() => SessionType::Wayland,
+    _ if env::var_os("DISPLAY").is_some() => SessionType::X11,
+    _ => SessionType::Unknown,
+  }
+}
+
+fn is_gnome(report: &DiagnosticReport) -> bool {
+  report
+    .desktop
+    .as_deref()
+    .map(|desktop| desktop.to_uppercase().contains("GNOME"))
+    .unwrap_or(report.gnome_shell_version.is_some())
+}
+
+/**
+ * Read the schema version of the extension on its object path
+ */
+fn ping_extension() -> Result<()> {
+  with_session(|connection| extension_proxy(connection).map(|_| ()))
+}
+
+fn ping_eval() -> Result<()> {
+  let (success, response) = with_session(|connection| Ok(shell_proxy(connection)?.eval("true")?))?;
+  if !success {
+    return Err(format!("GNOME Shell refused to evaluate scripts: {response}").into());
+  }
+  Ok(())
+}
+
+/**
+ * Suggest how to fix the setup described by the report
+ */
+fn suggestions(report: &DiagnosticReport) -> Vec<String> {
+  let mut suggestions: Vec<String> = vec![];
+  match report.session_type {
+    SessionType::Unknown => suggestions.push(
+      "No graphical session detected, `WAYLAND_DISPLAY` or `DISPLAY` needs to be set.".into(),
+    ),
+    SessionType::Wayland if !is_gnome(report) => suggestions.push(
+      "Only GNOME Shell is supported with Wayland, other compositors do not share their windows."
+        .into(),
+    ),
+    _ => {}
+  }
+  match &report.extension_status {
+    Some(ExtensionStatus::NotInstalled) => suggestions.push(
+      "Install the extension with `install_extension()` then log out and log back in.".into(),
+    ),
+    Some(ExtensionStatus::Outdated) => suggestions.push(
+      "Update the extension with `install_extension()` then log out and log back in.".into(),
+    ),
+    Some(ExtensionStatus::InstalledNeedsRestart) => {
+      suggestions.push("Log out and log back in so GNOME Shell loads the extension.".into())
+    }
+    Some(ExtensionStatus::Disabled) => {
+      suggestions.push("Enable the extension with `enable_extension()`.".into())
+    }
+    Some(ExtensionStatus::Error(_)) => suggestions.push(
+      "Check `journalctl --user -b /usr/bin/gnome-shell` for the extension error then reinstall it with `install_extension()`."
+        .into(),
+    ),
+    Some(ExtensionStatus::Enabled) if report.dbus_responds.eq(&Some(false)) => suggestions.push(
+      "The extension is enabled but does not answer over D-Bus, reinstall it with `install_extension()` then log out and log back in."
+        .into(),
+    ),
+    _ => {}
+  }
+  if report.extension_status.is_none() && report.dbus_responds.eq(&Some(false)) {
+    suggestions.push(
+      "GNOME Shell does not answer over D-Bus, check that `org.gnome.Shell` is available on the session bus."
+        .into(),
+    );
+  }
+  suggestions
+}
+
+#[cfg(test)]
+mod tests {
+  use super::suggestions;
+  use crate::common::x_win_struct::{
+    diagnostic_report::{DiagnosticReport, SessionType},
+    extension_status::ExtensionStatus,
+  };
+
+  fn gnome_report(
+    extension_status: Option<ExtensionStatus>,
+    dbus_responds: bool,
+  ) -> DiagnosticReport {
+    let mut report = DiagnosticReport::new(String::from("linux"), SessionType::Wayland);
+    report.desktop = Some(String::from("ubuntu:GNOME"));
+    report.gnome_shell_version = Some(String::from("46.0"));
+    report.extension_status = extension_status;
+    report.dbus_responds = Some(dbus_responds);
+    report
+  }
+
+  #[test]
+  fn test_suggestions() {
+    assert!(suggestions(&gnome_report(Some(ExtensionStatus::Enabled), true)).is_empty());
+    assert!(
+      suggestions(&gnome_report(Some(ExtensionStatus::NotInstalled), false))[0]
+        .contains("install_extension()")
+    );
+    assert!(
+      suggestions(&gnome_report(Some(ExtensionStatus::Disabled), false))[0]
+        .contains("enable_extension()")
+    );
+    assert!(suggestions(&gnome_report(
+      Some(ExtensionStatus::InstalledNeedsRestart),
+      false
+    ))[0]
+      .contains("log back in"));
+    assert!(suggestions(&gnome_report(
+      Some(ExtensionStatus::Error(String::from("SyntaxError"))),
+      false
+    ))[0]
+      .contains("journalctl"));
+    assert!(
+      suggestions(&gnome_report(Some(ExtensionStatus::Enabled), false))[0]
+        .contains("does not answer")
+    );
+    assert!(suggestions(&gnome_report(None, false))[0].contains("org.gnome.Shell"));
+
+    let mut report = DiagnosticReport::new(String::from("linux"), SessionType::Wayland);
+    report.desktop = Some(String::from("KDE"));
+    assert!(suggestions(&report)[0].contains("Only GNOME Shell"));
+    assert_eq!(
+      suggestions(&DiagnosticReport::new(
+        String::from("linux"),
+        SessionType::X11
+      ))
+      .len(),
+      0
+    );
+    assert_eq!(
+      suggestions(&DiagnosticReport::new(
+        String::from("linux"),
+        SessionType::Unknown
+      ))
+      .len(),
+      1
+    );
+  }
+}
@@ -0,0 +1,194 @@
+#![deny(unused_imports)]
+
+use std::{
+  env, fs,
+  path::{Path, PathBuf},
+  process::Command,
+  sync::Mutex,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::common::{result::Result, x_win_struct::sandbox_info::SandboxInfo};
+
+use super::{
+  gnome_dbus::{shell_owner, with_session},
+  gnome_shell::GNOME_EXTENSIONS_FOLDER_PATH,
+};
+
+/// File mounted by Flatpak at the root of every sandbox
+const FLATPAK_INFO_PATH: &str = "/.flatpak-info";
+
+/**
+ * Last failure to read a host process from the sandbox, cleared by the next successful read
+ */
+static HOST_PROCESS_ERROR: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_host_process_error(err: Option<String>) {
+  if let Ok(mut host_process_error) = HOST_PROCESS_ERROR.lock() {
+    *host_process_error = err;
+  }
+}
+
+pub fn host_process_error() -> Option<String> {
+  HOST_PROCESS_ERROR
+    .lock()
+    .ok()
+    .and_then(|host_process_error| host_process_error.clone())
+}
+
+/**
+ * Application id when x-win runs inside a Flatpak sandbox
+ */
+pub fn flatpak_id() -> Option<String> {
+  if let Some(id) = env::var("FLATPAK_ID").ok().filter(|id| !id.is_empty()) {
+    return Some(id);
+  }
+  let info = fs::read_to_string(FLATPAK_INFO_PATH).ok()?;
+  Some(parse_flatpak_info_name(&info).unwrap_or_default())
+}
+
+pub fn is_flatpak() -> bool {
+  env::var("FLATPAK_ID")
+    .map(|id| !id.is_empty())
+    .unwrap_or(false)
+    || Path::new(FLATPAK_INFO_PATH).exists()
+}
+
+/**
+ * Read `name` of the `[Application]` section of `/.flatpak-info`
+ */
+fn parse_flatpak_info_name(info: &str) -> Option<String> {
+  let mut in_application = false;
+  for line in info.lines().map(str::trim) {
+    if line.starts_with('[') {
+      in_application = line.eq("[Application]");
+    } else if in_application {
+      if let Some(name) = line.strip_prefix("name=") {
+        return Some(name.trim().to_owned());
+      }
+    }
+  }
+  None
+}
+
+/**
+ * Command running `program` on the host, through `flatpak-spawn --host` inside a Flatpak sandbox.
+ * `flatpak-spawn --host` needs the `--talk-name=org.freedesktop.Flatpak` permission.
+ */
+pub fn host_command(program: &str) -> Command {
+  command_for(is_flatpak(), program)
+}
+
+fn command_for(sandboxed: bool, program: &str) -> Command {
+  if sandboxed {
+    let mut command = Command::new("flatpak-spawn");
+    command.args(["--host", program]);
+    command
+  } else {
+    Command::new(program)
+  }
+}
+
+/**
+ * Run `program` on the host and return its output
+ */
+fn host_output(program: &str, args: &[&str]) -> Result<String> {
+  let output = host_command(program).args(args).output()?;
+  if !output.status.success() {
+    return Err(
+      format!(
+        "{program} failed on the host: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+      )
+      .into(),
+    );
+  }
+  Ok(
+    String::from_utf8_lossy(&output.stdout)
+      .trim_end()
+      .to_owned(),
+  )
+}
+
+/**
+ * Read a link of the host, processes of the host are not visible in `/proc` of the sandbox
+ */
+pub fn read_host_link(path: &str) -> Result<PathBuf> {
+  if is_flatpak() {
+    return Ok(PathBuf::from(host_output("readlink", &[path])?));
+  }
+  Ok(fs::read_link(path)?)
+}
+
+/**
+ * Home folder of the user on the host
+ */
+pub fn host_home() -> Result<PathBuf> {
+  if is_flatpak() {
+    if let Ok(home_dir) = host_output("printenv", &["HOME"]) {
+      if !home_dir.is_empty() {
+        return Ok(PathBuf::from(home_dir));
+      }
+    }
+  }
+  let home_dir = env::var_os("HOME").ok_or("HOME env not available")?;
+  Ok(PathBuf::from(home_dir))
+}
+
+/**
+ * Tell what x-win can reach from the sandbox it runs in
+ */
+pub fn sandbox_info() -> SandboxInfo {
+  let flatpak_id = match flatpak_id() {
+    Some(flatpak_id) => flatpak_id,
+    None => return SandboxInfo::unsandboxed(),
+  };
+  let host_commands = host_output("true", &[]).is_ok();
+  let extension_install = host_home()
+    .map(|home_dir| {
+      home_dir
+        .join(".local")
+        .join(GNOME_EXTENSIONS_FOLDER_PATH)
+        .is_dir()
+    })
+    .unwrap_or(false);
+  let process_info_error = host_process_error();
+  SandboxInfo {
+    flatpak_id: Some(flatpak_id),
+    host_commands,
+    process_info: host_commands && process_info_error.is_none(),
+    process_info_error,
+    extension_install,
+    gnome_shell_bus: with_session(shell_owner).is_ok(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{command_for, parse_flatpak_info_name};
+
+  #[test]
+  fn test_parse_flatpak_info_name() {
+    let info = "[Application]\nname=org.example.App\nruntime=runtime/org.gnome.Platform/x86_64/46\n\n[Instance]\nname=other\n";
+    assert_eq!(
+      parse_flatpak_info_name(info),
+      Some(String::from("org.example.App"))
+    );
+    assert_eq!(parse_flatpak_info_name("[Instance]\nname=other\n"), None);
+    assert_eq!(parse_flatpak_info_name(""), None);
+  }
+
+  #[test]
+  fn test_command_for() {
+    let command = command_for(true, "gsettings");
+    assert_eq!(command.get_program(), "flatpak-spawn");
+    assert_eq!(
+      command.get_args().collect::<Vec<_>>(),
+      vec!["--host", "gsettings"]
+    );
+    let command = command_for(false, "gsettings");
+    assert_eq!(command.get_program(), "gsettings");
+    assert_eq!(command.get_args().count(), 0);
+  }
+}
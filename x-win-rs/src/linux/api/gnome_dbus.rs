@@ -0,0 +1,884 @@
+#![deny(unused_imports)]
+
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+  },
+};
+
+use once_cell::sync::Lazy;
+use zbus::{
+  blocking::Connection,
+  proxy,
+  proxy::CacheProperties,
+  zvariant::{DeserializeDict, OwnedValue, SerializeDict, Type},
+};
+
+use crate::common::{
+  error::{Error, XWinError},
+  result::Result,
+  x_win_struct::{
+    icon_data::IconData, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+    window_position::WindowPosition,
+  },
+};
+
+#[cfg(feature = "async")]
+use crate::r#async::{send_error, unblock, Error as AsyncError, Result as AsyncResult};
+
+use super::{
+  common_api::os_name,
+  gnome_extension::GNOME_XWIN_SCHEMA_VERSION,
+  gnome_shell::{DESTINATION, GNOME_XWIN_UUID, XWIN_IFACE, XWIN_PATH},
+};
+
+/**
+ * Window dictionary returned by the extension, every key is required
+ */
+#[derive(Debug, Clone, PartialEq, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct ExtensionWindow {
+  pub id: u32,
+  pub os: String,
+  pub title: String,
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
+  pub is_full_screen: bool,
+  pub process_id: u32,
+  pub path: String,
+  pub name: String,
+  pub exec_name: String,
+  pub memory: u32,
+}
+
+impl From<ExtensionWindow> for WindowInfo {
+  fn from(value: ExtensionWindow) -> Self {
+    WindowInfo::new(
+      value.id,
+      os_name(),
+      value.title,
+      WindowPosition::new(
+        value.x,
+        value.y,
+        value.width,
+        value.height,
+        value.is_full_screen,
+      ),
+      ProcessInfo::new(value.process_id, value.path, value.name, value.exec_name),
+      UsageInfo::new(value.memory),
+    )
+  }
+}
+
+/**
+ * Icon dictionary returned by the extension with raw image bytes
+ */
+#[derive(Debug, Clone, PartialEq, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct ExtensionIcon {
+  pub data: Vec<u8>,
+  pub mime: String,
+  pub height: u32,
+  pub width: u32,
+}
+
+impl From<ExtensionIcon> for IconData {
+  fn from(value: ExtensionIcon) -> Self {
+    IconData::new(value.data, value.mime, value.height, value.width)
+  }
+}
+
+/**
+ * Declare the D-Bus interface of the extension once, both the XML written in the extension and the proxy are generated from it
+ */
+macro_rules! extension_interface {
+  (
+    interface $interface:tt at $path:tt;
+    property $property:ident as $property_name:tt: $property_type:ty = $property_signature:tt;
+    $(
+      method $method:ident as $method_name:tt($($arg:ident: $arg_type:ty = $arg_signature:tt),*)
+        -> $out:ident: $out_type:ty = $out_signature:tt;
+    )*
+  ) => {
+    /**
+     * Windows are `a{sv}` dictionaries with `id`, `os`, `title`, `x`, `y`, `width`, `height`, `is_full_screen`,
+     * `process_id`, `path`, `name`, `exec_name` and `memory` keys.
+     * Icons are `a{sv}` dictionaries with `data` (`ay`), `mime`, `width` and `height` keys.
+     */
+    pub const GNOME_XWIN_DBUS_INTERFACE: &str = concat!(
+      "<node>\n",
+      "  <interface name=\"", $interface, "\">\n",
+      "    <property name=\"", $property_name, "\" type=\"", $property_signature, "\" access=\"read\" />\n",
+      $(
+        "    <method name=\"", $method_name, "\">\n",
+        $("      <arg name=\"", stringify!($arg), "\" type=\"", $arg_signature, "\" direction=\"in\" />\n",)*
+        "      <arg name=\"", stringify!($out), "\" type=\"", $out_signature, "\" direction=\"out\" />\n",
+        "    </method>\n",
+      )*
+      "  </interface>\n",
+      "</node>"
+    );
+
+    /**
+     * Proxy of `GNOME_XWIN_DBUS_INTERFACE`
+     */
+    #[proxy(
+      interface = $interface,
+      default_service = "org.gnome.Shell",
+      default_path = $path,
+      blocking_name = "XWinExtensionProxy",
+      async_name = "XWinExtensionProxyAsync"
+    )]
+    // Async proxy is only used with the `async` feature
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    pub trait XWinExtension {
+      $(
+        #[zbus(name = $method_name)]
+        fn $method(&self, $($arg: $arg_type),*) -> zbus::Result<$out_type>;
+      )*
+
+      #[zbus(property, name = $property_name)]
+      fn $property(&self) -> zbus::Result<$property_type>;
+    }
+
+    /**
+     * Signature of every argument given by its Rust type and by `GNOME_XWIN_DBUS_INTERFACE`
+     */
+    #[cfg(test)]
+    fn interface_signatures() -> Vec<(String, &'static str)> {
+      vec![
+        (<$property_type as Type>::SIGNATURE.to_string(), $property_signature),
+        $(
+          $((<$arg_type as Type>::SIGNATURE.to_string(), $arg_signature),)*
+          (<$out_type as Type>::SIGNATURE.to_string(), $out_signature),
+        )*
+      ]
+    }
+  };
+}
+
+extension_interface! {
+  interface "org.gnome.Shell.Extensions.XWinWaylandExtension"
+    at "/org/gnome/Shell/Extensions/XWinWaylandExtension";
+  property schema_version as "SchemaVersion": u32 = "u";
+  method get_active_window as "get_active_window"() -> window: ExtensionWindow = "a{sv}";
+  method get_open_windows as "get_open_windows"() -> windows: Vec<ExtensionWindow> = "aa{sv}";
+  method get_icon as "get_icon"(window_id: u32 = "u", size: u32 = "u") -> icon: ExtensionIcon = "a{sv}";
+}
+
+/**
+ * Proxy of `org.gnome.Shell.Extensions` interface used to manage the extension
+ */
+#[proxy(
+  interface = "org.gnome.Shell.Extensions",
+  default_service = "org.gnome.Shell",
+  default_path = "/org/gnome/Shell",
+  gen_async = false
+)]
+pub trait ShellExtensions {
+  fn enable_extension(&self, uuid: &str) -> zbus::Result<bool>;
+
+  fn disable_extension(&self, uuid: &str) -> zbus::Result<bool>;
+
+  fn get_extension_info(&self, uuid: &str) -> zbus::Result<HashMap<String, OwnedValue>>;
+}
+
+/**
+ * Proxy of `org.gnome.Shell` interface used to evaluate scripts on GNOME Shell < 41
+ */
+#[proxy(
+  interface = "org.gnome.Shell",
+  default_service = "org.gnome.Shell",
+  default_path = "/org/gnome/Shell",
+  gen_async = false
+)]
+pub trait Shell {
+  fn eval(&self, script: &str) -> zbus::Result<(bool, String)>;
+
+  #[zbus(property)]
+  fn shell_version(&self) -> zbus::Result<String>;
+}
+
+/**
+ * Proxy of the idle monitor of Mutter, telling the time since the last input of the user
+ */
+#[proxy(
+  interface = "org.gnome.Mutter.IdleMonitor",
+  default_service = "org.gnome.Mutter.IdleMonitor",
+  default_path = "/org/gnome/Mutter/IdleMonitor/Core",
+  gen_async = false
+)]
+pub trait IdleMonitor {
+  /**
+   * Milliseconds since the last input
+   */
+  fn get_idletime(&self) -> zbus::Result<u64>;
+}
+
+pub fn shell_extensions_proxy(connection: &Connection) -> Result<ShellExtensionsProxy<'_>> {
+  Ok(
+    ShellExtensionsProxy::builder(connection)
+      .cache_properties(CacheProperties::No)
+      .build()?,
+  )
+}
+
+pub fn idle_monitor_proxy(connection: &Connection) -> Result<IdleMonitorProxy<'_>> {
+  Ok(
+    IdleMonitorProxy::builder(connection)
+      .cache_properties(CacheProperties::No)
+      .build()?,
+  )
+}
+
+pub fn shell_proxy(connection: &Connection) -> Result<ShellProxy<'_>> {
+  Ok(
+    ShellProxy::builder(connection)
+      .cache_properties(CacheProperties::No)
+      .build()?,
+  )
+}
+
+/**
+ * Unique name owning `org.gnome.Shell`, changed each time GNOME Shell restarts
+ */
+pub fn shell_owner(connection: &Connection) -> Result<String> {
+  let owner = zbus::blocking::fdo::DBusProxy::new(connection)?.get_name_owner(
+    zbus::names::BusName::try_from(DESTINATION.unwrap_or_default())?,
+  )?;
+  Ok(owner.to_string())
+}
+
+/**
+ * Connection opened on first use and shared by every call, opened again once the bus is disconnected
+ */
+pub struct SharedConnection {
+  connect: Box<dyn Fn() -> zbus::Result<Connection> + Send + Sync>,
+  connection: Mutex<Option<Connection>>,
+  /**
+   * Schema version of the extension has been checked on the current connection
+   */
+  handshake: AtomicBool,
+}
+
+impl SharedConnection {
+  pub fn new(connect: impl Fn() -> zbus::Result<Connection> + Send + Sync + 'static) -> Self {
+    Self {
+      connect: Box::new(connect),
+      connection: Mutex::new(None),
+      handshake: AtomicBool::new(false),
+    }
+  }
+
+  /**
+   * Run `call` with the proxy to the extension, the schema version is read once per connection.
+   * It is checked again after a failed call as the extension may have been disabled or updated.
+   */
+  pub fn call_extension<T>(
+    &self,
+    call: impl Fn(&XWinExtensionProxy<'_>) -> Result<T>,
+  ) -> Result<T> {
+    self.with(|connection| {
+      let proxy = match self.handshake.load(Ordering::SeqCst) {
+        true => build_extension_proxy(connection)?,
+        false => {
+          let proxy = extension_proxy(connection)?;
+          self.handshake.store(true, Ordering::SeqCst);
+          proxy
+        }
+      };
+      let response = call(&proxy);
+      if response.is_err() {
+        self.handshake.store(false, Ordering::SeqCst);
+      }
+      response
+    })
+  }
+
+  /**
+   * Run `call` with the shared connection, retried once on a new connection when the bus was disconnected
+   */
+  pub fn with<T>(&self, call: impl Fn(&Connection) -> Result<T>) -> Result<T> {
+    match call(&self.connection()?) {
+      Err(err) if is_disconnected(&err) => {
+        self.reset()?;
+        call(&self.connection()?)
+      }
+      response => response,
+    }
+  }
+
+  fn connection(&self) -> Result<Connection> {
+    let mut connection = self.connection.lock().map_err(|err| err.to_string())?;
+    match connection.as_ref() {
+      Some(current) if !current.is_closed() => Ok(current.clone()),
+      _ => {
+        let current = (self.connect)()?;
+        self.handshake.store(false, Ordering::SeqCst);
+        *connection = Some(current.clone());
+        Ok(current)
+      }
+    }
+  }
+
+  /**
+   * Return the connection when it is already opened, without waiting for the lock
+   */
+  #[cfg(feature = "async")]
+  fn current(&self) -> Option<Connection> {
+    self
+      .connection
+      .try_lock()
+      .ok()
+      .and_then(|connection| connection.clone())
+      .filter(|connection| !connection.is_closed())
+  }
+
+  fn reset(&self) -> Result<()> {
+    *self.connection.lock().map_err(|err| err.to_string())? = None;
+    self.handshake.store(false, Ordering::SeqCst);
+    Ok(())
+  }
+}
+
+/**
+ * Session bus connection used to talk with GNOME Shell
+ */
+#[cfg(not(test))]
+static SESSION: Lazy<SharedConnection> = Lazy::new(|| SharedConnection::new(Connection::session));
+
+/**
+ * System bus connection used to talk with logind
+ */
+#[cfg(not(test))]
+static SYSTEM: Lazy<SharedConnection> = Lazy::new(|| SharedConnection::new(Connection::system));
+
+/**
+ * Tests redirect both connections to the private bus given by `set_test_session`
+ */
+#[cfg(test)]
+static SESSION: Lazy<SharedConnection> =
+  Lazy::new(|| SharedConnection::new(super::test_dbus::test_session_connection));
+
+#[cfg(test)]
+static SYSTEM: Lazy<SharedConnection> =
+  Lazy::new(|| SharedConnection::new(super::test_dbus::test_system_connection));
+
+/**
+ * Close the shared connections to open them again on the bus given by `set_test_session`
+ */
+#[cfg(test)]
+pub fn reset_connections() {
+  let _ = SESSION.reset();
+  let _ = SYSTEM.reset();
+}
+
+/**
+ * Run `call` with the shared session bus connection
+ */
+pub fn with_session<T>(call: impl Fn(&Connection) -> Result<T>) -> Result<T> {
+  SESSION.with(call)
+}
+
+/**
+ * Run `call` with the proxy to the extension on the shared session bus connection
+ */
+pub fn call_extension<T>(call: impl Fn(&XWinExtensionProxy<'_>) -> Result<T>) -> Result<T> {
+  SESSION.call_extension(call)
+}
+
+/**
+ * Run `call` with the shared system bus connection
+ */
+pub fn with_system<T>(call: impl Fn(&Connection) -> Result<T>) -> Result<T> {
+  SYSTEM.with(call)
+}
+
+/**
+ * Run `call` with the async side of the shared session bus connection, retried once on a new connection when the bus was disconnected
+ */
+#[cfg(feature = "async")]
+pub async fn with_session_async<T, F, Fut>(call: F) -> AsyncResult<T>
+where
+  F: Fn(zbus::Connection) -> Fut,
+  Fut: Future<Output = AsyncResult<T>>,
+{
+  match call(session_async().await?).await {
+    Err(err) if is_disconnected_async(&err) => {
+      SESSION.reset().map_err(send_error)?;
+      call(session_async().await?).await
+    }
+    response => response,
+  }
+}
+
+/**
+ * Opening the connection blocks on the authentication, it is done on the thread pool
+ */
+#[cfg(feature = "async")]
+async fn session_async() -> AsyncResult<zbus::Connection> {
+  if let Some(connection) = SESSION.current() {
+    return Ok(connection.into_inner());
+  }
+  unblock(|| SESSION.connection())
+    .await
+    .map(|connection| connection.into_inner())
+}
+
+#[cfg(feature = "async")]
+fn is_disconnected_async(err: &AsyncError) -> bool {
+  err
+    .downcast_ref::<zbus::Error>()
+    .map(is_disconnected_error)
+    .unwrap_or(false)
+}
+
+fn is_disconnected(err: &Error) -> bool {
+  err
+    .downcast_ref::<zbus::Error>()
+    .map(is_disconnected_error)
+    .unwrap_or(false)
+}
+
+fn is_disconnected_error(err: &zbus::Error) -> bool {
+  match err {
+    zbus::Error::InputOutput(_) => true,
+    zbus::Error::FDO(err) => matches!(**err, zbus::fdo::Error::Disconnected(_)),
+    zbus::Error::MethodError(name, _, _) => {
+      name.as_str() == "org.freedesktop.DBus.Error.Disconnected"
+    }
+    _ => false,
+  }
+}
+
+fn build_extension_proxy(connection: &Connection) -> zbus::Result<XWinExtensionProxy<'_>> {
+  XWinExtensionProxy::builder(connection)
+    .cache_properties(CacheProperties::No)
+    .build()
+}
+
+/**
+ * Return a proxy to the extension after checking that it exposes the expected schema version
+ */
+pub fn extension_proxy(connection: &Connection) -> Result<XWinExtensionProxy<'_>> {
+  let proxy = build_extension_proxy(connection)?;
+  let version = match proxy.schema_version() {
+    Ok(version) => version,
+    Err(err) if is_disconnected_error(&err) => return Err(err.into()),
+    // Extensions returning JSON strings did not have the property
+    Err(_) if has_extension_interface(connection) => 1,
+    Err(_) => return Err(extension_unreachable().into()),
+  };
+  check_schema_version(version)?;
+  Ok(proxy)
+}
+
+/**
+ * Async version of `call_extension`, the schema version is checked on the same terms
+ */
+#[cfg(feature = "async")]
+pub async fn call_extension_async<T, F, Fut>(call: F) -> AsyncResult<T>
+where
+  F: Fn(XWinExtensionProxyAsync<'static>) -> Fut,
+  Fut: Future<Output = zbus::Result<T>>,
+{
+  let call = &call;
+  with_session_async(|connection| async move {
+    let proxy = match SESSION.handshake.load(Ordering::SeqCst) {
+      true => build_extension_proxy_async(&connection).await?,
+      false => {
+        let proxy = extension_proxy_async(&connection).await?;
+        SESSION.handshake.store(true, Ordering::SeqCst);
+        proxy
+      }
+    };
+    let response = call(proxy).await;
+    if response.is_err() {
+      SESSION.handshake.store(false, Ordering::SeqCst);
+    }
+    Ok(response?)
+  })
+  .await
+}
+
+#[cfg(feature = "async")]
+async fn build_extension_proxy_async(
+  connection: &zbus::Connection,
+) -> zbus::Result<XWinExtensionProxyAsync<'static>> {
+  XWinExtensionProxyAsync::builder(connection)
+    .cache_properties(CacheProperties::No)
+    .build()
+    .await
+}
+
+/**
+ * Async version of `extension_proxy`
+ */
+#[cfg(feature = "async")]
+pub async fn extension_proxy_async(
+  connection: &zbus::Connection,
+) -> AsyncResult<XWinExtensionProxyAsync<'static>> {
+  let proxy = build_extension_proxy_async(connection).await?;
+  let version = match proxy.schema_version().await {
+    Ok(version) => version,
+    Err(err) if is_disconnected_error(&err) => return Err(err.into()),
+    Err(_) if has_extension_interface_async(connection).await => 1,
+    Err(_) => return Err(extension_unreachable().into()),
+  };
+  check_schema_version(version).map_err(send_error)?;
+  Ok(proxy)
+}
+
+fn extension_unreachable() -> String {
+  format!(
+    r#"Unable to reach "{GNOME_XWIN_UUID}" extension via GNOME Shell. Please verify that the extension is correctly installed or enabled."#
+  )
+}
+
+fn check_schema_version(version: u32) -> Result<()> {
+  if version.ne(&GNOME_XWIN_SCHEMA_VERSION) {
+    return Err(
+      XWinError::IncompatibleExtension {
+        found: version,
+        expected: GNOME_XWIN_SCHEMA_VERSION,
+      }
+      .into(),
+    );
+  }
+  Ok(())
+}
+
+fn has_extension_interface(connection: &Connection) -> bool {
+  connection
+    .call_method(
+      DESTINATION,
+      XWIN_PATH,
+      Some("org.freedesktop.DBus.Introspectable"),
+      "Introspect",
+      &(),
+    )
+    .ok()
+    .and_then(|response| response.body().deserialize::<String>().ok())
+    .map(|xml| xml.contains(XWIN_IFACE.unwrap_or_default()))
+    .unwrap_or(false)
+}
+
+#[cfg(feature = "async")]
+async fn has_extension_interface_async(connection: &zbus::Connection) -> bool {
+  match connection
+    .call_method(
+      DESTINATION,
+      XWIN_PATH,
+      Some("org.freedesktop.DBus.Introspectable"),
+      "Introspect",
+      &(),
+    )
+    .await
+  {
+    Ok(response) => response
+      .body()
+      .deserialize::<String>()
+      .map(|xml| xml.contains(XWIN_IFACE.unwrap_or_default()))
+      .unwrap_or(false),
+    Err(_) => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc, Mutex,
+    },
+    time::Instant,
+  };
+
+  use zbus::blocking::connection;
+
+  use super::{
+    extension_proxy, interface_signatures, is_disconnected, shell_owner, shell_proxy,
+    SharedConnection,
+  };
+  use crate::{
+    common::error::XWinError,
+    linux::api::{
+      gnome_extension::GNOME_XWIN_SCHEMA_VERSION,
+      gnome_shell::{XWIN_IFACE, XWIN_PATH},
+      test_dbus::{
+        fake_window, serve_gnome_shell, FakeExtension, FakeJsonExtension, PrivateBus, ShellState,
+      },
+    },
+  };
+
+  fn serve(bus: &PrivateBus, schema_version: u32) -> zbus::Result<zbus::blocking::Connection> {
+    serve_gnome_shell(
+      &bus.address,
+      Arc::new(Mutex::new(ShellState::default())),
+      Some(FakeExtension::new(schema_version)),
+    )
+  }
+
+  #[test]
+  fn test_extension_proxy() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    assert_eq!(
+      XWIN_IFACE,
+      Some("org.gnome.Shell.Extensions.XWinWaylandExtension")
+    );
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+
+    let proxy = extension_proxy(&client)?;
+    assert_eq!(proxy.get_active_window()?, fake_window(1));
+    assert_eq!(proxy.get_open_windows()?.len(), 2);
+    let icon = proxy.get_icon(7, 32)?;
+    assert_eq!(icon.data, vec![7, 2, 3]);
+    assert_eq!(icon.width, 32);
+
+    let window_info: crate::common::x_win_struct::window_info::WindowInfo = fake_window(1).into();
+    assert_eq!(window_info.position.y, -20);
+    assert_eq!(window_info.info.exec_name, "code");
+    Ok(())
+  }
+
+  #[test]
+  fn test_extension_proxy_schema_version() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION + 1)?;
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+
+    let err = extension_proxy(&client)
+      .err()
+      .ok_or("incompatible extension accepted")?;
+    assert_eq!(
+      err.downcast_ref::<XWinError>(),
+      Some(&XWinError::IncompatibleExtension {
+        found: GNOME_XWIN_SCHEMA_VERSION + 1,
+        expected: GNOME_XWIN_SCHEMA_VERSION,
+      })
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_extension_proxy_handshake() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    let shared = |address: String| {
+      SharedConnection::new(move || connection::Builder::address(address.as_str())?.build())
+    };
+    let client = shared(bus.address.clone());
+    let active_window =
+      |client: &SharedConnection| client.call_extension(|proxy| Ok(proxy.get_active_window()?.id));
+    assert_eq!(active_window(&client)?, 1);
+
+    // Schema version is not read again on the same connection
+    service
+      .object_server()
+      .remove::<FakeExtension, _>(XWIN_PATH)?;
+    service
+      .object_server()
+      .at(XWIN_PATH, FakeExtension::new(GNOME_XWIN_SCHEMA_VERSION + 1))?;
+    assert_eq!(active_window(&client)?, 1);
+    assert!(active_window(&shared(bus.address.clone())).is_err());
+
+    // Failed call checks the schema version again
+    assert!(client
+      .call_extension(|_| Err::<(), _>("failed".into()))
+      .is_err());
+    let err = active_window(&client)
+      .err()
+      .ok_or("incompatible extension accepted")?;
+    assert_eq!(
+      err.downcast_ref::<XWinError>(),
+      Some(&XWinError::IncompatibleExtension {
+        found: GNOME_XWIN_SCHEMA_VERSION + 1,
+        expected: GNOME_XWIN_SCHEMA_VERSION,
+      })
+    );
+
+    // New connection checks the schema version again
+    service
+      .object_server()
+      .remove::<FakeExtension, _>(XWIN_PATH)?;
+    service
+      .object_server()
+      .at(XWIN_PATH, FakeExtension::new(GNOME_XWIN_SCHEMA_VERSION))?;
+    assert_eq!(active_window(&client)?, 1);
+    client.reset()?;
+    service
+      .object_server()
+      .remove::<FakeExtension, _>(XWIN_PATH)?;
+    service
+      .object_server()
+      .at(XWIN_PATH, FakeExtension::new(GNOME_XWIN_SCHEMA_VERSION + 1))?;
+    assert!(active_window(&client).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_interface_signatures() {
+    for (signature, interface_signature) in interface_signatures() {
+      assert_eq!(signature, interface_signature);
+    }
+    assert!(super::GNOME_XWIN_DBUS_INTERFACE
+      .contains(r#"<arg name="window_id" type="u" direction="in" />"#));
+  }
+
+  #[test]
+  fn test_extension_proxy_json_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    // `get_icon(window_id, size)` returning JSON was released without `SchemaVersion`
+    let _service = connection::Builder::address(bus.address.as_str())?
+      .name("org.gnome.Shell")?
+      .serve_at(XWIN_PATH, FakeJsonExtension)?
+      .build()?;
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+
+    let err = extension_proxy(&client)
+      .err()
+      .ok_or("JSON extension accepted")?;
+    assert_eq!(
+      err.downcast_ref::<XWinError>(),
+      Some(&XWinError::IncompatibleExtension {
+        found: 1,
+        expected: GNOME_XWIN_SCHEMA_VERSION,
+      })
+    );
+    Ok(())
+  }
+
+  #[cfg(feature = "async")]
+  #[test]
+  fn test_extension_proxy_async() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+
+    futures_lite::future::block_on(async {
+      let proxy = super::extension_proxy_async(client.inner()).await?;
+      assert_eq!(proxy.get_active_window().await?, fake_window(1));
+      assert_eq!(proxy.get_open_windows().await?.len(), 2);
+      Ok::<(), crate::r#async::Error>(())
+    })
+    .map_err(|err| err.to_string())?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_shared_connection_reconnect() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    let connects = Arc::new(AtomicUsize::new(0));
+    let shared = {
+      let address = bus.address.clone();
+      let connects = connects.clone();
+      SharedConnection::new(move || {
+        connects.fetch_add(1, Ordering::SeqCst);
+        connection::Builder::address(address.as_str())?.build()
+      })
+    };
+
+    for _ in 0..3 {
+      let window =
+        shared.with(|connection| Ok(extension_proxy(connection)?.get_active_window()?))?;
+      assert_eq!(window.id, 1);
+    }
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+
+    // Closed connection is opened again before the call
+    shared.with(|connection| Ok(connection.clone().close()?))?;
+    let windows = shared.with(|connection| Ok(extension_proxy(connection)?.get_open_windows()?))?;
+    assert_eq!(windows.len(), 2);
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+
+    // Disconnected error is retried once on a new connection
+    let calls = AtomicUsize::new(0);
+    let window = shared.with(|connection| {
+      if calls.fetch_add(1, Ordering::SeqCst).eq(&0) {
+        return Err(zbus::Error::from(zbus::fdo::Error::Disconnected(String::from("test"))).into());
+      }
+      Ok(extension_proxy(connection)?.get_active_window()?)
+    })?;
+    assert_eq!(window.id, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(connects.load(Ordering::SeqCst), 3);
+    Ok(())
+  }
+
+  #[test]
+  fn test_shell_version() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let client = connection::Builder::address(bus.address.as_str())?.build()?;
+    assert!(shell_owner(&client).is_err());
+
+    let service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    assert_eq!(shell_proxy(&client)?.shell_version()?, "46.2");
+    let owner = shell_owner(&client)?;
+    assert_eq!(
+      Some(owner.as_str()),
+      service.unique_name().map(|name| name.as_str())
+    );
+
+    // Restarted GNOME Shell gets a new unique name
+    drop(service);
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    assert_ne!(shell_owner(&client)?, owner);
+    Ok(())
+  }
+
+  #[test]
+  fn test_is_disconnected() {
+    let disconnected: crate::common::error::Error =
+      zbus::Error::from(zbus::fdo::Error::Disconnected(String::from("test"))).into();
+    assert!(is_disconnected(&disconnected));
+    let io: crate::common::error::Error =
+      zbus::Error::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe)).into();
+    assert!(is_disconnected(&io));
+    let other: crate::common::error::Error =
+      zbus::Error::from(zbus::fdo::Error::ServiceUnknown(String::from("test"))).into();
+    assert!(!is_disconnected(&other));
+    assert!(!is_disconnected(&"Not a D-Bus error".into()));
+  }
+
+  /**
+   * Latency comparison on wall-clock timings, reuse and reconnection are covered by `test_shared_connection_reconnect`
+   */
+  #[test]
+  #[ignore = "Compares timings, run with `cargo test -- --ignored test_shared_connection_faster`"]
+  fn test_shared_connection_faster() -> Result<(), Box<dyn std::error::Error>> {
+    let bus = PrivateBus::start()?;
+    let _service = serve(&bus, GNOME_XWIN_SCHEMA_VERSION)?;
+    let iterations = 50;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+      let client = connection::Builder::address(bus.address.as_str())?.build()?;
+      extension_proxy(&client)?.get_active_window()?;
+    }
+    let fresh = start.elapsed() / iterations;
+
+    let address = bus.address.clone();
+    let shared =
+      SharedConnection::new(move || connection::Builder::address(address.as_str())?.build());
+    let start = Instant::now();
+    for _ in 0..iterations {
+      shared.with(|connection| Ok(extension_proxy(connection)?.get_active_window()?))?;
+    }
+    let reused = start.elapsed() / iterations;
+
+    // Opening a connection authenticates and registers on the bus before the call
+    assert!(
+      reused * 2 < fresh,
+      "shared connection {reused:?}/call, new connection {fresh:?}/call"
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,395 @@
+#![deny(unused_imports)]
+
+use super::gnome_dbus::GNOME_XWIN_DBUS_INTERFACE;
+
+/**
+ * Flavour of the javascript generated for GNOME Shell
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionFlavour {
+  /**
+   * Script evaluated with `org.gnome.Shell.Eval` (GNOME < 41), results are serialized by GNOME Shell
+   */
+  Eval,
+  /**
+   * Extension using legacy `imports.gi` (GNOME 41 to 44)
+   */
+  Legacy,
+  /**
+   * Extension using ES modules (GNOME 45+)
+   */
+  Esm,
+}
+
+impl ExtensionFlavour {
+  /**
+   * Flavour of the extension to install for a GNOME Shell major version
+   */
+  pub fn from_version(version: u32) -> Self {
+    match version {
+      x if x.lt(&45) => ExtensionFlavour::Legacy,
+      _ => ExtensionFlavour::Esm,
+    }
+  }
+}
+
+/**
+ * Version of the D-Bus schema exposed by the extension, to increase on every change of `GNOME_XWIN_DBUS_INTERFACE`
+ * or of the keys of returned dictionaries.
+ * Extensions without `SchemaVersion` are version 1, returning JSON strings with `get_icon(window_id)` or `get_icon(window_id, size)`.
+ */
+pub const GNOME_XWIN_SCHEMA_VERSION: u32 = 2;
+
+const LEGACY_IMPORTS: &str = r#"const { Gio, GLib, Meta, Gtk: St, Shell } = imports.gi;"#;
+
+const ESM_IMPORTS: &str = r#"import { Extension } from 'resource:///org/gnome/shell/extensions/extension.js';
+import Gio from 'gi://Gio';
+import GLib from 'gi://GLib';
+import Meta from 'gi://Meta';
+import St from 'gi://St';
+import Shell from 'gi://Shell';"#;
+
+/// `org.gnome.Shell.Eval` serializes the value of the last statement as JSON
+const EVAL_API: &str = r#"function get_open_windows() {
+  return _get_open_windows();
+}
+
+function get_active_window() {
+  return _get_active_window();
+}
+
+function get_icon(window_id, size) {
+  const icon = _get_icon(window_id, size);
+  return {
+    data: icon.data.length ? `data:${icon.mime};base64,` + GLib.base64_encode(icon.data) : "",
+    height: icon.height,
+    width: icon.width,
+  };
+}"#;
+
+/// Results of the extension are typed `a{sv}` dictionaries following `GNOME_XWIN_DBUS_INTERFACE`
+const DBUS_API: &str = r#"function _window_variant(data) {
+  return {
+    id: new GLib.Variant('u', data.id),
+    os: new GLib.Variant('s', data.os),
+    title: new GLib.Variant('s', data.title),
+    x: new GLib.Variant('i', data.position.x),
+    y: new GLib.Variant('i', data.position.y),
+    width: new GLib.Variant('i', data.position.width),
+    height: new GLib.Variant('i', data.position.height),
+    is_full_screen: new GLib.Variant('b', data.position.isFullScreen),
+    process_id: new GLib.Variant('u', data.info.processId),
+    path: new GLib.Variant('s', data.info.path),
+    name: new GLib.Variant('s', data.info.name),
+    exec_name: new GLib.Variant('s', data.info.execName),
+    memory: new GLib.Variant('u', data.usage.memory),
+  };
+}
+
+function get_open_windows() {
+  return _get_open_windows().map(_window_variant);
+}
+
+function get_active_window() {
+  return _window_variant(_get_active_window());
+}
+
+function get_icon(window_id, size) {
+  const icon = _get_icon(window_id, size);
+  return {
+    data: new GLib.Variant('ay', icon.data),
+    mime: new GLib.Variant('s', icon.mime),
+    height: new GLib.Variant('u', icon.height),
+    width: new GLib.Variant('u', icon.width),
+  };
+}
+
+const WaylandInterface = `
+{{interface}}
+`;
+
+const XWinApi = {
+  SchemaVersion: {{schema_version}},
+  get_open_windows,
+  get_active_window,
+  get_icon,
+};"#;
+
+const LEGACY_ENTRY: &str = r#"let _dbus = undefined;
+
+function enable() {
+  _dbus = Gio.DBusExportedObject.wrapJSObject(
+    WaylandInterface,
+    XWinApi,
+  );
+  _dbus.export(
+    Gio.DBus.session,
+    '/org/gnome/Shell/Extensions/XWinWaylandExtension',
+  );
+}
+
+function disable() {
+  _dbus.flush();
+  _dbus.unexport();
+  _dbus = undefined;
+}
+
+function init() {
+  /** Do nothing */
+}"#;
+
+const ESM_ENTRY: &str = r#"export default class XWinWaylandExtension extends Extension {
+
+  _dbus = undefined;
+
+  enable() {
+    this._dbus = Gio.DBusExportedObject.wrapJSObject(
+      WaylandInterface,
+      XWinApi,
+    );
+    this._dbus.export(
+      Gio.DBus.session,
+      '/org/gnome/Shell/Extensions/XWinWaylandExtension',
+    );
+  }
+
+  disable() {
+    this._dbus.flush();
+    this._dbus.unexport();
+    this._dbus = undefined;
+  }
+}"#;
+
+/**
+ * Source shared by every flavour, `{{...}}` markers are replaced by `generate_script`
+ */
+const GNOME_XWIN_SCRIPT_TEMPLATE: &str = r#"{{imports}}
+
+const AllowedWindow = [
+  Meta.WindowType.DESKTOP,
+  Meta.WindowType.OVERRIDE_OTHER,
+  Meta.WindowType.UTILITY,
+  Meta.WindowType.MODAL_DIALOG,
+  Meta.WindowType.DIALOG,
+  Meta.WindowType.NORMAL
+];
+
+function _filterWindow(x, index, array) {
+  if (x && x.get_meta_window && x.get_meta_window().get_window_type) {
+    return x.get_meta_window().get_window_type() !== -1;
+  } else {
+    return false;
+  }
+}
+
+function _get_open_windows() {
+  return global.get_window_actors()
+    .filter(_filterWindow)
+    .map(_strcut_data);
+}
+
+function _get_active_window() {
+  const activeWindow = global.get_window_actors().find(x => x.get_meta_window().has_focus() && _filterWindow(x));
+  return _strcut_data(activeWindow);
+}
+
+function _strcut_data(window_actor) {
+  if (window_actor && window_actor.get_meta_window) {
+    const _window = window_actor.get_meta_window();
+
+    const process_id = _window.get_pid ? _window.get_pid() : 0;
+    const info = _get_process_info(process_id);
+
+    return {
+      id: _window.get_id(),
+      os: 'linux',
+      info: {
+        processId: process_id,
+        name: (_window.get_wm_class ? _window.get_wm_class() : '') || '',
+        path: info.path,
+        execName: info.exec_name,
+      },
+      title: (_window.get_title ? _window.get_title() : '') || '',
+      position: {
+        width: Math.round(window_actor.get_width ? window_actor.get_width() : 0),
+        height: Math.round(window_actor.get_height ? window_actor.get_height() : 0),
+        x: Math.round(window_actor.get_x ? window_actor.get_x() : 0),
+        y: Math.round(window_actor.get_y ? window_actor.get_y() : 0),
+        isFullScreen: _window.is_fullscreen(),
+      },
+      usage: { memory: _get_memory_usage(process_id) },
+    };
+  } else {
+    return {
+      id: 0,
+      os: 'linux',
+      title: '',
+      info: {
+        processId: 0,
+        path: '',
+        execName: '',
+        name: '',
+      },
+      position: {
+        width: 0,
+        height: 0,
+        x: 0,
+        y: 0,
+        isFullScreen: false,
+      },
+      usage: { memory: 0 },
+    };
+  }
+}
+
+function _get_memory_usage(pid) {
+  const [isOk, contents] = GLib.file_get_contents(`/proc/${pid}/statm`);
+  if (isOk) {
+    const value = String.fromCharCode.apply(null, contents);
+    return parseInt(value.toString().split(' ')[0], 10);
+  }
+  return 0;
+}
+
+function _get_process_info(pid) {
+  try {
+    const path = GLib.file_read_link(`/proc/${pid}/exe`);
+    if (path) {
+      return {
+        path,
+        exec_name: path.split('/').pop(),
+      };
+    }
+  } catch (e) { }
+  return {
+    path: '',
+    exec_name: '',
+  };
+}
+
+function _get_icon(window_id, size) {
+  const icon_size = size > 0 ? size : 128;
+  if (window_id) {
+    const window_actor = global.get_window_actors()
+      .filter(_filterWindow)
+      .find(x => x.get_meta_window().get_id() === window_id);
+    // Window may have been closed since it was listed
+    const meta_window = window_actor ? window_actor.get_meta_window() : null;
+    if (meta_window) {
+      const tracker = Shell.WindowTracker.get_default();
+      const window_app = tracker.get_window_app(meta_window);
+      if (window_app) {
+        const icon = window_app.get_icon();
+        if (icon) {
+          const iconTheme = new St.IconTheme();
+          const iconInfo = iconTheme.lookup_by_gicon(icon, icon_size, St.IconLookupFlags.FORCE_SIZE);
+          if (iconInfo) {
+            const pixBuf = iconInfo.load_icon();
+            if (pixBuf) {
+              const [ success, unitArray ] = pixBuf.save_to_bufferv('png', [], []);
+              if(success && unitArray.length) {
+                return {
+                  data: unitArray,
+                  mime: "image/png",
+                  height: pixBuf.get_height(),
+                  width: pixBuf.get_width(),
+                };
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+  return {
+    data: new Uint8Array(0),
+    mime: "",
+    height: 0,
+    width: 0,
+  };
+}
+
+{{api}}
+
+{{entry}}
+"#;
+
+/**
+ * Generate the javascript of `flavour` from the shared template
+ */
+pub fn generate_script(flavour: ExtensionFlavour) -> String {
+  let (imports, api, entry) = match flavour {
+    ExtensionFlavour::Eval => (LEGACY_IMPORTS, EVAL_API, ""),
+    ExtensionFlavour::Legacy => (LEGACY_IMPORTS, DBUS_API, LEGACY_ENTRY),
+    ExtensionFlavour::Esm => (ESM_IMPORTS, DBUS_API, ESM_ENTRY),
+  };
+  GNOME_XWIN_SCRIPT_TEMPLATE
+    .replace("{{imports}}", imports)
+    .replace("{{api}}", api)
+    .replace("{{interface}}", GNOME_XWIN_DBUS_INTERFACE)
+    .replace("{{schema_version}}", &GNOME_XWIN_SCHEMA_VERSION.to_string())
+    .replace("{{entry}}", entry)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    generate_script, ExtensionFlavour, GNOME_XWIN_DBUS_INTERFACE, GNOME_XWIN_SCHEMA_VERSION,
+  };
+
+  fn dbus_methods() -> Vec<&'static str> {
+    GNOME_XWIN_DBUS_INTERFACE
+      .split("<method name=\"")
+      .skip(1)
+      .filter_map(|method| method.split('"').next())
+      .collect()
+  }
+
+  #[test]
+  fn test_extensions_expose_same_dbus_interface() {
+    let methods = dbus_methods();
+    assert_eq!(
+      methods,
+      vec!["get_active_window", "get_open_windows", "get_icon"]
+    );
+
+    let legacy = generate_script(ExtensionFlavour::Legacy);
+    let esm = generate_script(ExtensionFlavour::Esm);
+    for script in [&legacy, &esm] {
+      assert!(script.contains(GNOME_XWIN_DBUS_INTERFACE));
+      assert!(!script.contains("{{"));
+      assert!(script.contains(&format!("SchemaVersion: {GNOME_XWIN_SCHEMA_VERSION},")));
+      for method in &methods {
+        assert!(script.contains(&format!("function {method}(")));
+        assert!(script.contains(&format!("\n  {method},")));
+      }
+      assert!(script.contains("wrapJSObject(\n"));
+      assert!(script.contains("XWinApi,\n"));
+    }
+
+    assert!(legacy.starts_with("const { Gio, GLib, Meta, Gtk: St, Shell } = imports.gi;"));
+    assert!(legacy.contains("\nfunction enable() {"));
+    assert!(esm.starts_with("import { Extension }"));
+    assert!(esm.contains("export default class XWinWaylandExtension extends Extension {"));
+  }
+
+  #[test]
+  fn test_eval_script() {
+    let eval = generate_script(ExtensionFlavour::Eval);
+    assert!(!eval.contains("WaylandInterface"));
+    assert!(!eval.contains("GLib.Variant"));
+    assert!(!eval.contains("{{"));
+    assert!(eval.contains("function get_icon(window_id, size) {"));
+    // Window actors are always filtered with the same function
+    assert_eq!(
+      eval.matches(".filter(").count(),
+      eval.matches(".filter(_filterWindow)").count()
+    );
+  }
+
+  #[test]
+  fn test_flavour_from_version() {
+    assert_eq!(ExtensionFlavour::from_version(44), ExtensionFlavour::Legacy);
+    assert_eq!(ExtensionFlavour::from_version(45), ExtensionFlavour::Esm);
+  }
+}
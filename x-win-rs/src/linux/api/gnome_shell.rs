@@ -1,13 +1,23 @@
-use std::sync::Mutex;
+use std::{
+  sync::Mutex,
+  time::{Duration, Instant},
+};
 
 use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 
-use crate::common::x_win_struct::{
-  icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
-  window_position::WindowPosition,
+use crate::common::{
+  result::Result,
+  x_win_struct::{
+    icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+    window_position::WindowPosition,
+  },
 };
 
-use super::common_api::get_gnome_version;
+use super::{
+  common_api::{gnome_major_version, gnome_shell_binary_version},
+  gnome_dbus::{shell_owner, shell_proxy, with_session},
+};
 
 pub const GNOME_XWIN_UUID: &str = r#"x-win@miniben90.org"#;
 
@@ -15,8 +25,6 @@ pub static DESTINATION: std::option::Option<&str> = Some("org.gnome.Shell");
 pub static XWIN_IFACE: std::option::Option<&str> =
   Some("org.gnome.Shell.Extensions.XWinWaylandExtension");
 pub static XWIN_PATH: &str = "/org/gnome/Shell/Extensions/XWinWaylandExtension";
-pub static SHELL_PATH: &str = "/org/gnome/Shell";
-pub static SHELL_IFACE: std::option::Option<&str> = Some("org.gnome.Shell.Extensions");
 
 // Metadata for gnome version > then 41
 pub const GNOME_XWIN_EXTENSION_META: &str = r#"
@@ -30,600 +38,220 @@ pub const GNOME_XWIN_EXTENSION_META: &str = r#"
 }
 "#;
 
-pub const GNOME_XWIN_EXTENSION_FOLDER_PATH: &str =
-  r#".local/share/gnome-shell/extensions/x-win@miniben90.org"#;
-
-pub const GNOME_XWIN_GET_ICON_SCRIPT: &str = r#"function _get_icon(window_id) {
-  if (window_id) {
-    let meta_window = global.get_window_actors()
-      .filter(x => _filterWindow)
-      .find(x => x && x.get_meta_window && x.get_meta_window() && x.get_meta_window().get_id() === window_id)
-      .get_meta_window();
-    if (meta_window) {
-      const tracker = Shell.WindowTracker.get_default();
-      const window_app = tracker.get_window_app(meta_window);
-      if (window_app) {
-        const icon = window_app.get_icon();
-        if (icon) {
-          const iconTheme = new St.IconTheme();
-          const iconInfo = iconTheme.lookup_by_gicon(icon, 128, St.IconLookupFlags.FORCE_SIZE);
-          if (iconInfo) {
-            const pixBuf = iconInfo.load_icon();
-            if (pixBuf) {
-              const [ success, unitArray ] = pixBuf.save_to_bufferv('png', [], []);
-              if(success && unitArray.length) {
-                const data = GLib.base64_encode(unitArray);
-                if (data) {
-                  return {
-                    data: "data:image/png;base64," + data,
-                    height: pixBuf.get_height(),
-                    width: pixBuf.get_width(),
-                  };
-                }
-              }
-            }
-          }
-        }
-      }
-    }
-  }
-  return {
-    data: "",
-    height: 0,
-    width: 0,
-  };
-}"#;
-
-pub const GNOME_XWIN_EVAL_SCRIPT: &str = r#"
-const { Gio, GLib, Meta, Gtk: St, Shell } = imports.gi;
-
-const AllowedWindow = [
-  Meta.WindowType.DESKTOP,
-  Meta.WindowType.OVERRIDE_OTHER,
-  Meta.WindowType.UTILITY,
-  Meta.WindowType.MODAL_DIALOG,
-  Meta.WindowType.DIALOG,
-  Meta.WindowType.NORMAL
-];
-
-function _filterWindow(x, index, array) {
-  if (x && x.get_meta_window && x.get_meta_window().get_window_type) {
-    return x.get_meta_window().get_window_type() !== -1;
-  } else {
-    return false;
-  }
-}
+/// Folder of extensions relative to an installation prefix (`$HOME/.local`, `/usr`, ...)
+pub const GNOME_EXTENSIONS_FOLDER_PATH: &str = r#"share/gnome-shell/extensions"#;
 
-function _get_open_windows() {
-  return global.get_window_actors()
-    .filter(_filterWindow)
-    .map(_strcut_data);
-}
+/// Folder of GSettings schemas relative to an installation prefix
+pub const GLIB_SCHEMAS_FOLDER_PATH: &str = r#"share/glib-2.0/schemas"#;
 
-function get_open_windows() {
-  return Object(_get_open_windows());
-}
+/// GSettings override enabling the extension by default
+pub const GNOME_XWIN_SCHEMA_OVERRIDE_FILE_NAME: &str = r#"90_x-win.gschema.override"#;
 
-function get_active_window() {
-  const activeWindow = global.get_window_actors().find(x => x.get_meta_window().has_focus() && _filterWindow(x));
-  return Object(_strcut_data(activeWindow));
+/**
+ * Read a value of the eval script, values of another type are replaced by the default one like missing values
+ */
+fn lenient<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+  D: Deserializer<'de>,
+  T: DeserializeOwned + Default,
+{
+  let value = serde_json::Value::deserialize(deserializer)?;
+  Ok(T::deserialize(value).unwrap_or_default())
 }
 
-function get_icon(window_id) {
-  const iconInfo = _get_icon(window_id);
-  return Object(iconInfo);
+/**
+ * Icon returned by the eval script
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EvalIcon {
+  #[serde(deserialize_with = "lenient")]
+  data: String,
+  #[serde(deserialize_with = "lenient")]
+  height: u32,
+  #[serde(deserialize_with = "lenient")]
+  width: u32,
 }
 
-function _strcut_data(window_actor) {
-  if (window_actor && window_actor.get_meta_window) {
-    const _window = window_actor.get_meta_window();
-
-    const process_id = _window.get_pid ? _window.get_pid() : 0;
-    const info = _get_process_info(process_id);
-
-    return {
-      id: _window.get_id(),
-      os: 'linux',
-      info: {
-        process_id,
-        name: _window.get_wm_class ? _window.get_wm_class() : '',
-        path: info.path,
-        exec_name: info.exec_name,
-      },
-      title: _window.get_title ? _window.get_title() : '',
-      position: {
-        width: window_actor.get_width ? window_actor.get_width() : 0,
-        height: window_actor.get_height ? window_actor.get_height() : 0,
-        x: window_actor.get_x ? window_actor.get_x() : 0,
-        y: window_actor.get_y ? window_actor.get_y() : 0,
-        isFullScreen: _window.is_fullscreen(),
-      },
-      usage: { memory: _get_memory_usage(process_id) },
-    };
-  } else {
-    return {
-      id: 0,
-      os: 'linux',
-      title: '',
-      info: {
-        process_id: 0,
-        path: '',
-        exec_name: '',
-        name: '',
-      },
-      position: {
-        width: 0,
-        height: 0,
-        x: 0,
-        y: 0,
-        isFullScreen: false,
-      },
-      usage: { memory: 0 },
-    };
-  }
+/**
+ * Window returned by the eval script, with the camelCase names of `WindowInfo`
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EvalWindow {
+  #[serde(deserialize_with = "lenient")]
+  id: u32,
+  #[serde(deserialize_with = "lenient")]
+  os: String,
+  #[serde(deserialize_with = "lenient")]
+  title: String,
+  #[serde(deserialize_with = "lenient")]
+  position: EvalPosition,
+  #[serde(deserialize_with = "lenient")]
+  info: EvalProcess,
+  #[serde(deserialize_with = "lenient")]
+  usage: EvalUsage,
 }
 
-function _get_memory_usage(pid) {
-  const [isOk, contents] = GLib.file_get_contents(`/proc/${pid}/statm`);
-  if (isOk) {
-    return parseInt(contents.toString().split(' ')[0], 10);
-  }
-  return 0;
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct EvalPosition {
+  #[serde(deserialize_with = "lenient")]
+  x: i32,
+  #[serde(deserialize_with = "lenient")]
+  y: i32,
+  #[serde(deserialize_with = "lenient")]
+  width: i32,
+  #[serde(deserialize_with = "lenient")]
+  height: i32,
+  #[serde(deserialize_with = "lenient")]
+  is_full_screen: bool,
 }
 
-function _get_process_info(pid) {
-  try {
-    const path = GLib.file_read_link(`/proc/${pid}/exe`, null);
-    if (path) {
-      return {
-        path,
-        exec_name: path.split('/').pop(),
-      };
-    }
-  } catch (e) { }
-  return {
-    path: '',
-    exec_name: '',
-  };
-}"#;
-
-pub const GNOME_XWIN_EXTENSION_COMMON_SCRIPT: &str = r#"
-const WaylandInterface = `
-<node>
-  <interface name="org.gnome.Shell.Extensions.XWinWaylandExtension">
-    <method name="get_active_window">
-      <arg name="value" type="s" direction="out" />
-    </method>
-    <method name="get_open_windows">
-      <arg name="value" type="s" direction="out" />
-    </method>
-    <method name="get_icon">
-      <arg name="value" type="d" direction="in" />
-      <arg name="value" type="s" direction="out" />
-    </method>
-  </interface>
-</node>
-`;
-
-const AllowedWindow = [
-  Meta.WindowType.DESKTOP,
-  Meta.WindowType.OVERRIDE_OTHER,
-  Meta.WindowType.UTILITY,
-  Meta.WindowType.MODAL_DIALOG,
-  Meta.WindowType.DIALOG,
-  Meta.WindowType.NORMAL
-];
-
-function _filterWindow(x, index, array) {
-  if (x && x.get_meta_window && x.get_meta_window().get_window_type) {
-    return x.get_meta_window().get_window_type() !== -1;
-  } else {
-    return false;
-  }
-}
-
-function _get_open_windows() {
-  return global.get_window_actors()
-    .filter(_filterWindow)
-    .map(_strcut_data);
+/**
+ * Scripts of x-win 2 and older used snake_case names
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct EvalProcess {
+  #[serde(alias = "process_id", deserialize_with = "lenient")]
+  process_id: u32,
+  #[serde(deserialize_with = "lenient")]
+  path: String,
+  #[serde(deserialize_with = "lenient")]
+  name: String,
+  #[serde(alias = "exec_name", deserialize_with = "lenient")]
+  exec_name: String,
 }
 
-function get_open_windows() {
-  return JSON.stringify(_get_open_windows());
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EvalUsage {
+  #[serde(deserialize_with = "lenient")]
+  memory: u32,
 }
 
-function get_active_window() {
-  const activeWindow = global.get_window_actors().find(x => x.get_meta_window().has_focus() && _filterWindow(x));
-  return JSON.stringify(_strcut_data(activeWindow));
+pub fn value_to_icon_info(response: &serde_json::Value) -> Result<IconInfo> {
+  let icon = EvalIcon::deserialize(response)?;
+  Ok(IconInfo::new(icon.data, icon.height, icon.width))
 }
 
-function get_icon(window_id) {
-  const iconInfo = _get_icon(window_id);
-  return JSON.stringify(iconInfo);
+pub fn value_to_window_info(response: &serde_json::Value) -> Result<WindowInfo> {
+  let window = EvalWindow::deserialize(response)?;
+  Ok(WindowInfo::new(
+    window.id,
+    window.os,
+    window.title,
+    WindowPosition::new(
+      window.position.x,
+      window.position.y,
+      window.position.width,
+      window.position.height,
+      window.position.is_full_screen,
+    ),
+    ProcessInfo::new(
+      window.info.process_id,
+      window.info.path,
+      window.info.name,
+      window.info.exec_name,
+    ),
+    UsageInfo::new(window.usage.memory),
+  ))
 }
 
-function _strcut_data(window_actor) {
-  if (window_actor && window_actor.get_meta_window) {
-    const _window = window_actor.get_meta_window();
-
-    const process_id = _window.get_pid ? _window.get_pid() : 0;
-    const info = _get_process_info(process_id);
-
-    return {
-      id: _window.get_id(),
-      os: 'linux',
-      info: {
-        process_id,
-        name: _window.get_wm_class ? _window.get_wm_class() : '',
-        path: info.path,
-        exec_name: info.exec_name,
-      },
-      title: _window.get_title ? _window.get_title() : '',
-      position: {
-        width: window_actor.get_width ? window_actor.get_width() : 0,
-        height: window_actor.get_height ? window_actor.get_height() : 0,
-        x: window_actor.get_x ? window_actor.get_x() : 0,
-        y: window_actor.get_y ? window_actor.get_y() : 0,
-        isFullScreen: _window.is_fullscreen(),
-      },
-      usage: { memory: _get_memory_usage(process_id) },
-    };
-  } else {
-    return {
-      id: 0,
-      os: 'linux',
-      title: '',
-      info: {
-        process_id: 0,
-        path: '',
-        exec_name: '',
-        name: '',
-      },
-      position: {
-        width: 0,
-        height: 0,
-        x: 0,
-        y: 0,
-        isFullScreen: false,
-      },
-      usage: { memory: 0 },
-    };
-  }
+#[derive(Debug, Clone, PartialEq)]
+pub struct GnomeVersion {
+  pub version: u32,
+  pub use_eval: bool,
 }
 
-function _get_memory_usage(pid) {
-  const [isOk, contents] = GLib.file_get_contents(`/proc/${pid}/statm`);
-  if (isOk) {
-    const value = String.fromCharCode.apply(null, contents);
-    return parseInt(value.toString().split(' ')[0], 10);
+impl GnomeVersion {
+  fn new(version: Option<&str>) -> Self {
+    // Unknown versions are handled as the latest one
+    let version = version.and_then(gnome_major_version).unwrap_or(999);
+    let use_eval = version < 41;
+    Self { use_eval, version }
   }
-  return 0;
-}
-
-function _get_process_info(pid) {
-  try {
-    const path = GLib.file_read_link(`/proc/${pid}/exe`);
-    if (path) {
-      return {
-        path,
-        exec_name: path.split('/').pop(),
-      };
-    }
-  } catch (e) { }
-  return {
-    path: '',
-    exec_name: '',
-  };
-}"#;
-
-// Javascript extension to get active and open window(s) informations
-pub const GNOME_XWIN_EXTENSION_SCRIPT: &str = r#"const { Gio, GLib, Meta, Gtk: St, Shell } = imports.gi;
-
-let _dbus = undefined;
-
-function enable() {
-  _dbus = Gio.DBusExportedObject.wrapJSObject(
-    WaylandInterface,
-    this,
-  );
-  _dbus.export(
-    Gio.DBus.session,
-    '/org/gnome/Shell/Extensions/XWinWaylandExtension',
-  );
-}
-
-function disable() {
-  _dbus.flush();
-  _dbus.unexport();
-  _dbus = undefined;
 }
 
-function init() {
-  /** Do nothing */
+/**
+ * Version detected for a GNOME Shell instance, identified by its unique name on the session bus
+ */
+struct DetectedGnomeVersion {
+  gnome_version: GnomeVersion,
+  owner: Option<String>,
+  checked_at: Instant,
 }
-"#;
-
-pub const GNOME45_XWIN_EXTENSION_SCRIPT: &str = r#"import { Extension } from 'resource:///org/gnome/shell/extensions/extension.js';
-import Gio from 'gi://Gio';
-import GLib from 'gi://GLib';
-import Meta from 'gi://Meta';
-import St from "gi://St";
-import Shell from "gi://Shell";
-
-export default class XWinWaylandExtension extends Extension {
 
-  _dbus = undefined;
+/// Delay before checking again if GNOME Shell has been restarted
+const GNOME_OWNER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
-  enable() {
-    this._dbus = Gio.DBusExportedObject.wrapJSObject(
-      WaylandInterface,
-      this,
-    );
-    this._dbus.export(
-      Gio.DBus.session,
-      '/org/gnome/Shell/Extensions/XWinWaylandExtension',
-    );
-  }
+static GNOME_SINGLETON: Lazy<Mutex<Option<DetectedGnomeVersion>>> = Lazy::new(|| Mutex::new(None));
 
-  disable() {
-    this._dbus.flush();
-    this._dbus.unexport();
-    this._dbus = undefined;
-  }
-
-  get_open_windows() {
-    return get_open_windows();
-  }
-
-  get_active_window() {
-    return get_active_window();
-  }
-
-  get_icon(window_id) {
-    return get_icon(window_id);
+/**
+ * Version of the running GNOME Shell, detected again when GNOME Shell has been restarted
+ */
+pub fn gnome_version() -> Result<GnomeVersion> {
+  let mut detected = GNOME_SINGLETON.lock().map_err(|err| err.to_string())?;
+  if let Some(current) = detected.as_ref() {
+    if current.checked_at.elapsed() < GNOME_OWNER_CHECK_INTERVAL {
+      return Ok(current.gnome_version.clone());
+    }
   }
-}
-"#;
-
-fn number_to_u32(value: &serde_json::Map<String, serde_json::Value>, key: &str) -> u32 {
-  value_to_i64(value, key) as u32
-}
-
-fn number_to_i32(value: &serde_json::Map<String, serde_json::Value>, key: &str) -> i32 {
-  value_to_i64(value, key) as i32
-}
-
-fn value_to_i64(value: &serde_json::Map<String, serde_json::Value>, key: &str) -> i64 {
-  value.get(key).and_then(|v| v.as_i64()).unwrap_or(0)
-}
-
-fn str_to_string(value: &serde_json::Map<String, serde_json::Value>, key: &str) -> String {
-  value
-    .get(key)
-    .and_then(|v| v.as_str())
-    .unwrap_or("")
-    .to_string()
-}
-
-pub fn value_to_icon_info(response: &serde_json::Value) -> Result<IconInfo, &'static str> {
-  let response = response.as_object().ok_or("Expected JSON object")?;
-
-  Ok(IconInfo {
-    data: str_to_string(response, "data"),
-    height: number_to_u32(response, "height"),
-    width: number_to_u32(response, "width"),
-  })
-}
-
-pub fn value_to_window_info(response: &serde_json::Value) -> Result<WindowInfo, &'static str> {
-  let response = response.as_object().ok_or("Expected JSON object")?;
-  let position = response["position"]
-    .as_object()
-    .ok_or("Expected JSON object")?;
-  let info = response["info"].as_object().ok_or("Expected JSON object")?;
-  let usage = response["usage"]
-    .as_object()
-    .ok_or("Expected JSON object")?;
-
-  Ok(WindowInfo {
-    id: number_to_u32(response, "id"),
-    os: str_to_string(response, "os"),
-    title: str_to_string(response, "title"),
-    position: WindowPosition {
-      height: number_to_i32(position, "height"),
-      width: number_to_i32(position, "width"),
-      x: number_to_i32(position, "x"),
-      y: number_to_i32(position, "y"),
-      is_full_screen: position
-        .get("isFullScreen")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false),
-    },
-    info: ProcessInfo {
-      exec_name: str_to_string(info, "exec_name"),
-      name: str_to_string(info, "name"),
-      path: str_to_string(info, "path"),
-      process_id: number_to_u32(info, "process_id"),
-    },
-    usage: UsageInfo {
-      memory: number_to_u32(usage, "memory"),
-    },
-  })
-}
-
-pub struct GnomeVersion {
-  pub version: u32,
-  pub use_eval: bool,
+  let owner = with_session(shell_owner).ok();
+  let gnome_version = match detected.as_ref() {
+    Some(current) if current.owner.eq(&owner) => current.gnome_version.clone(),
+    _ => GnomeVersion::new(gnome_shell_version().as_deref()),
+  };
+  *detected = Some(DetectedGnomeVersion {
+    gnome_version: gnome_version.clone(),
+    owner,
+    checked_at: Instant::now(),
+  });
+  Ok(gnome_version)
 }
 
-impl GnomeVersion {
-  fn new() -> Self {
-    let version = get_gnome_version();
-    let version: u32 = version.split(".").collect::<Vec<&str>>()[0]
-      .parse()
-      .unwrap_or(999);
-    let use_eval = version < 41;
-    Self { use_eval, version }
-  }
+/**
+ * Version of GNOME Shell from its `ShellVersion` property, `gnome-shell --version` is only used as fallback
+ */
+pub fn gnome_shell_version() -> Option<String> {
+  with_session(|connection| Ok(shell_proxy(connection)?.shell_version()?))
+    .ok()
+    .filter(|version| !version.is_empty())
+    .or_else(gnome_shell_binary_version)
 }
 
-pub static GNOME_SINGLETON: Lazy<Mutex<GnomeVersion>> =
-  Lazy::new(|| Mutex::new(GnomeVersion::new()));
-
 #[cfg(test)]
 mod tests {
   use crate::common::x_win_struct::{
     icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
     window_position::WindowPosition,
   };
-  use crate::linux::api::gnome_shell::{
-    number_to_i32, number_to_u32, str_to_string, value_to_icon_info, value_to_window_info,
-  };
+  use crate::linux::api::gnome_shell::{value_to_icon_info, value_to_window_info, GnomeVersion};
 
-  /**
-   * Test str_to_string function
-   */
   #[test]
-  fn test_str_to_string() -> Result<(), Box<dyn std::error::Error>> {
-    let title = {
-      let value: serde_json::Value = serde_json::from_str("{}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      str_to_string(value, "title")
-    };
-
-    assert_eq!(title, "");
-
-    let title = {
-      let value: serde_json::Value = serde_json::from_str("{\"title\":\"\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      str_to_string(value, "title")
-    };
-    assert_eq!(title, "");
-
-    let title = {
-      let value: serde_json::Value = serde_json::from_str("{\"title\":\"test\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      str_to_string(value, "title")
-    };
-    assert_eq!(title, "test");
-
-    Ok(())
-  }
-
-  /**
-   * Test number_to_u32 function
-   */
-  #[test]
-  fn test_number_to_u32() -> Result<(), Box<dyn std::error::Error>> {
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"test\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":0}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":0.0}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":1000}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 1000);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"1000\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_u32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    Ok(())
-  }
-
-  /**
-   * Test number_to_i32 function
-   */
-  #[test]
-  fn test_number_to_i32() -> Result<(), Box<dyn std::error::Error>> {
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"test\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":0}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":0.0}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":1000}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 1000);
-
-    let width = {
-      let value: serde_json::Value = serde_json::from_str("{\"width\":\"1000\"}")?;
-      let value = value.as_object().ok_or("failed to test")?;
-      number_to_i32(value, "width")
-    };
-    assert_eq!(width, 0);
-
-    Ok(())
+  fn test_gnome_version() {
+    assert_eq!(
+      GnomeVersion::new(Some("40.9")),
+      GnomeVersion {
+        version: 40,
+        use_eval: true
+      }
+    );
+    assert_eq!(
+      GnomeVersion::new(Some("46.0")),
+      GnomeVersion {
+        version: 46,
+        use_eval: false
+      }
+    );
+    assert_eq!(
+      GnomeVersion::new(None),
+      GnomeVersion {
+        version: 999,
+        use_eval: false
+      }
+    );
   }
 
   /**
@@ -713,7 +341,7 @@ mod tests {
     // Test total blank values
     let window_info = {
       let value: serde_json::Value = serde_json::from_str(
-        r#"{"id":0,"os":"","info":{"process_id":0,"name":"","path":"","exec_name":""},"title":"","position":{"width":0,"height":0,"x":0,"y":0,"isFullScreen":false},"usage":{"memory":0}}"#,
+        r#"{"id":0,"os":"","info":{"processId":0,"name":"","path":"","execName":""},"title":"","position":{"width":0,"height":0,"x":0,"y":0,"isFullScreen":false},"usage":{"memory":0}}"#,
       )?;
       value_to_window_info(&value)?
     };
@@ -733,7 +361,7 @@ mod tests {
     // Test without name attribute
     let window_info = {
       let value: serde_json::Value = serde_json::from_str(
-        r#"{"id":0,"os":"","info":{"process_id":0,"path":"","exec_name":""},"title":"","position":{"width":0,"height":0,"x":0,"y":0,"isFullScreen":false},"usage":{"memory":0}}"#,
+        r#"{"id":0,"os":"","info":{"processId":0,"path":"","execName":""},"title":"","position":{"width":0,"height":0,"x":0,"y":0,"isFullScreen":false},"usage":{"memory":0}}"#,
       )?;
       value_to_window_info(&value)?
     };
@@ -753,7 +381,7 @@ mod tests {
     // Test without name attribute
     let window_info = {
       let value: serde_json::Value = serde_json::from_str(
-        r#"{"id":3038270935,"os":"linux","info":{"process_id":95389,"name":"gnome-terminal-server","path":"/usr/libexec/gnome-terminal-server","exec_name":"gnome-terminal-server"},"title":"new terminal","position":{"width":866,"height":629,"x":1077,"y":192,"isFullScreen":true},"usage":{"memory":139473}}"#,
+        r#"{"id":3038270935,"os":"linux","info":{"processId":95389,"name":"gnome-terminal-server","path":"/usr/libexec/gnome-terminal-server","execName":"gnome-terminal-server"},"title":"new terminal","position":{"width":866,"height":629,"x":1077,"y":192,"isFullScreen":true},"usage":{"memory":139473}}"#,
       )?;
       value_to_window_info(&value)?
     };
@@ -777,4 +405,37 @@ mod tests {
 
     Ok(())
   }
+  /**
+   * Values of another type are read as missing values, snake_case names of older scripts are accepted
+   */
+  #[test]
+  fn test_value_to_window_info_lenient() -> Result<(), Box<dyn std::error::Error>> {
+    let value: serde_json::Value = serde_json::from_str(
+      r#"{"id":"7","title":null,"info":{"process_id":95389,"exec_name":"code","path":1},"position":{"width":866.5,"height":"629","x":-10,"isFullScreen":1},"usage":[]}"#,
+    )?;
+    assert_eq!(
+      value_to_window_info(&value)?,
+      WindowInfo::new(
+        0,
+        String::from(""),
+        String::from(""),
+        WindowPosition::new(-10, 0, 0, 0, false),
+        ProcessInfo::new(
+          95389,
+          String::from(""),
+          String::from(""),
+          String::from("code")
+        ),
+        UsageInfo::new(0)
+      )
+    );
+
+    let value: serde_json::Value = serde_json::from_str(r#"{"data":"test","width":"100"}"#)?;
+    assert_eq!(
+      value_to_icon_info(&value)?,
+      IconInfo::new(String::from("test"), 0, 0)
+    );
+    assert!(value_to_window_info(&serde_json::Value::Null).is_err());
+    Ok(())
+  }
 }
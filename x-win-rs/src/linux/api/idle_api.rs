@@ -0,0 +1,190 @@
+#![deny(unused_imports)]
+
+use std::{
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use wayland_client::{
+  delegate_noop,
+  globals::{registry_queue_init, GlobalListContents},
+  protocol::{wl_registry::WlRegistry, wl_seat::WlSeat},
+  Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::ext::idle_notify::v1::client::{
+  ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+  ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+use xcb::x;
+
+use crate::common::{error::XWinError, result::Result};
+
+use super::gnome_dbus::{idle_monitor_proxy, with_session};
+
+/**
+ * Time since the last input from the MIT-SCREEN-SAVER extension of the X server
+ */
+pub fn x11_idle_time(conn: &xcb::Connection) -> Result<Duration> {
+  let extension = conn.wait_for_reply(conn.send_request(&x::QueryExtension {
+    name: b"MIT-SCREEN-SAVER",
+  }))?;
+  // Requests of a missing extension close the connection
+  if !extension.present() {
+    return Err(
+      XWinError::Unsupported(String::from(
+        "MIT-SCREEN-SAVER extension is not available on the X server",
+      ))
+      .into(),
+    );
+  }
+  let root = conn
+    .get_setup()
+    .roots()
+    .next()
+    .map(|screen| screen.root())
+    .ok_or("Something got wrong, not possible to get access of X Server!")?;
+  let info = conn.wait_for_reply(conn.send_request(&xcb::screensaver::QueryInfo {
+    drawable: x::Drawable::Window(root),
+  }))?;
+  Ok(Duration::from_millis(info.ms_since_user_input() as u64))
+}
+
+/**
+ * Time since the last input from the idle monitor of Mutter
+ */
+pub fn gnome_idle_time() -> Result<Duration> {
+  let idle_time = with_session(|connection| Ok(idle_monitor_proxy(connection)?.get_idletime()?))?;
+  Ok(Duration::from_millis(idle_time))
+}
+
+/**
+ * Timeout of the `ext-idle-notify-v1` notification, idle times below it are reported as zero
+ */
+const WAYLAND_IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+ * Idle state followed by the thread dispatching `ext-idle-notify-v1` events
+ */
+#[derive(Debug, Default)]
+struct WaylandIdle {
+  /**
+   * Time of the last input when the compositor reported the user as idle
+   */
+  idle_since: Option<Instant>,
+  closed: bool,
+}
+
+static WAYLAND_IDLE: Lazy<Mutex<Option<Arc<Mutex<WaylandIdle>>>>> = Lazy::new(|| Mutex::new(None));
+
+/**
+ * Time since the last input from the `ext-idle-notify-v1` protocol of the compositor.
+ * The compositor only notifies when the user becomes idle, a connection is kept to follow it.
+ */
+pub fn wayland_idle_time() -> Result<Duration> {
+  let mut current = WAYLAND_IDLE.lock().map_err(|err| err.to_string())?;
+  let idle = match current.as_ref().filter(|idle| !lock(idle).closed) {
+    Some(idle) => idle.clone(),
+    None => {
+      let idle = watch_wayland_idle()?;
+      *current = Some(idle.clone());
+      idle
+    }
+  };
+  let idle_since = lock(&idle).idle_since;
+  Ok(idle_since.map(|since| since.elapsed()).unwrap_or_default())
+}
+
+struct WaylandIdleState(Arc<Mutex<WaylandIdle>>);
+
+fn watch_wayland_idle() -> Result<Arc<Mutex<WaylandIdle>>> {
+  let conn = Connection::connect_to_env()?;
+  let (globals, mut queue) = registry_queue_init::<WaylandIdleState>(&conn)?;
+  let qh = queue.handle();
+  let notifier: ExtIdleNotifierV1 = globals.bind(&qh, 1..=1, ()).map_err(|_| {
+    XWinError::Unsupported(String::from(
+      "ext-idle-notify-v1 protocol is not available on this compositor",
+    ))
+  })?;
+  let seat: WlSeat = globals.bind(&qh, 1..=1, ())?;
+  notifier.get_idle_notification(WAYLAND_IDLE_TIMEOUT.as_millis() as u32, &seat, &qh, ());
+
+  let idle = Arc::new(Mutex::new(WaylandIdle::default()));
+  let mut state = WaylandIdleState(idle.clone());
+  queue.roundtrip(&mut state)?;
+  std::thread::spawn(move || {
+    // Keep the globals alive with the queue
+    let _globals = (notifier, seat);
+    while queue.blocking_dispatch(&mut state).is_ok() {}
+    lock(&state.0).closed = true;
+  });
+  Ok(idle)
+}
+
+impl Dispatch<ExtIdleNotificationV1, ()> for WaylandIdleState {
+  fn event(
+    state: &mut Self,
+    _notification: &ExtIdleNotificationV1,
+    event: ext_idle_notification_v1::Event,
+    _data: &(),
+    _conn: &Connection,
+    _qh: &QueueHandle<Self>,
+  ) {
+    let mut idle = lock(&state.0);
+    match event {
+      ext_idle_notification_v1::Event::Idled => {
+        idle.idle_since = Instant::now().checked_sub(WAYLAND_IDLE_TIMEOUT);
+      }
+      ext_idle_notification_v1::Event::Resumed => idle.idle_since = None,
+      _ => {}
+    }
+  }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for WaylandIdleState {
+  fn event(
+    _state: &mut Self,
+    _registry: &WlRegistry,
+    _event: <WlRegistry as wayland_client::Proxy>::Event,
+    _data: &GlobalListContents,
+    _conn: &Connection,
+    _qh: &QueueHandle<Self>,
+  ) {
+  }
+}
+
+delegate_noop!(WaylandIdleState: ignore WlSeat);
+delegate_noop!(WaylandIdleState: ExtIdleNotifierV1);
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{gnome_idle_time, x11_idle_time};
+  use crate::linux::api::{test_dbus::FakeGnomeShell, test_x11::Xvfb};
+
+  #[test]
+  fn test_gnome_idle_time() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    assert_eq!(gnome_idle_time()?, Duration::ZERO);
+    shell.state().idle_time = 90_000;
+    assert_eq!(gnome_idle_time()?, Duration::from_secs(90));
+    Ok(())
+  }
+
+  #[test]
+  #[ignore = "Requires Xvfb, run with `cargo test -- --ignored`"]
+  fn test_xvfb_idle_time() -> Result<(), Box<dyn std::error::Error>> {
+    let xvfb = Xvfb::start()?;
+    let conn = xvfb.connect()?;
+    let first = x11_idle_time(&conn)?;
+    std::thread::sleep(Duration::from_millis(200));
+    // Nobody types on a virtual framebuffer
+    assert!(x11_idle_time(&conn)? >= first + Duration::from_millis(100));
+    Ok(())
+  }
+}
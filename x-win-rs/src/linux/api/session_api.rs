@@ -0,0 +1,259 @@
+#![deny(unused_imports)]
+
+use std::sync::{
+  mpsc::{self, Receiver, Sender},
+  Arc, Mutex,
+};
+
+use zbus::{blocking::Connection, proxy, proxy::CacheProperties};
+
+use crate::common::{
+  result::Result,
+  x_win_struct::{diagnostic_report::SessionType, session_state::SessionState},
+};
+
+use super::{
+  diagnostic::session_type,
+  gnome_dbus::{with_session, with_system},
+};
+
+/**
+ * Session of logind x-win runs in, `auto` is resolved by logind to the session of the caller
+ * or to the graphical session of the user
+ */
+#[proxy(
+  interface = "org.freedesktop.login1.Session",
+  default_service = "org.freedesktop.login1",
+  default_path = "/org/freedesktop/login1/session/auto",
+  gen_async = false
+)]
+pub trait Login1Session {
+  #[zbus(property)]
+  fn active(&self) -> zbus::Result<bool>;
+
+  /**
+   * Set by the desktop while its lock screen is shown
+   */
+  #[zbus(property)]
+  fn locked_hint(&self) -> zbus::Result<bool>;
+
+  #[zbus(property, name = "Type")]
+  fn session_type(&self) -> zbus::Result<String>;
+}
+
+pub fn login1_session_proxy(connection: &Connection) -> Result<Login1SessionProxy<'_>> {
+  Ok(
+    Login1SessionProxy::builder(connection)
+      .cache_properties(CacheProperties::No)
+      .build()?,
+  )
+}
+
+/**
+ * Screen savers with `GetActive` and `ActiveChanged`, their service name is also their interface name
+ */
+const SCREEN_SAVERS: [(&str, &str); 2] = [
+  ("org.gnome.ScreenSaver", "/org/gnome/ScreenSaver"),
+  (
+    "org.freedesktop.ScreenSaver",
+    "/org/freedesktop/ScreenSaver",
+  ),
+];
+
+const LOGIN1_SERVICE: &str = "org.freedesktop.login1";
+const LOGIN1_SESSION_PATH: &str = "/org/freedesktop/login1/session/auto";
+
+/**
+ * Read the lock and activity state from logind, the screen saver of the session also tells if it is locked.
+ * Without logind, like in containers, the screen saver alone is used.
+ */
+pub fn session_state() -> Result<SessionState> {
+  let login = with_system(|connection| {
+    let session = login1_session_proxy(connection)?;
+    Ok((
+      session.active()?,
+      session.locked_hint()?,
+      session.session_type()?,
+    ))
+  });
+  let screen_saver = with_session(screen_saver_active);
+  match (login, screen_saver) {
+    (Ok((active, locked_hint, kind)), screen_saver) => Ok(SessionState::new(
+      locked_hint || screen_saver.ok().flatten().unwrap_or(false),
+      active,
+      login1_session_type(&kind),
+    )),
+    (Err(_), Ok(Some(screen_saver))) => Ok(SessionState::new(screen_saver, true, session_type())),
+    (Err(err), _) => Err(err),
+  }
+}
+
+/**
+ * Send the current state, then a new state each time logind or a screen saver reports a change.
+ * Listening threads stop at the first change after the receiver is dropped.
+ */
+pub fn watch_session_state() -> Result<Receiver<SessionState>> {
+  let (sender, receiver) = mpsc::channel();
+  let state = session_state()?;
+  let last = Arc::new(Mutex::new(Some(state.clone())));
+  let _ = sender.send(state);
+  let mut listening = false;
+  if let Ok(system) = with_system(|connection| Ok(connection.clone())) {
+    listening |= listen(
+      system,
+      LOGIN1_SERVICE,
+      LOGIN1_SESSION_PATH,
+      "org.freedesktop.DBus.Properties",
+      "PropertiesChanged",
+      (sender.clone(), last.clone()),
+    );
+  }
+  let session = with_session(|connection| Ok(connection.clone()))?;
+  for (name, path) in SCREEN_SAVERS {
+    if has_owner(&session, name)? {
+      listening |= listen(
+        session.clone(),
+        name,
+        path,
+        name,
+        "ActiveChanged",
+        (sender.clone(), last.clone()),
+      );
+    }
+  }
+  match listening {
+    true => Ok(receiver),
+    false => Err("Neither logind nor a screen saver can be followed on D-Bus".into()),
+  }
+}
+
+/**
+ * `GetActive` of the first running screen saver, `None` when there is none
+ */
+fn screen_saver_active(connection: &Connection) -> Result<Option<bool>> {
+  for (name, path) in SCREEN_SAVERS {
+    if has_owner(connection, name)? {
+      let proxy = zbus::blocking::Proxy::new(connection, name, path, name)?;
+      return Ok(Some(proxy.call("GetActive", &())?));
+    }
+  }
+  Ok(None)
+}
+
+fn has_owner(connection: &Connection, name: &str) -> Result<bool> {
+  Ok(
+    zbus::blocking::fdo::DBusProxy::new(connection)?
+      .name_has_owner(zbus::names::BusName::try_from(name)?)?,
+  )
+}
+
+fn login1_session_type(kind: &str) -> SessionType {
+  match kind {
+    "x11" => SessionType::X11,
+    "wayland" => SessionType::Wayland,
+    // `tty`, `mir` or `unspecified`
+    _ => SessionType::Unknown,
+  }
+}
+
+/**
+ * Send the session state when it differs from the last one sent, return false once the receiver is dropped
+ */
+fn notify(sender: &Sender<SessionState>, last: &Mutex<Option<SessionState>>) -> bool {
+  let state = match session_state() {
+    Ok(state) => state,
+    // Keep listening, the bus may answer on the next change
+    Err(_) => return true,
+  };
+  let mut last = last.lock().unwrap_or_else(|err| err.into_inner());
+  if last.as_ref().eq(&Some(&state)) {
+    return true;
+  }
+  *last = Some(state.clone());
+  sender.send(state).is_ok()
+}
+
+/**
+ * Call `notify` on each `signal` in a thread, return false when the signal can not be subscribed
+ */
+fn listen(
+  connection: Connection,
+  destination: &'static str,
+  path: &'static str,
+  interface: &'static str,
+  signal: &'static str,
+  (sender, last): (Sender<SessionState>, Arc<Mutex<Option<SessionState>>>),
+) -> bool {
+  let signals = zbus::blocking::Proxy::new(&connection, destination, path, interface)
+    .and_then(|proxy| proxy.receive_signal(signal));
+  match signals {
+    Ok(signals) => {
+      std::thread::spawn(move || {
+        for _ in signals {
+          if !notify(&sender, &last) {
+            break;
+          }
+        }
+      });
+      true
+    }
+    Err(_) => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{session_state, watch_session_state};
+  use crate::{
+    common::x_win_struct::{diagnostic_report::SessionType, session_state::SessionState},
+    linux::api::test_dbus::FakeGnomeShell,
+  };
+
+  #[test]
+  fn test_session_state() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    assert_eq!(
+      session_state()?,
+      SessionState::new(false, true, SessionType::Wayland)
+    );
+    // GNOME shows its screen saver before logind knows about the lock
+    shell.state().screen_saver = true;
+    assert!(session_state()?.locked);
+    shell.state().screen_saver = false;
+    shell.state().session = (false, false, String::from("x11"));
+    assert_eq!(
+      session_state()?,
+      SessionState::new(false, false, SessionType::X11)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_watch_session_state() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    let timeout = Duration::from_secs(5);
+    let states = watch_session_state()?;
+    assert!(!states.recv_timeout(timeout)?.is_away());
+
+    shell.set_locked(true)?;
+    let locked = states.recv_timeout(timeout)?;
+    assert!(locked.locked && locked.active);
+    // Both signals of the lock give the same state, only one is received
+    shell.set_session_active(false)?;
+    assert_eq!(
+      states.recv_timeout(timeout)?,
+      SessionState::new(true, false, SessionType::Wayland)
+    );
+
+    shell.set_session_active(true)?;
+    shell.set_locked(false)?;
+    let mut last = states.recv_timeout(timeout)?;
+    while let Ok(state) = states.recv_timeout(Duration::from_millis(200)) {
+      last = state;
+    }
+    assert!(!last.is_away());
+    Ok(())
+  }
+}
@@ -0,0 +1,485 @@
+#![cfg(test)]
+
+use std::{
+  collections::HashMap,
+  io::{BufRead, BufReader},
+  process::{Child, Command, Stdio},
+  sync::{Arc, Mutex, MutexGuard},
+};
+
+use once_cell::sync::Lazy;
+use zbus::{
+  blocking::{connection, Connection},
+  fdo, interface,
+  zvariant::{OwnedValue, Value},
+};
+
+use super::{
+  gnome_dbus::{reset_connections, ExtensionIcon, ExtensionWindow},
+  gnome_shell::{GNOME_XWIN_UUID, XWIN_PATH},
+};
+
+/**
+ * Private `dbus-daemon --session` started for a test and killed on drop
+ */
+pub struct PrivateBus {
+  child: Child,
+  pub address: String,
+}
+
+impl PrivateBus {
+  /**
+   * Return an error failing the test when `dbus-daemon` is not available, D-Bus tests are never skipped
+   */
+  pub fn start() -> Result<Self, String> {
+    let missing =
+      |err: &dyn std::fmt::Display| format!("dbus-daemon is required by D-Bus tests: {err}");
+    let mut child = Command::new("dbus-daemon")
+      .args(["--session", "--nofork", "--print-address=1"])
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+      .map_err(|err| missing(&err))?;
+    let mut address = String::new();
+    if let Some(stdout) = child.stdout.take() {
+      BufReader::new(stdout)
+        .read_line(&mut address)
+        .map_err(|err| missing(&err))?;
+    }
+    let address = address.trim().to_owned();
+    if address.is_empty() {
+      let _ = child.kill();
+      return Err(missing(&"no address printed"));
+    }
+    Ok(Self { child, address })
+  }
+}
+
+impl Drop for PrivateBus {
+  fn drop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+  }
+}
+
+/**
+ * Tests redirecting the session bus to a fake GNOME Shell can't run at the same time
+ */
+static SESSION_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/**
+ * Taken by tests calling the GNOME backend without `FakeGnomeShell`, another test could redirect the session bus meanwhile
+ */
+pub fn session_lock() -> MutexGuard<'static, ()> {
+  lock(&SESSION_LOCK)
+}
+
+/**
+ * Bus used instead of the session and system buses by tests driving the GNOME backend
+ */
+static TEST_SESSION_ADDRESS: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/**
+ * Send calls of the GNOME backend and of logind to the bus at `address`, or to the session and system buses again with `None`
+ */
+pub fn set_test_session(address: Option<&str>) {
+  *lock(&TEST_SESSION_ADDRESS) = address.map(|address| address.to_owned());
+  reset_connections();
+}
+
+/**
+ * Open the shared session connection on the bus given by `set_test_session`
+ */
+pub fn test_session_connection() -> zbus::Result<Connection> {
+  let address = lock(&TEST_SESSION_ADDRESS).clone();
+  match address {
+    Some(address) => connection::Builder::address(address.as_str())?.build(),
+    None => Connection::session(),
+  }
+}
+
+/**
+ * Open the shared system connection, logind is served on the same private bus as GNOME Shell
+ */
+pub fn test_system_connection() -> zbus::Result<Connection> {
+  let redirected = lock(&TEST_SESSION_ADDRESS).is_some();
+  match redirected {
+    true => test_session_connection(),
+    false => Connection::system(),
+  }
+}
+
+pub fn fake_window(id: u32) -> ExtensionWindow {
+  ExtensionWindow {
+    id,
+    os: String::from("linux"),
+    title: String::from("x-win - Visual Studio Code"),
+    x: 10,
+    y: -20,
+    width: 1920,
+    height: 1080,
+    is_full_screen: true,
+    process_id: 4242,
+    path: String::from("/usr/share/code/code"),
+    name: String::from("Code"),
+    exec_name: String::from("code"),
+    memory: 1024,
+  }
+}
+
+/**
+ * `XWinWaylandExtension` object exported by the extension
+ */
+pub struct FakeExtension {
+  pub schema_version: u32,
+  pub windows: Vec<ExtensionWindow>,
+}
+
+impl FakeExtension {
+  pub fn new(schema_version: u32) -> Self {
+    Self {
+      schema_version,
+      windows: vec![fake_window(1), fake_window(2)],
+    }
+  }
+}
+
+#[interface(name = "org.gnome.Shell.Extensions.XWinWaylandExtension")]
+impl FakeExtension {
+  #[zbus(name = "get_active_window")]
+  fn get_active_window(&self) -> fdo::Result<ExtensionWindow> {
+    self
+      .windows
+      .first()
+      .cloned()
+      .ok_or_else(|| fdo::Error::Failed(String::from("No active window")))
+  }
+
+  #[zbus(name = "get_open_windows")]
+  fn get_open_windows(&self) -> Vec<ExtensionWindow> {
+    self.windows.clone()
+  }
+
+  #[zbus(name = "get_icon")]
+  fn get_icon(&self, window_id: u32, size: u32) -> ExtensionIcon {
+    ExtensionIcon {
+      data: vec![window_id as u8, 2, 3],
+      mime: String::from("image/png"),
+      height: size,
+      width: size,
+    }
+  }
+
+  #[zbus(property, name = "SchemaVersion")]
+  fn schema_version(&self) -> u32 {
+    self.schema_version
+  }
+}
+
+/**
+ * Extension released before the schema version, returning JSON strings and without `SchemaVersion`
+ */
+pub struct FakeJsonExtension;
+
+#[interface(name = "org.gnome.Shell.Extensions.XWinWaylandExtension")]
+impl FakeJsonExtension {
+  #[zbus(name = "get_active_window")]
+  fn get_active_window(&self) -> String {
+    String::from("{}")
+  }
+
+  #[zbus(name = "get_open_windows")]
+  fn get_open_windows(&self) -> String {
+    String::from("[]")
+  }
+
+  #[zbus(name = "get_icon")]
+  fn get_icon(&self, _window_id: u32, _size: u32) -> String {
+    String::from(r#"{"data":"","height":0,"width":0}"#)
+  }
+}
+
+/**
+ * State of the fake GNOME Shell, changed by the test and by calls of x-win
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellState {
+  /**
+   * Response of `Eval`
+   */
+  pub eval: (bool, String),
+  /**
+   * Scripts received by `Eval`
+   */
+  pub scripts: Vec<String>,
+  /**
+   * `state` and `error` returned by `GetExtensionInfo`, `None` when the extension is not installed
+   */
+  pub extension: Option<(f64, String)>,
+  /**
+   * Milliseconds returned by `GetIdletime` of the Mutter idle monitor
+   */
+  pub idle_time: u64,
+  /**
+   * `Active`, `LockedHint` and `Type` of the logind session
+   */
+  pub session: (bool, bool, String),
+  /**
+   * Returned by `GetActive` of `org.gnome.ScreenSaver`
+   */
+  pub screen_saver: bool,
+}
+
+impl Default for ShellState {
+  fn default() -> Self {
+    Self {
+      eval: (true, String::new()),
+      scripts: vec![],
+      extension: None,
+      idle_time: 0,
+      session: (true, false, String::from("wayland")),
+      screen_saver: false,
+    }
+  }
+}
+
+struct FakeShell {
+  state: Arc<Mutex<ShellState>>,
+}
+
+#[interface(name = "org.gnome.Shell")]
+impl FakeShell {
+  fn eval(&self, script: String) -> (bool, String) {
+    let mut state = lock(&self.state);
+    state.scripts.push(script);
+    state.eval.clone()
+  }
+
+  #[zbus(property)]
+  fn shell_version(&self) -> String {
+    String::from("46.2")
+  }
+}
+
+struct FakeShellExtensions {
+  state: Arc<Mutex<ShellState>>,
+}
+
+#[interface(name = "org.gnome.Shell.Extensions")]
+impl FakeShellExtensions {
+  fn get_extension_info(&self, uuid: String) -> HashMap<String, OwnedValue> {
+    let mut info = HashMap::new();
+    if let (Some((state, error)), true) = (&lock(&self.state).extension, uuid.eq(GNOME_XWIN_UUID)) {
+      info.insert(String::from("uuid"), owned(Value::from(uuid)));
+      info.insert(String::from("state"), owned(Value::from(*state)));
+      info.insert(String::from("error"), owned(Value::from(error.as_str())));
+    }
+    info
+  }
+
+  fn enable_extension(&self, uuid: String) -> bool {
+    self.set_extension_state(&uuid, 1.0)
+  }
+
+  fn disable_extension(&self, uuid: String) -> bool {
+    self.set_extension_state(&uuid, 2.0)
+  }
+}
+
+impl FakeShellExtensions {
+  fn set_extension_state(&self, uuid: &str, new_state: f64) -> bool {
+    match (&mut lock(&self.state).extension, uuid.eq(GNOME_XWIN_UUID)) {
+      (Some((state, _)), true) => {
+        *state = new_state;
+        true
+      }
+      _ => false,
+    }
+  }
+}
+
+struct FakeIdleMonitor {
+  state: Arc<Mutex<ShellState>>,
+}
+
+#[interface(name = "org.gnome.Mutter.IdleMonitor")]
+impl FakeIdleMonitor {
+  fn get_idletime(&self) -> u64 {
+    lock(&self.state).idle_time
+  }
+}
+
+struct FakeLogin1Session {
+  state: Arc<Mutex<ShellState>>,
+}
+
+#[interface(name = "org.freedesktop.login1.Session")]
+impl FakeLogin1Session {
+  #[zbus(property)]
+  fn active(&self) -> bool {
+    lock(&self.state).session.0
+  }
+
+  #[zbus(property)]
+  fn locked_hint(&self) -> bool {
+    lock(&self.state).session.1
+  }
+
+  #[zbus(property, name = "Type")]
+  fn session_type(&self) -> String {
+    lock(&self.state).session.2.clone()
+  }
+}
+
+struct FakeScreenSaver {
+  state: Arc<Mutex<ShellState>>,
+}
+
+#[interface(name = "org.gnome.ScreenSaver")]
+impl FakeScreenSaver {
+  fn get_active(&self) -> bool {
+    lock(&self.state).screen_saver
+  }
+}
+
+fn owned(value: Value<'_>) -> OwnedValue {
+  OwnedValue::try_from(value).unwrap_or_else(|_| OwnedValue::from(0u32))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/**
+ * Own `org.gnome.Shell` on the bus at `address` with `Eval`, extension management and optionally the extension object.
+ * The Mutter idle monitor, the GNOME screen saver and the logind session are served on the same connection.
+ */
+pub fn serve_gnome_shell(
+  address: &str,
+  state: Arc<Mutex<ShellState>>,
+  extension: Option<FakeExtension>,
+) -> zbus::Result<Connection> {
+  let builder = connection::Builder::address(address)?
+    .name("org.gnome.Shell")?
+    .name("org.gnome.Mutter.IdleMonitor")?
+    .name("org.gnome.ScreenSaver")?
+    .name("org.freedesktop.login1")?
+    .serve_at(
+      "/org/gnome/ScreenSaver",
+      FakeScreenSaver {
+        state: state.clone(),
+      },
+    )?
+    .serve_at(
+      "/org/freedesktop/login1/session/auto",
+      FakeLogin1Session {
+        state: state.clone(),
+      },
+    )?
+    .serve_at(
+      "/org/gnome/Mutter/IdleMonitor/Core",
+      FakeIdleMonitor {
+        state: state.clone(),
+      },
+    )?
+    .serve_at(
+      "/org/gnome/Shell",
+      FakeShell {
+        state: state.clone(),
+      },
+    )?
+    .serve_at("/org/gnome/Shell", FakeShellExtensions { state })?;
+  match extension {
+    Some(extension) => builder.serve_at(XWIN_PATH, extension)?.build(),
+    None => builder.build(),
+  }
+}
+
+/**
+ * Fake GNOME Shell on a private bus, used by the GNOME backend instead of the session bus until dropped
+ */
+pub struct FakeGnomeShell {
+  state: Arc<Mutex<ShellState>>,
+  service: Connection,
+  _bus: PrivateBus,
+  _lock: MutexGuard<'static, ()>,
+}
+
+impl FakeGnomeShell {
+  /**
+   * Return an error failing the test when `dbus-daemon` is not available
+   */
+  pub fn start() -> Result<Self, String> {
+    let lock = session_lock();
+    let bus = PrivateBus::start()?;
+    let state = Arc::new(Mutex::new(ShellState::default()));
+    let service =
+      serve_gnome_shell(&bus.address, state.clone(), None).map_err(|err| err.to_string())?;
+    set_test_session(Some(&bus.address));
+    Ok(Self {
+      state,
+      service,
+      _bus: bus,
+      _lock: lock,
+    })
+  }
+
+  pub fn state(&self) -> MutexGuard<'_, ShellState> {
+    lock(&self.state)
+  }
+
+  /**
+   * Export the object of the extension, like GNOME Shell does once the extension is enabled
+   */
+  pub fn export_extension(&self, extension: FakeExtension) -> zbus::Result<bool> {
+    self.service.object_server().at(XWIN_PATH, extension)
+  }
+
+  /**
+   * Lock or unlock the screen like GNOME Shell does, with `ActiveChanged` and logind `LockedHint`
+   */
+  pub fn set_locked(&self, locked: bool) -> zbus::Result<()> {
+    {
+      let mut state = self.state();
+      state.screen_saver = locked;
+      state.session.1 = locked;
+    }
+    self.service.emit_signal(
+      None::<()>,
+      "/org/gnome/ScreenSaver",
+      "org.gnome.ScreenSaver",
+      "ActiveChanged",
+      &(locked,),
+    )?;
+    self.emit_session_changed("LockedHint", locked)
+  }
+
+  /**
+   * Switch to another session of the seat and back, with logind `Active`
+   */
+  pub fn set_session_active(&self, active: bool) -> zbus::Result<()> {
+    self.state().session.0 = active;
+    self.emit_session_changed("Active", active)
+  }
+
+  fn emit_session_changed(&self, property: &str, value: bool) -> zbus::Result<()> {
+    let changed = HashMap::from([(property, Value::from(value))]);
+    self.service.emit_signal(
+      None::<()>,
+      "/org/freedesktop/login1/session/auto",
+      "org.freedesktop.DBus.Properties",
+      "PropertiesChanged",
+      &(
+        "org.freedesktop.login1.Session",
+        changed,
+        Vec::<&str>::new(),
+      ),
+    )
+  }
+}
+
+impl Drop for FakeGnomeShell {
+  fn drop(&mut self) {
+    set_test_session(None);
+  }
+}
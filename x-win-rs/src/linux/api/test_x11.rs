@@ -0,0 +1,291 @@
+#![cfg(test)]
+
+use std::{
+  io::{BufRead, BufReader},
+  process::{Child, Command, Stdio},
+};
+
+use xcb::{x, Connection, Xid};
+
+use super::x11_api::get_atom;
+use crate::common::result::Result;
+
+/**
+ * Private `Xvfb` server started for a test and killed on drop
+ */
+pub struct Xvfb {
+  child: Child,
+  pub display: String,
+}
+
+impl Xvfb {
+  /**
+   * Return an error failing the test when `Xvfb` is not available, tests using it are ignored by default
+   */
+  pub fn start() -> std::result::Result<Self, String> {
+    let missing = |err: &dyn std::fmt::Display| format!("Xvfb is required by X11 tests: {err}");
+    // Display number is chosen by Xvfb and written on `-displayfd` once it accepts connections
+    let mut child = Command::new("Xvfb")
+      .args([
+        "-displayfd",
+        "1",
+        "-nolisten",
+        "tcp",
+        "-screen",
+        "0",
+        "1280x800x24",
+      ])
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+      .map_err(|err| missing(&err))?;
+    let mut display = String::new();
+    if let Some(stdout) = child.stdout.take() {
+      BufReader::new(stdout)
+        .read_line(&mut display)
+        .map_err(|err| missing(&err))?;
+    }
+    let display = display.trim().to_owned();
+    if display.is_empty() {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(missing(&"no display printed"));
+    }
+    Ok(Self {
+      child,
+      display: format!(":{display}"),
+    })
+  }
+
+  pub fn connect(&self) -> Result<Connection> {
+    let (conn, _) = Connection::connect(Some(&self.display))?;
+    Ok(conn)
+  }
+}
+
+impl Drop for Xvfb {
+  fn drop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+  }
+}
+
+/**
+ * Client window created by `FakeWindowManager`
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestWindow {
+  pub title: String,
+  /**
+   * Instance and class of `WM_CLASS`
+   */
+  pub class: (String, String),
+  pub pid: u32,
+  pub x: i16,
+  pub y: i16,
+  pub width: u16,
+  pub height: u16,
+  pub full_screen: bool,
+  /**
+   * `_NET_WM_WINDOW_TYPE_NORMAL`, dialogs and docks are not listed as open windows
+   */
+  pub normal: bool,
+  /**
+   * `_NET_WM_ICON` values: width, height and ARGB pixels of each size
+   */
+  pub icon: Vec<u32>,
+}
+
+impl TestWindow {
+  pub fn new(title: &str, class: &str) -> Self {
+    Self {
+      title: title.to_owned(),
+      class: (class.to_lowercase(), class.to_owned()),
+      pid: std::process::id(),
+      x: 10,
+      y: 20,
+      width: 640,
+      height: 480,
+      full_screen: false,
+      normal: true,
+      icon: vec![],
+    }
+  }
+}
+
+/**
+ * Minimal EWMH window manager: it does not manage windows, it only publishes on the root window
+ * the properties read by x-win (`_NET_CLIENT_LIST_STACKING`, `_NET_ACTIVE_WINDOW`, ...)
+ */
+pub struct FakeWindowManager {
+  conn: Connection,
+  root: x::Window,
+  visual: x::Visualid,
+  clients: Vec<x::Window>,
+}
+
+impl FakeWindowManager {
+  pub fn start(xvfb: &Xvfb) -> Result<Self> {
+    let conn = xvfb.connect()?;
+    let (root, visual) = {
+      let screen = conn.get_setup().roots().next().ok_or("No screen on Xvfb")?;
+      (screen.root(), screen.root_visual())
+    };
+    let wm = Self {
+      conn,
+      root,
+      visual,
+      clients: vec![],
+    };
+
+    let check = wm.create(-1, -1, 1, 1)?;
+    let supporting_wm_check = wm.atom(b"_NET_SUPPORTING_WM_CHECK");
+    wm.set_property(root, supporting_wm_check, x::ATOM_WINDOW, &[check])?;
+    wm.set_property(check, supporting_wm_check, x::ATOM_WINDOW, &[check])?;
+    let utf8_string = wm.atom(b"UTF8_STRING");
+    wm.set_property(check, wm.atom(b"_NET_WM_NAME"), utf8_string, b"x-win-test")?;
+    let supported = [
+      wm.atom(b"_NET_ACTIVE_WINDOW"),
+      wm.atom(b"_NET_CLIENT_LIST_STACKING"),
+      wm.atom(b"_NET_WM_PID"),
+      wm.atom(b"_NET_WM_WINDOW_TYPE"),
+      wm.atom(b"_NET_WM_STATE"),
+      wm.atom(b"_NET_WM_STATE_FULLSCREEN"),
+      wm.atom(b"_NET_WM_ICON"),
+    ];
+    wm.set_property(root, wm.atom(b"_NET_SUPPORTED"), x::ATOM_ATOM, &supported)?;
+    wm.set_property::<x::Window>(
+      root,
+      wm.atom(b"_NET_CLIENT_LIST_STACKING"),
+      x::ATOM_WINDOW,
+      &[],
+    )?;
+    wm.set_property(
+      root,
+      wm.atom(b"_NET_ACTIVE_WINDOW"),
+      x::ATOM_WINDOW,
+      &[x::Window::none()],
+    )?;
+    Ok(wm)
+  }
+
+  /**
+   * Create and map `window` on top of the other clients
+   */
+  pub fn open(&mut self, window: &TestWindow) -> Result<x::Window> {
+    let id = self.create(window.x, window.y, window.width, window.height)?;
+    self.set_property(id, x::ATOM_WM_NAME, x::ATOM_STRING, window.title.as_bytes())?;
+    let class = format!("{}\0{}\0", window.class.0, window.class.1);
+    self.set_property(id, x::ATOM_WM_CLASS, x::ATOM_STRING, class.as_bytes())?;
+    self.set_property(
+      id,
+      self.atom(b"_NET_WM_PID"),
+      x::ATOM_CARDINAL,
+      &[window.pid],
+    )?;
+    let window_type = match window.normal {
+      true => self.atom(b"_NET_WM_WINDOW_TYPE_NORMAL"),
+      false => self.atom(b"_NET_WM_WINDOW_TYPE_DIALOG"),
+    };
+    self.set_property(
+      id,
+      self.atom(b"_NET_WM_WINDOW_TYPE"),
+      x::ATOM_ATOM,
+      &[window_type],
+    )?;
+    if window.full_screen {
+      self.set_property(
+        id,
+        self.atom(b"_NET_WM_STATE"),
+        x::ATOM_ATOM,
+        &[self.atom(b"_NET_WM_STATE_FULLSCREEN")],
+      )?;
+    }
+    if !window.icon.is_empty() {
+      self.set_property(
+        id,
+        self.atom(b"_NET_WM_ICON"),
+        x::ATOM_CARDINAL,
+        &window.icon,
+      )?;
+    }
+    self
+      .conn
+      .send_and_check_request(&x::MapWindow { window: id })?;
+    self.clients.push(id);
+    self.publish_clients()?;
+    Ok(id)
+  }
+
+  pub fn close(&mut self, window: x::Window) -> Result<()> {
+    self.clients.retain(|client| client.ne(&window));
+    self
+      .conn
+      .send_and_check_request(&x::DestroyWindow { window })?;
+    self.publish_clients()
+  }
+
+  /**
+   * Set `_NET_ACTIVE_WINDOW`, `x::Window::none()` when no window has the focus
+   */
+  pub fn focus(&self, window: x::Window) -> Result<()> {
+    self.set_property(
+      self.root,
+      self.atom(b"_NET_ACTIVE_WINDOW"),
+      x::ATOM_WINDOW,
+      &[window],
+    )
+  }
+
+  pub fn set_title(&self, window: x::Window, title: &str) -> Result<()> {
+    self.set_property(window, x::ATOM_WM_NAME, x::ATOM_STRING, title.as_bytes())
+  }
+
+  fn create(&self, x: i16, y: i16, width: u16, height: u16) -> Result<x::Window> {
+    let window: x::Window = self.conn.generate_id();
+    self.conn.send_and_check_request(&x::CreateWindow {
+      depth: x::COPY_FROM_PARENT as u8,
+      wid: window,
+      parent: self.root,
+      x,
+      y,
+      width,
+      height,
+      border_width: 0,
+      class: x::WindowClass::InputOutput,
+      visual: self.visual,
+      value_list: &[],
+    })?;
+    Ok(window)
+  }
+
+  fn publish_clients(&self) -> Result<()> {
+    self.set_property(
+      self.root,
+      self.atom(b"_NET_CLIENT_LIST_STACKING"),
+      x::ATOM_WINDOW,
+      &self.clients,
+    )
+  }
+
+  fn atom(&self, name: &[u8]) -> x::Atom {
+    get_atom(&self.conn, name, false)
+  }
+
+  fn set_property<P: x::PropEl>(
+    &self,
+    window: x::Window,
+    property: x::Atom,
+    r#type: x::Atom,
+    data: &[P],
+  ) -> Result<()> {
+    self.conn.send_and_check_request(&x::ChangeProperty {
+      mode: x::PropMode::Replace,
+      window,
+      property,
+      r#type,
+      data,
+    })?;
+    Ok(())
+  }
+}
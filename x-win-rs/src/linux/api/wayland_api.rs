@@ -1,50 +1,71 @@
 #![allow(unused_imports)]
 
 use std::{
-  ops::Deref,
   path::{Path, PathBuf},
+  time::Duration,
 };
 
 use crate::{
   common::{
     api::Api,
     result::Result,
-    x_win_struct::{icon_info::IconInfo, window_info::WindowInfo},
-  },
-  linux::api::{
-    common_api::{get_window_memory_usage, get_window_path_name},
-    gnome_shell::GNOME_XWIN_EXTENSION_FOLDER_PATH,
+    x_win_struct::{
+      browser_info::BrowserInfo,
+      capabilities::{Backend, Capabilities},
+      extension_status::ExtensionStatus,
+      icon_data::IconData,
+      icon_info::IconInfo,
+      icon_options::IconOptions,
+      install_options::InstallOptions,
+      window_info::WindowInfo,
+    },
   },
+  linux::api::common_api::{get_window_memory_usage, get_window_path_name},
 };
 
 use super::{
-  common_api::{get_gnome_version, init_entity},
-  gnome_shell::{self, GNOME_SINGLETON},
+  common_api::{init_entity, is_gnome_desktop},
+  flatpak::sandbox_info,
+  gnome_shell::{self, gnome_version},
+  idle_api::gnome_idle_time,
   wayland_eval_api, wayland_extension_api, APIGnome,
 };
 
-fn gnome_use_eval() -> bool {
-  match GNOME_SINGLETON.lock() {
-    Ok(gnome_singleton) => {
-      let use_eval: bool = gnome_singleton.use_eval;
-      let _ = gnome_singleton.deref();
-      use_eval
-    }
-    Err(_) => false,
-  }
+pub fn gnome_use_eval() -> bool {
+  gnome_version()
+    .map(|gnome_version| gnome_version.use_eval)
+    .unwrap_or(false)
 }
 
 /**
  * Struct to use similar as API to get active window and open windows for XOrg desktop
  */
-pub struct WaylandApi {}
+#[derive(Default)]
+pub struct WaylandApi {
+  /**
+   * Forced GNOME Shell backend, detected from GNOME Shell version on each call when `None`
+   */
+  use_eval: Option<bool>,
+}
+
+impl WaylandApi {
+  pub fn with_eval(use_eval: bool) -> Self {
+    Self {
+      use_eval: Some(use_eval),
+    }
+  }
+
+  fn use_eval(&self) -> bool {
+    self.use_eval.unwrap_or_else(gnome_use_eval)
+  }
+}
 
 /**
  * Impl. for Linux system
  */
 impl Api for WaylandApi {
   fn get_active_window(&self) -> Result<WindowInfo> {
-    if gnome_use_eval() {
+    if self.use_eval() {
       wayland_eval_api::get_active_window()
     } else {
       wayland_extension_api::get_active_window()
@@ -52,7 +73,7 @@ impl Api for WaylandApi {
   }
 
   fn get_open_windows(&self) -> Result<Vec<WindowInfo>> {
-    if gnome_use_eval() {
+    if self.use_eval() {
       wayland_eval_api::get_open_windows()
     } else {
       wayland_extension_api::get_open_windows()
@@ -60,30 +81,78 @@ impl Api for WaylandApi {
   }
 
   fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
-    if gnome_use_eval() {
-      wayland_eval_api::get_icon(window_info)
+    self.get_app_icon_with(window_info, &IconOptions::default())
+  }
+
+  fn get_app_icon_data(&self, window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
+    if self.use_eval() {
+      wayland_eval_api::get_icon(window_info, options)
+    } else {
+      wayland_extension_api::get_icon(window_info, options)
+    }
+  }
+
+  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+    super::atspi_api::get_browser_url(window_info)
+  }
+
+  fn get_idle_time(&self) -> Result<Duration> {
+    gnome_idle_time()
+  }
+
+  fn capabilities(&self) -> Capabilities {
+    if !is_gnome_desktop() {
+      return Capabilities::new(Backend::Unsupported);
+    }
+    let capabilities = if self.use_eval() {
+      gnome_capabilities(true, None, true)
     } else {
-      wayland_extension_api::get_icon(window_info)
+      gnome_capabilities(
+        false,
+        wayland_extension_api::extension_status().ok(),
+        sandbox_info().extension_install,
+      )
+    };
+    Capabilities {
+      browser_url: super::atspi_api::is_available(),
+      ..capabilities
     }
   }
+}
 
-  fn get_browser_url(&self, _: &WindowInfo) -> Result<String> {
-    Ok(super::common_api::get_browser_url())
+/**
+ * Capabilities of GNOME Shell, windows are recovered with the extension only once it is enabled
+ */
+fn gnome_capabilities(
+  use_eval: bool,
+  extension_status: Option<ExtensionStatus>,
+  extension_install: bool,
+) -> Capabilities {
+  if use_eval {
+    return Capabilities::with_windows(Backend::GnomeShellEval);
+  }
+  let capabilities = match extension_status {
+    Some(ExtensionStatus::Enabled) => Capabilities::with_windows(Backend::GnomeShellExtension),
+    _ => Capabilities::new(Backend::GnomeShellExtension),
+  };
+  Capabilities {
+    extension_install,
+    ..capabilities
   }
 }
 
 impl APIGnome for WaylandApi {
-  fn install_extension() -> Result<bool> {
+  fn install_extension_with(options: &InstallOptions) -> Result<bool> {
     if !gnome_use_eval() {
-      wayland_extension_api::install_extension()
+      wayland_extension_api::install_extension_with(options)
     } else {
       Ok(false)
     }
   }
 
-  fn uninstall_extension() -> Result<bool> {
+  fn uninstall_extension_with(options: &InstallOptions) -> Result<bool> {
     if !gnome_use_eval() {
-      wayland_extension_api::uninstall_extension()
+      wayland_extension_api::uninstall_extension_with(options)
     } else {
       Ok(false)
     }
@@ -120,4 +189,36 @@ impl APIGnome for WaylandApi {
       Ok(false)
     }
   }
+
+  fn extension_status() -> Result<ExtensionStatus> {
+    if !gnome_use_eval() {
+      wayland_extension_api::extension_status()
+    } else {
+      Ok(ExtensionStatus::NotInstalled)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::gnome_capabilities;
+  use crate::common::x_win_struct::{
+    capabilities::{Backend, Capabilities},
+    extension_status::ExtensionStatus,
+  };
+
+  #[test]
+  fn test_gnome_capabilities() {
+    assert_eq!(
+      gnome_capabilities(true, None, true),
+      Capabilities::with_windows(Backend::GnomeShellEval)
+    );
+    let capabilities = gnome_capabilities(false, Some(ExtensionStatus::Enabled), true);
+    assert_eq!(capabilities.backend, Backend::GnomeShellExtension);
+    assert!(capabilities.active_window && capabilities.icons && capabilities.extension_install);
+    let capabilities = gnome_capabilities(false, Some(ExtensionStatus::NotInstalled), false);
+    assert!(!capabilities.active_window && !capabilities.open_windows);
+    assert!(!capabilities.extension_install);
+    assert!(!gnome_capabilities(false, None, true).icons);
+  }
 }
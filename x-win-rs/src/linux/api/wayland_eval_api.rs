@@ -1,28 +1,26 @@
-use zbus::blocking::Connection;
-
 use crate::{
   common::{
-    api::empty_icon,
+    api::empty_icon_data,
+    icon::{convert_icon_data, icon_info_to_data},
     result::Result,
-    x_win_struct::{icon_info::IconInfo, window_info::WindowInfo},
+    x_win_struct::{icon_data::IconData, icon_options::IconOptions, window_info::WindowInfo},
   },
-  linux::api::gnome_shell::GNOME_XWIN_EVAL_SCRIPT,
+  linux::api::gnome_extension::{generate_script, ExtensionFlavour},
 };
 
 use super::{
   common_api::init_entity,
-  gnome_shell::{
-    value_to_icon_info, value_to_window_info, DESTINATION, GNOME_XWIN_GET_ICON_SCRIPT, SHELL_IFACE,
-    SHELL_PATH,
-  },
+  gnome_dbus::{shell_proxy, with_session},
+  gnome_shell::{value_to_icon_info, value_to_window_info},
 };
 
 pub fn get_active_window() -> Result<WindowInfo> {
   let script = format!(
     r#"
-{GNOME_XWIN_EVAL_SCRIPT}
+{}
 get_active_window();
-"#
+"#,
+    generate_script(ExtensionFlavour::Eval)
   );
 
   let response = call_script(&script)?;
@@ -30,7 +28,7 @@ get_active_window();
   if !response.is_empty() {
     let response: serde_json::Value = serde_json::from_str(response.as_str())?;
     if response.is_object() {
-      return Ok(value_to_window_info(&response)?);
+      return value_to_window_info(&response);
     }
   }
 
@@ -40,10 +38,10 @@ get_active_window();
 pub fn get_open_windows() -> Result<Vec<WindowInfo>> {
   let script = format!(
     r#"
-{GNOME_XWIN_EVAL_SCRIPT}
-
+{}
 get_open_windows();
-"#
+"#,
+    generate_script(ExtensionFlavour::Eval)
   );
 
   let response = call_script(&script)?;
@@ -64,28 +62,25 @@ get_open_windows();
   Ok(vec![])
 }
 
-fn call_script(script: &String) -> Result<String> {
-  let connection = Connection::session()?;
-
-  let response = connection.call_method(DESTINATION, SHELL_PATH, SHELL_IFACE, "Eval", script)?;
-
-  if !response.body().is_empty() {
-    let response: String = response.body().deserialize()?;
+fn call_script(script: &str) -> Result<String> {
+  let (success, response) = with_session(|connection| Ok(shell_proxy(connection)?.eval(script)?))?;
+  if success {
     return Ok(response);
   }
 
-  Err(String::from("Not possible to execute eval gnome shell").into())
+  Err(format!("Not possible to execute eval gnome shell: {response}").into())
 }
 
-pub fn get_icon(window_info: &WindowInfo) -> Result<IconInfo> {
+pub fn get_icon(window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
   if window_info.id.ne(&0) {
     let script = format!(
       r#"
-{GNOME_XWIN_EVAL_SCRIPT}
-{GNOME_XWIN_GET_ICON_SCRIPT}
-get_icon({0});
+{0}
+get_icon({1}, {2});
 "#,
-      window_info.id
+      generate_script(ExtensionFlavour::Eval),
+      window_info.id,
+      options.preferred_size
     );
 
     let response = call_script(&script)?;
@@ -93,10 +88,74 @@ get_icon({0});
     if !response.is_empty() {
       let response: serde_json::Value = serde_json::from_str(response.as_str())?;
       if response.is_object() {
-        return Ok(value_to_icon_info(&response)?);
+        let icon_data = icon_info_to_data(&value_to_icon_info(&response)?)?;
+        return convert_icon_data(icon_data, options.format);
       }
     }
   }
 
-  Ok(empty_icon())
+  Ok(empty_icon_data())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{get_active_window, get_open_windows};
+  use crate::linux::api::{common_api::init_entity, test_dbus::FakeGnomeShell};
+
+  fn window_json(id: u32) -> String {
+    serde_json::json!({
+      "id": id,
+      "os": "linux",
+      "title": "new terminal",
+      "position": { "x": 1077, "y": 192, "width": 866, "height": 629, "isFullScreen": false },
+      "info": {
+        "processId": 95389,
+        "path": "/usr/libexec/gnome-terminal-server",
+        "name": "gnome-terminal-server",
+        "execName": "gnome-terminal-server"
+      },
+      "usage": { "memory": 139473 }
+    })
+    .to_string()
+  }
+
+  #[test]
+  fn test_eval() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    shell.state().eval = (true, window_json(7));
+    let active_window = get_active_window()?;
+    assert_eq!(active_window.id, 7);
+    assert_eq!(active_window.info.process_id, 95389);
+    assert!(shell
+      .state()
+      .scripts
+      .last()
+      .map(|script| script.contains("get_active_window();"))
+      .unwrap_or(false));
+
+    shell.state().eval = (true, format!("[{},{}]", window_json(7), window_json(8)));
+    let open_windows = get_open_windows()?;
+    assert_eq!(open_windows.len(), 2);
+    assert_eq!(open_windows[1].id, 8);
+
+    // Nothing is focused
+    shell.state().eval = (true, String::new());
+    assert_eq!(get_active_window()?, init_entity());
+    assert!(get_open_windows()?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn test_eval_disabled() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    // GNOME Shell 41+ refuses `Eval` outside of unsafe mode
+    shell.state().eval = (false, String::new());
+    let err = get_active_window()
+      .err()
+      .ok_or("active window recovered while eval is disabled")?;
+    assert!(err
+      .to_string()
+      .contains("Not possible to execute eval gnome shell"));
+    Ok(())
+  }
 }
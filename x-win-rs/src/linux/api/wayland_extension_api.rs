@@ -1,174 +1,358 @@
-use zbus::{blocking::Connection, zvariant::OwnedValue, Message};
+use zbus::zvariant::OwnedValue;
 
-use std::{collections::HashMap, env, fs, ops::Deref, path};
+use std::{
+  collections::HashMap,
+  env, fs,
+  path::{self, Path},
+};
 
 use crate::{
   common::{
-    api::empty_icon,
+    api::empty_icon_data,
+    error::{Error, XWinError},
+    hash::fnv1a_64,
+    icon::convert_icon_data,
     result::Result,
-    x_win_struct::{icon_info::IconInfo, window_info::WindowInfo},
+    x_win_struct::{
+      extension_status::ExtensionStatus,
+      icon_data::IconData,
+      icon_options::IconOptions,
+      install_options::{InstallOptions, InstallScope},
+      window_info::WindowInfo,
+    },
   },
   linux::api::gnome_shell::{
-    value_to_window_info, GNOME45_XWIN_EXTENSION_SCRIPT, GNOME_SINGLETON,
-    GNOME_XWIN_EXTENSION_COMMON_SCRIPT, GNOME_XWIN_EXTENSION_FOLDER_PATH,
-    GNOME_XWIN_EXTENSION_META, GNOME_XWIN_EXTENSION_SCRIPT, GNOME_XWIN_UUID,
+    gnome_version, GNOME_XWIN_EXTENSION_META, GNOME_XWIN_SCHEMA_OVERRIDE_FILE_NAME, GNOME_XWIN_UUID,
   },
 };
 
 use super::{
-  common_api::init_entity,
-  gnome_shell::{
-    value_to_icon_info, DESTINATION, GNOME_XWIN_GET_ICON_SCRIPT, SHELL_IFACE, SHELL_PATH,
-    XWIN_IFACE, XWIN_PATH,
-  },
+  flatpak::{host_command, host_home, is_flatpak},
+  gnome_dbus::{call_extension, shell_extensions_proxy, with_session},
+  gnome_extension::{generate_script, ExtensionFlavour},
+  gnome_shell::{GLIB_SCHEMAS_FOLDER_PATH, GNOME_EXTENSIONS_FOLDER_PATH},
 };
 
+const EXTENSION_FILE_NAME: &str = "extension.js";
+const METADATA_FILE_NAME: &str = "metadata.json";
+const GLIB_COMPILED_SCHEMAS_FILE_NAME: &str = "gschemas.compiled";
+/// Keys added to `metadata.json` to know which x-win wrote the extension
+const XWIN_VERSION_KEY: &str = "x-win-version";
+const XWIN_HASH_KEY: &str = "x-win-hash";
+
 pub fn get_active_window() -> Result<WindowInfo> {
-  let response = call_script("get_active_window");
+  let window = call_extension(|proxy| {
+    proxy
+      .get_active_window()
+      .map_err(|err| extension_call_error("active window", err))
+  })?;
+  Ok(window.into())
+}
 
-  if response.is_err() {
-    return Err(
-      format!(
-        r#"Unable to get informations of active window from "{GNOME_XWIN_UUID}" extension via GNOME Shell. Please verify that the extension is correctly installed or enabled."#
-      ).into()
-    );
-  }
+pub fn get_open_windows() -> Result<Vec<WindowInfo>> {
+  let windows = call_extension(|proxy| {
+    proxy
+      .get_open_windows()
+      .map_err(|err| extension_call_error("open windows", err))
+  })?;
+  Ok(windows.into_iter().map(|window| window.into()).collect())
+}
 
-  if let Ok(response) = response {
-    if !response.is_empty() {
-      let response: serde_json::Value = serde_json::from_str(response.as_str())?;
-      return match response.is_object() {
-        true => Ok(value_to_window_info(&response)?),
-        false => Err(String::from("No data founded for active window").into()),
-      };
+pub fn get_icon(window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
+  if window_info.id.ne(&0) {
+    let icon = call_extension(|proxy| Ok(proxy.get_icon(window_info.id, options.preferred_size)?))?;
+    if !icon.data.is_empty() {
+      return convert_icon_data(icon.into(), options.format);
     }
   }
-  Ok(init_entity())
+  Ok(empty_icon_data())
 }
 
-pub fn get_open_windows() -> Result<Vec<WindowInfo>> {
-  let response = call_script("get_open_windows");
-
-  if response.is_err() {
-    return Err(
-      format!(
-        r#"Unable to get informations of open windows from "{GNOME_XWIN_UUID}" extension via GNOME Shell. Please verify that the extension is correctly installed or enabled."#
-      ).into()
-    );
+/**
+ * Describe a failed call of the extension, disconnection errors are kept as is to be retried
+ */
+fn extension_call_error(subject: &str, err: zbus::Error) -> Error {
+  if matches!(err, zbus::Error::MethodError(..)) {
+    return format!(
+      r#"Unable to get informations of {subject} from "{GNOME_XWIN_UUID}" extension via GNOME Shell: {err}"#
+    )
+    .into();
   }
+  err.into()
+}
 
-  let response = response?;
-
-  if !response.is_empty() {
-    let response: serde_json::Value = serde_json::from_str(response.as_str())?;
-    match response.as_array() {
-      Some(values) => {
-        let mut response: Vec<WindowInfo> = vec![];
-        for value in values {
-          let value = value_to_window_info(value)?;
-          response.push(value);
+pub fn install_extension_with(options: &InstallOptions) -> Result<bool> {
+  write_extension(
+    &get_extension_path_for(&options.scope)?,
+    &extension_script()?,
+  )?;
+  if options.enable_by_default {
+    match &options.scope {
+      InstallScope::User => enable_for_user()?,
+      scope => {
+        let schemas_path = get_prefix(scope)?.join(GLIB_SCHEMAS_FOLDER_PATH);
+        write_schema_override(&schemas_path)?;
+        // Packaging prefixes are compiled by the package manager on installation
+        if scope.eq(&InstallScope::System) {
+          compile_schemas(&schemas_path)?;
         }
-        return Ok(response);
       }
-      _ => return Ok(vec![]),
     }
   }
-  Ok(vec![])
+  Ok(true)
 }
 
-pub fn get_icon(window_info: &WindowInfo) -> Result<IconInfo> {
-  if window_info.id.ne(&0) {
-    let response = call_script_arg("get_icon", window_info.id)?;
-    if !response.is_empty() {
-      let response: serde_json::Value = serde_json::from_str(response.as_str())?;
-      if response.is_object() {
-        return Ok(value_to_icon_info(&response)?);
+/**
+ * Write a GSettings override adding the extension to `org.gnome.shell enabled-extensions` default value
+ */
+fn write_schema_override(schemas_path: &Path) -> Result<()> {
+  fs::create_dir_all(schemas_path).map_err(|err| install_error(schemas_path, err))?;
+  write_file(
+    &schemas_path.join(GNOME_XWIN_SCHEMA_OVERRIDE_FILE_NAME),
+    &format!(
+      "[org.gnome.shell]\nenabled-extensions={}\n",
+      format_string_list(&[GNOME_XWIN_UUID.to_owned()])
+    ),
+  )
+}
+
+fn compile_schemas(schemas_path: &Path) -> Result<()> {
+  let output = host_command("glib-compile-schemas")
+    .arg(schemas_path)
+    .output()
+    .map_err(|err| install_error(schemas_path, err))?;
+  if !output.status.success() {
+    return Err(
+      XWinError::ExtensionInstall {
+        path: schemas_path.to_string_lossy().to_string(),
+        reason: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
       }
-    }
+      .into(),
+    );
   }
-  Ok(empty_icon())
+  Ok(())
 }
 
-pub fn install_extension() -> Result<bool> {
-  if std::fs::metadata(get_extension_path()?).is_ok() {
-    if std::fs::metadata(get_extension_file_path()?).is_ok() {
-      remove_extension_file()?;
-    }
-    if std::fs::metadata(get_medata_file_path()?).is_ok() {
-      remove_metadata_file()?;
+/**
+ * Add the extension to `enabled-extensions` of the current user, GNOME Shell will enable it on next session
+ */
+fn enable_for_user() -> Result<()> {
+  set_enabled_for_user(true)
+}
+
+/**
+ * Remove the extension from `enabled-extensions` of the current user
+ */
+fn disable_for_user() -> Result<()> {
+  set_enabled_for_user(false)
+}
+
+fn set_enabled_for_user(enabled: bool) -> Result<()> {
+  let setting_error = |reason: String| -> Error {
+    XWinError::ExtensionInstall {
+      path: String::from("org.gnome.shell enabled-extensions"),
+      reason,
     }
-  } else if let Err(create_folder_err) = std::fs::create_dir_all(get_extension_path()?) {
-    panic!(
-      "Not possible to create extension folder to \"{}\"!\nRaison: {}",
-      get_extension_path()?.to_string_lossy(),
-      create_folder_err
-    );
+    .into()
+  };
+  let output = host_command("gsettings")
+    .args(["get", "org.gnome.shell", "enabled-extensions"])
+    .output()
+    .map_err(|err| setting_error(err.to_string()))?;
+  if !output.status.success() {
+    return Err(setting_error(
+      String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+    ));
+  }
+  let mut extensions = parse_string_list(&String::from_utf8_lossy(&output.stdout));
+  if extensions
+    .iter()
+    .any(|uuid| uuid.eq(GNOME_XWIN_UUID))
+    .eq(&enabled)
+  {
+    return Ok(());
   }
+  match enabled {
+    true => extensions.push(GNOME_XWIN_UUID.to_owned()),
+    false => extensions.retain(|uuid| uuid.ne(GNOME_XWIN_UUID)),
+  }
+  let output = host_command("gsettings")
+    .args([
+      "set",
+      "org.gnome.shell",
+      "enabled-extensions",
+      &format_string_list(&extensions),
+    ])
+    .output()
+    .map_err(|err| setting_error(err.to_string()))?;
+  if !output.status.success() {
+    return Err(setting_error(
+      String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+    ));
+  }
+  Ok(())
+}
 
-  let script: String = {
-    let gnome_singleton = GNOME_SINGLETON.lock()?;
-    let version: u32 = gnome_singleton.version;
-    let _ = gnome_singleton.deref();
-    let script: String = match version {
-      x if x.lt(&45) => GNOME_XWIN_EXTENSION_SCRIPT.to_string(),
-      _ => GNOME45_XWIN_EXTENSION_SCRIPT.to_string(),
-    };
+/**
+ * Parse a GVariant string array printed by `gsettings` (`['a', 'b']` or `@as []`)
+ */
+fn parse_string_list(value: &str) -> Vec<String> {
+  value
+    .split('\'')
+    .skip(1)
+    .step_by(2)
+    .map(|value| value.to_owned())
+    .collect()
+}
 
-    let script: String = format!(
-      r#"{script}
+fn format_string_list(values: &[String]) -> String {
+  let values: Vec<String> = values.iter().map(|value| format!("'{value}'")).collect();
+  format!("[{}]", values.join(", "))
+}
 
-{GNOME_XWIN_EXTENSION_COMMON_SCRIPT}
+/**
+ * Return the state of the extension from installed files and GNOME Shell
+ */
+pub fn extension_status() -> Result<ExtensionStatus> {
+  // Extension of the user takes precedence over the one installed for all users
+  let extension_path = match get_extension_path()? {
+    path if is_installed_at(&path) => path,
+    _ => get_extension_path_for(&InstallScope::System)?,
+  };
+  if !is_installed_at(&extension_path) {
+    return Ok(ExtensionStatus::NotInstalled);
+  }
+  if installed_hash(&extension_path) != Some(script_hash(&extension_script()?)) {
+    return Ok(ExtensionStatus::Outdated);
+  }
+  Ok(status_from_info(&request_extension_info()?))
+}
 
-{GNOME_XWIN_GET_ICON_SCRIPT}
-"#
-    );
+/**
+ * Convert `GetExtensionInfo` response of GNOME Shell to `ExtensionStatus`
+ */
+fn status_from_info(info: &HashMap<String, OwnedValue>) -> ExtensionStatus {
+  if info.is_empty() {
+    return ExtensionStatus::InstalledNeedsRestart;
+  }
+  let state = info
+    .get("state")
+    .and_then(|v| v.downcast_ref::<f64>().ok())
+    .unwrap_or(0.0);
+  let error = info
+    .get("error")
+    .and_then(|v| v.downcast_ref::<String>().ok())
+    .unwrap_or_default();
+  // States from GNOME Shell `ExtensionState`
+  match state as u32 {
+    1 => ExtensionStatus::Enabled,
+    3 => ExtensionStatus::Error(match error.is_empty() {
+      true => format!(
+        r#""{GNOME_XWIN_UUID}" extension is installed but does not work correctly. Please check your journalctl to find the error."#
+      ),
+      false => error,
+    }),
+    4 => ExtensionStatus::Error(format!(
+      r#""{GNOME_XWIN_UUID}" extension is not compatible with this GNOME Shell version."#
+    )),
+    0 | 99 => ExtensionStatus::InstalledNeedsRestart,
+    _ => ExtensionStatus::Disabled,
+  }
+}
 
-    script
-  };
+/**
+ * Script of the extension for the running GNOME Shell version
+ */
+fn extension_script() -> Result<String> {
+  let version: u32 = gnome_version()?.version;
+  Ok(generate_script(ExtensionFlavour::from_version(version)))
+}
 
-  if fs::write(get_extension_file_path()?, script).is_ok() {
-    if fs::write(get_medata_file_path()?, GNOME_XWIN_EXTENSION_META).is_ok() {
-      Ok(true)
-    } else {
-      remove_extension_file()?;
-      panic!(
-        "Not possible to write \"{}\" file!",
-        get_medata_file_path()?.to_string_lossy()
-      );
-    }
-  } else {
-    panic!(
-      "Not possible to write \"{}\" file!",
-      get_extension_file_path()?.to_string_lossy()
+fn script_hash(script: &str) -> String {
+  format!("{:016x}", fnv1a_64(script.as_bytes()))
+}
+
+/**
+ * Content of `metadata.json` stamped with the version of x-win and the hash of the script
+ */
+fn extension_metadata(script: &str) -> Result<String> {
+  let mut metadata: serde_json::Value = serde_json::from_str(GNOME_XWIN_EXTENSION_META)?;
+  if let Some(metadata) = metadata.as_object_mut() {
+    metadata.insert(
+      XWIN_VERSION_KEY.to_owned(),
+      env!("CARGO_PKG_VERSION").into(),
     );
+    metadata.insert(XWIN_HASH_KEY.to_owned(), script_hash(script).into());
   }
+  Ok(serde_json::to_string_pretty(&metadata)?)
 }
 
-fn toggle_extension(enable: bool) -> Result<bool> {
-  let connection = Connection::session()?;
-  let method_name = {
-    if enable {
-      "EnableExtension"
-    } else {
-      "DisableExtension"
-    }
-  };
-  let response = connection.call_method(
-    DESTINATION,
-    SHELL_PATH,
-    SHELL_IFACE,
-    method_name,
-    &GNOME_XWIN_UUID.to_string(),
+fn is_installed_at(extension_path: &Path) -> bool {
+  extension_path.join(EXTENSION_FILE_NAME).is_file()
+    && extension_path.join(METADATA_FILE_NAME).is_file()
+}
+
+/**
+ * Hash of the script stamped in `metadata.json`, `None` if not installed or installed by an older x-win
+ */
+fn installed_hash(extension_path: &Path) -> Option<String> {
+  let metadata = fs::read_to_string(extension_path.join(METADATA_FILE_NAME)).ok()?;
+  let metadata: serde_json::Value = serde_json::from_str(&metadata).ok()?;
+  metadata
+    .get(XWIN_HASH_KEY)?
+    .as_str()
+    .map(|hash| hash.to_owned())
+}
+
+/**
+ * Write extension files into `extension_path`, files are kept untouched when the installed script is the same.
+ * Return `true` if files have been written.
+ */
+fn write_extension(extension_path: &Path, script: &str) -> Result<bool> {
+  if is_installed_at(extension_path) && installed_hash(extension_path) == Some(script_hash(script))
+  {
+    return Ok(false);
+  }
+  fs::create_dir_all(extension_path).map_err(|err| install_error(extension_path, err))?;
+  write_file(&extension_path.join(EXTENSION_FILE_NAME), script)?;
+  // Metadata is written last, an interrupted upgrade is seen as outdated and written again
+  write_file(
+    &extension_path.join(METADATA_FILE_NAME),
+    &extension_metadata(script)?,
   )?;
+  Ok(true)
+}
 
-  if !response.body().is_empty() {
-    let response: bool = response.body().deserialize()?;
-    return Ok(response);
+/**
+ * Write `content` into a temporary file renamed to `path` to never leave a partially written file
+ */
+fn write_file(path: &Path, content: &str) -> Result<()> {
+  let temporary_path = path.with_extension("tmp");
+  fs::write(&temporary_path, content)
+    .and_then(|_| fs::rename(&temporary_path, path))
+    .map_err(|err| {
+      let _ = fs::remove_file(&temporary_path);
+      install_error(path, err)
+    })
+}
+
+fn install_error(path: &Path, err: std::io::Error) -> Error {
+  XWinError::ExtensionInstall {
+    path: path.to_string_lossy().to_string(),
+    reason: err.to_string(),
   }
+  .into()
+}
 
-  Err(format!(
-        "Unable to enable or disable the {GNOME_XWIN_UUID} extension via GNOME Shell. Please verify that the extension is correctly installed."
-    ).into())
+fn toggle_extension(enable: bool) -> Result<bool> {
+  with_session(|connection| {
+    let proxy = shell_extensions_proxy(connection)?;
+    if enable {
+      Ok(proxy.enable_extension(GNOME_XWIN_UUID)?)
+    } else {
+      Ok(proxy.disable_extension(GNOME_XWIN_UUID)?)
+    }
+  })
 }
 
 pub fn enable_extension() -> Result<bool> {
@@ -179,35 +363,60 @@ pub fn disable_extension() -> Result<bool> {
   toggle_extension(false)
 }
 
-pub fn uninstall_extension() -> Result<bool> {
-  if let Ok(_folder_dir) = cleanup_extension_folder() {
-    cleanup_extension_folder()?;
-    Ok(true)
-  } else {
-    Ok(false)
+/**
+ * Remove the extension of the scope, return false when it was not installed.
+ * The schema override of a system or custom prefix is removed and the compiled schemas of the prefix are compiled again.
+ */
+pub fn uninstall_extension_with(options: &InstallOptions) -> Result<bool> {
+  let extension_path = get_extension_path_for(&options.scope)?;
+  let removed = match fs::remove_dir_all(&extension_path) {
+    Ok(_) => true,
+    Err(err) if err.kind().eq(&std::io::ErrorKind::NotFound) => false,
+    Err(err) => return Err(install_error(&extension_path, err)),
+  };
+  match &options.scope {
+    InstallScope::User => {
+      if options.enable_by_default {
+        disable_for_user()?;
+      }
+    }
+    scope => remove_schema_override(scope)?,
+  }
+  Ok(removed)
+}
+
+fn remove_schema_override(scope: &InstallScope) -> Result<()> {
+  let schemas_path = get_prefix(scope)?.join(GLIB_SCHEMAS_FOLDER_PATH);
+  let override_path = schemas_path.join(GNOME_XWIN_SCHEMA_OVERRIDE_FILE_NAME);
+  match fs::remove_file(&override_path) {
+    Ok(_) => {}
+    Err(err) if err.kind().eq(&std::io::ErrorKind::NotFound) => return Ok(()),
+    Err(err) => return Err(install_error(&override_path, err)),
   }
+  // Compiled schemas hold every schema of the prefix, they are compiled again without the override
+  if scope.eq(&InstallScope::System) || schemas_path.join(GLIB_COMPILED_SCHEMAS_FILE_NAME).is_file()
+  {
+    compile_schemas(&schemas_path)?;
+  }
+  Ok(())
 }
 
 pub fn is_enabled_extension() -> Result<bool> {
   let response = request_extension_info()?;
-  let body = response.body();
-  if !body.is_empty() {
-    let response: HashMap<String, OwnedValue> = body.deserialize()?;
-    if !response.is_empty() {
-      let state = response
-        .get("state")
-        .and_then(|v| v.downcast_ref::<f64>().ok())
-        .unwrap_or(0.0);
-      // State 3 = Error
-      if state.eq(&3.0) {
-        return Err(
+  if !response.is_empty() {
+    let state = response
+      .get("state")
+      .and_then(|v| v.downcast_ref::<f64>().ok())
+      .unwrap_or(0.0);
+    // State 3 = Error
+    if state.eq(&3.0) {
+      return Err(
     format!(
       r#""{GNOME_XWIN_UUID}" extension is installed but does not work correctly. Please check your journalctl to find the error."#
     ).into()
   );
-      }
-      return Ok(state.eq(&1.0));
     }
+    return Ok(state.eq(&1.0));
   }
 
   Err(
@@ -219,104 +428,258 @@ pub fn is_enabled_extension() -> Result<bool> {
 
 pub fn is_installed_extension() -> Result<bool> {
   let response = request_extension_info()?;
-  let body = response.body();
-  if !body.is_empty() {
-    let response: HashMap<String, OwnedValue> = body.deserialize()?;
-    if !response.is_empty() {
-      let state = response
-        .get("state")
-        .and_then(|v| v.downcast_ref::<f64>().ok())
-        .unwrap_or(0.0);
-      // State 3 = Error
-      if state.eq(&3.0) {
-        return Err(
+  if !response.is_empty() {
+    let state = response
+      .get("state")
+      .and_then(|v| v.downcast_ref::<f64>().ok())
+      .unwrap_or(0.0);
+    // State 3 = Error
+    if state.eq(&3.0) {
+      return Err(
     format!(
       r#""{GNOME_XWIN_UUID}" extension is installed but does not work correctly. Please check your journalctl to find the error."#
     ).into()
   );
-      }
-      return Ok(state.ne(&0.0));
     }
+    return Ok(state.ne(&0.0));
   }
   Ok(false)
 }
 
-fn request_extension_info() -> Result<Message> {
-  let connection = Connection::session()?;
-  Ok(connection.call_method(
-    DESTINATION,
-    SHELL_PATH,
-    SHELL_IFACE,
-    "GetExtensionInfo",
-    &GNOME_XWIN_UUID.to_string(),
-  )?)
+fn request_extension_info() -> Result<HashMap<String, OwnedValue>> {
+  with_session(|connection| {
+    Ok(shell_extensions_proxy(connection)?.get_extension_info(GNOME_XWIN_UUID)?)
+  })
 }
 
 fn get_extension_path() -> Result<path::PathBuf> {
-  let home_dir = env::var_os("HOME").ok_or("HOME env not available")?;
-  let home_dir = home_dir.clone().to_string_lossy().to_string();
-  let extension_dir: String = GNOME_XWIN_EXTENSION_FOLDER_PATH.to_owned();
-  Ok([home_dir, extension_dir].iter().collect())
+  get_extension_path_for(&InstallScope::User)
 }
 
-fn cleanup_extension_folder() -> Result<()> {
-  Ok(fs::remove_dir_all(get_extension_path()?)?)
+/**
+ * Installation prefix of the scope
+ */
+fn get_prefix(scope: &InstallScope) -> Result<path::PathBuf> {
+  Ok(match scope {
+    InstallScope::User => host_home()?.join(".local"),
+    // `/usr` of a sandbox belongs to its runtime and is read-only
+    InstallScope::System if is_flatpak() => {
+      return Err(
+        XWinError::Unsupported(String::from(
+          "GNOME extension can't be installed for all users from a Flatpak sandbox",
+        ))
+        .into(),
+      )
+    }
+    InstallScope::System => path::PathBuf::from("/usr"),
+    InstallScope::Custom(prefix) => prefix.to_owned(),
+  })
 }
 
-fn get_extension_file_path() -> Result<path::PathBuf> {
-  Ok(get_extension_path()?.join("extension.js"))
+fn get_extension_path_for(scope: &InstallScope) -> Result<path::PathBuf> {
+  Ok(
+    get_prefix(scope)?
+      .join(GNOME_EXTENSIONS_FOLDER_PATH)
+      .join(GNOME_XWIN_UUID),
+  )
 }
 
-fn get_medata_file_path() -> Result<path::PathBuf> {
-  Ok(get_extension_path()?.join("metadata.json"))
-}
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashMap, fs};
 
-fn remove_metadata_file() -> Result<()> {
-  Ok(fs::remove_file(get_medata_file_path()?)?)
-}
+  use zbus::zvariant::{OwnedValue, Value};
 
-fn remove_extension_file() -> Result<()> {
-  Ok(fs::remove_file(get_extension_file_path()?)?)
-}
+  use super::{
+    disable_extension, enable_extension, format_string_list, get_active_window, get_icon,
+    get_open_windows, install_extension_with, installed_hash, is_enabled_extension,
+    is_installed_extension, parse_string_list, request_extension_info, script_hash,
+    status_from_info, uninstall_extension_with, write_extension, EXTENSION_FILE_NAME,
+    METADATA_FILE_NAME, XWIN_VERSION_KEY,
+  };
+  use crate::{
+    common::{
+      error::XWinError,
+      x_win_struct::{
+        extension_status::ExtensionStatus,
+        icon_options::IconOptions,
+        install_options::{InstallOptions, InstallScope},
+      },
+    },
+    linux::api::{
+      gnome_extension::GNOME_XWIN_SCHEMA_VERSION,
+      test_dbus::{session_lock, FakeExtension, FakeGnomeShell},
+    },
+  };
 
-fn call_script(method_name: &str) -> Result<String> {
-  let connection = Connection::session()?;
+  #[test]
+  fn test_write_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let extension_path =
+      std::env::temp_dir().join(format!("x-win-extension-{}", std::process::id()));
 
-  let response = connection.call_method(DESTINATION, XWIN_PATH, XWIN_IFACE, method_name, &())?;
+    assert!(write_extension(&extension_path, "// v1")?);
+    assert_eq!(
+      fs::read_to_string(extension_path.join(EXTENSION_FILE_NAME))?,
+      "// v1"
+    );
+    assert_eq!(installed_hash(&extension_path), Some(script_hash("// v1")));
+    let metadata: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+      extension_path.join(METADATA_FILE_NAME),
+    )?)?;
+    assert_eq!(metadata["uuid"], "x-win@miniben90.org");
+    assert_eq!(metadata[XWIN_VERSION_KEY], env!("CARGO_PKG_VERSION"));
+
+    assert!(!write_extension(&extension_path, "// v1")?);
+    assert!(write_extension(&extension_path, "// v2")?);
+    assert_eq!(installed_hash(&extension_path), Some(script_hash("// v2")));
+
+    fs::remove_dir_all(extension_path)?;
+    Ok(())
+  }
 
-  if !response.body().is_empty() {
-    let response: String = response.body().deserialize()?;
-    return Ok(response);
+  #[test]
+  fn test_write_extension_error() {
+    let file_path = std::env::temp_dir().join(format!("x-win-not-a-folder-{}", std::process::id()));
+    fs::write(&file_path, "").unwrap();
+    let result = write_extension(&file_path.join("extension"), "// v1");
+    assert!(result.is_err());
+    fs::remove_file(file_path).unwrap();
   }
 
-  Err(
-    String::from(
-      "No result when calling org.gnome.Shell.Extensions.XWinWaylandExtension gnome script!",
-    )
-    .into(),
-  )
-}
+  fn info(state: f64, error: &str) -> HashMap<String, OwnedValue> {
+    let mut info: HashMap<String, OwnedValue> = HashMap::new();
+    info.insert(
+      String::from("state"),
+      OwnedValue::try_from(Value::from(state)).unwrap(),
+    );
+    info.insert(
+      String::from("error"),
+      OwnedValue::try_from(Value::from(error)).unwrap(),
+    );
+    info
+  }
 
-fn call_script_arg(method_name: &str, body: u32) -> Result<String> {
-  let connection = Connection::session()?;
+  #[test]
+  fn test_status_from_info() {
+    assert_eq!(
+      status_from_info(&HashMap::new()),
+      ExtensionStatus::InstalledNeedsRestart
+    );
+    assert_eq!(status_from_info(&info(1.0, "")), ExtensionStatus::Enabled);
+    assert_eq!(status_from_info(&info(2.0, "")), ExtensionStatus::Disabled);
+    assert_eq!(
+      status_from_info(&info(3.0, "SyntaxError: unexpected token")),
+      ExtensionStatus::Error(String::from("SyntaxError: unexpected token"))
+    );
+  }
 
-  let response = connection.call_method(
-    DESTINATION,
-    XWIN_PATH,
-    XWIN_IFACE,
-    method_name,
-    &(body as f64),
-  )?;
+  #[test]
+  fn test_install_extension_with_custom_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    // GNOME Shell version is read on the session bus to generate the script
+    let _session = session_lock();
+    let prefix = std::env::temp_dir().join(format!("x-win-prefix-{}", std::process::id()));
+
+    let options = InstallOptions::new(InstallScope::Custom(prefix.clone()), true);
+    assert!(install_extension_with(&options)?);
+
+    let extension_path = prefix.join("share/gnome-shell/extensions/x-win@miniben90.org");
+    assert!(extension_path.join(EXTENSION_FILE_NAME).is_file());
+    assert!(installed_hash(&extension_path).is_some());
+    assert_eq!(
+      fs::read_to_string(prefix.join("share/glib-2.0/schemas/90_x-win.gschema.override"))?,
+      "[org.gnome.shell]\nenabled-extensions=['x-win@miniben90.org']\n"
+    );
+
+    assert!(uninstall_extension_with(&options)?);
+    assert!(!extension_path.exists());
+    assert!(!prefix
+      .join("share/glib-2.0/schemas/90_x-win.gschema.override")
+      .exists());
+    assert!(!uninstall_extension_with(&options)?);
 
-  if !response.body().is_empty() {
-    let response: String = response.body().deserialize()?;
-    return Ok(response);
+    fs::remove_dir_all(prefix)?;
+    Ok(())
   }
 
-  Err(
-    String::from(
-      "No result when calling org.gnome.Shell.Extensions.XWinWaylandExtension gnome script!",
-    )
-    .into(),
-  )
+  #[test]
+  fn test_string_list() {
+    assert_eq!(parse_string_list("@as []"), Vec::<String>::new());
+    let extensions = parse_string_list("['ding@rastersoft.com', 'ubuntu-dock@ubuntu.com']\n");
+    assert_eq!(
+      extensions,
+      vec![
+        String::from("ding@rastersoft.com"),
+        String::from("ubuntu-dock@ubuntu.com")
+      ]
+    );
+    assert_eq!(
+      format_string_list(&extensions),
+      "['ding@rastersoft.com', 'ubuntu-dock@ubuntu.com']"
+    );
+  }
+
+  #[test]
+  fn test_extension_not_installed() -> Result<(), Box<dyn std::error::Error>> {
+    let _shell = FakeGnomeShell::start()?;
+    assert!(!is_installed_extension()?);
+    assert!(is_enabled_extension().is_err());
+    assert!(!enable_extension()?);
+    assert_eq!(
+      status_from_info(&request_extension_info()?),
+      ExtensionStatus::InstalledNeedsRestart
+    );
+    let err = get_active_window()
+      .err()
+      .ok_or("active window recovered without extension")?;
+    assert!(err.to_string().contains("Unable to reach"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_extension_error_state() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    shell.state().extension = Some((3.0, String::from("SyntaxError: unexpected token")));
+    assert!(is_installed_extension().is_err());
+    let err = is_enabled_extension()
+      .err()
+      .ok_or("extension in error seen as working")?;
+    assert!(err.to_string().contains("does not work correctly"));
+    assert_eq!(
+      status_from_info(&request_extension_info()?),
+      ExtensionStatus::Error(String::from("SyntaxError: unexpected token"))
+    );
+
+    // Extension left by another x-win version
+    shell.export_extension(FakeExtension::new(GNOME_XWIN_SCHEMA_VERSION + 1))?;
+    let err = get_open_windows()
+      .err()
+      .ok_or("incompatible extension accepted")?;
+    assert!(matches!(
+      err.downcast_ref::<XWinError>(),
+      Some(XWinError::IncompatibleExtension { .. })
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_extension_enabled() -> Result<(), Box<dyn std::error::Error>> {
+    let shell = FakeGnomeShell::start()?;
+    shell.state().extension = Some((2.0, String::new()));
+    assert!(is_installed_extension()?);
+    assert!(!is_enabled_extension()?);
+
+    assert!(enable_extension()?);
+    assert!(is_enabled_extension()?);
+    shell.export_extension(FakeExtension::new(GNOME_XWIN_SCHEMA_VERSION))?;
+    let active_window = get_active_window()?;
+    assert_eq!(active_window.id, 1);
+    assert_eq!(active_window.info.exec_name, "code");
+    assert_eq!(get_open_windows()?.len(), 2);
+    let icon = get_icon(&active_window, &IconOptions::default())?;
+    assert_eq!(icon.data, vec![1, 2, 3]);
+
+    assert!(disable_extension()?);
+    assert!(!is_enabled_extension()?);
+    Ok(())
+  }
 }
@@ -1,177 +1,219 @@
 #![deny(unused_imports)]
 
-use base64::Engine;
-use image::ImageFormat;
+use std::{sync::Mutex, time::Duration};
 
 use xcb::{x, Connection, Xid, XidNew};
 
 use crate::{
   common::{
-    api::{empty_icon, Api},
+    api::{empty_icon_data, Api},
+    icon::encode_icon,
     result::Result,
-    x_win_struct::{icon_info::IconInfo, window_info::WindowInfo, window_position::WindowPosition},
+    x_win_struct::{
+      browser_info::BrowserInfo,
+      capabilities::{Backend, Capabilities},
+      icon_data::IconData,
+      icon_info::IconInfo,
+      icon_options::IconOptions,
+      window_info::WindowInfo,
+      window_position::WindowPosition,
+    },
   },
   empty_entity,
   linux::api::common_api::{get_window_memory_usage, get_window_path_name},
 };
 
-use super::common_api::init_entity;
+use super::{common_api::init_entity, idle_api::x11_idle_time};
 
 /**
  * Struct to use similar as API to get active window and open windows for XOrg desktop
  */
-pub struct X11Api {}
+#[derive(Default)]
+pub struct X11Api {
+  /**
+   * Connection opened on first use and kept while the X server is reachable
+   */
+  conn: Mutex<Option<Connection>>,
+}
+
+impl X11Api {
+  pub fn new() -> Self {
+    Self {
+      conn: Mutex::new(None),
+    }
+  }
+
+  fn with_connection<T>(&self, call: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    let mut conn = self.conn.lock().map_err(|err| err.to_string())?;
+    if let Some(current) = conn.as_ref().filter(|current| current.has_error().is_ok()) {
+      return call(current);
+    }
+    let current = connection()?;
+    let response = call(&current);
+    *conn = Some(current);
+    response
+  }
+}
 
 /**
  * Impl. for windows system
  */
 impl Api for X11Api {
   fn get_active_window(&self) -> Result<WindowInfo> {
-    let conn = connection()?;
-    let setup = conn.get_setup();
-
-    match setup.roots().next() {
-      Some(screen) => {
-        let active_window_atom = get_active_window_atom(&conn);
-        if active_window_atom != x::ATOM_NONE {
-          let active_windows = conn.send_request(&x::GetProperty {
-            delete: false,
-            window: screen.root(),
-            property: active_window_atom,
-            r#type: x::ATOM_WINDOW,
-            long_offset: 0,
-            long_length: 1,
-          });
-          if let Ok(active_windows) = conn.wait_for_reply(active_windows) {
-            return match active_windows.value::<x::Window>().first() {
-              Some(active_window) => {
-                let active_window = get_window_information(&conn, active_window)?;
-                Ok(active_window)
-              }
-              None => Ok(empty_entity()),
-            };
-          }
-        }
-        Err(
-          String::from(
-            "Something got wrong, not possible to get active window calling _NET_ACTIVE_WINDOW",
-          )
-          .into(),
-        )
-      }
-      None => {
-        Err(String::from("Something got wrong, not possible to get access of X Server!").into())
-      }
-    }
+    self.with_connection(active_window)
   }
 
   fn get_open_windows(&self) -> Result<Vec<WindowInfo>> {
-    let conn = connection()?;
-    let setup = conn.get_setup();
-
-    match setup.roots().next() {
-      Some(screen) => {
-        let open_windows_atom = get_client_list_stacking_atom(&conn);
-
-        if open_windows_atom != x::ATOM_NONE {
-          let window_list = conn.send_request(&x::GetProperty {
-            delete: false,
-            window: screen.root(),
-            property: open_windows_atom,
-            r#type: x::ATOM_WINDOW,
-            long_offset: 0,
-            long_length: u32::MAX,
-          });
-
-          if let Ok(windows_reply) = conn.wait_for_reply(window_list) {
-            let window_list: Vec<x::Window> = windows_reply.value::<x::Window>().to_vec();
-            let mut results: Vec<WindowInfo> = Vec::new();
-
-            if window_list.len().ne(&0) {
-              for window in window_list {
-                let window: &x::Window = &window;
-                if let Ok(result) = get_window_information(&conn, window) {
-                  if result.id.ne(&0) && is_normal_window(&conn, *window) {
-                    results.push(result);
-                  }
-                }
-              }
-            }
+    self.with_connection(open_windows)
+  }
 
-            return Ok(results);
-          }
+  fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
+    self.get_app_icon_with(window_info, &IconOptions::default())
+  }
+
+  fn get_app_icon_data(&self, window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
+    self.with_connection(|conn| icon_data(conn, window_info, options))
+  }
+
+  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+    super::atspi_api::get_browser_url(window_info)
+  }
+
+  fn capabilities(&self) -> Capabilities {
+    // Icons are read from `_NET_WM_ICON`, windows without it have an empty icon
+    Capabilities {
+      browser_url: super::atspi_api::is_available(),
+      ..Capabilities::with_windows(Backend::X11)
+    }
+  }
+
+  fn get_idle_time(&self) -> Result<Duration> {
+    self.with_connection(x11_idle_time)
+  }
+}
+
+/**
+ * Get the active window with an opened connection
+ */
+pub fn active_window(conn: &Connection) -> Result<WindowInfo> {
+  let setup = conn.get_setup();
+
+  match setup.roots().next() {
+    Some(screen) => {
+      let active_window_atom = get_active_window_atom(conn);
+      if active_window_atom != x::ATOM_NONE {
+        let active_windows = conn.send_request(&x::GetProperty {
+          delete: false,
+          window: screen.root(),
+          property: active_window_atom,
+          r#type: x::ATOM_WINDOW,
+          long_offset: 0,
+          long_length: 1,
+        });
+        if let Ok(active_windows) = conn.wait_for_reply(active_windows) {
+          return match active_windows.value::<x::Window>().first() {
+            Some(active_window) => {
+              let active_window = get_window_information(conn, active_window)?;
+              Ok(active_window)
+            }
+            None => Ok(empty_entity()),
+          };
         }
-        Err(String::from("Something got wrong, not possible to get active window calling _NET_CLIENT_LIST_STACKING").into())
-      }
-      None => {
-        Err(String::from("Something got wrong, not possible to get access of X Server!").into())
       }
+      Err(
+        String::from(
+          "Something got wrong, not possible to get active window calling _NET_ACTIVE_WINDOW",
+        )
+        .into(),
+      )
+    }
+    None => {
+      Err(String::from("Something got wrong, not possible to get access of X Server!").into())
     }
   }
+}
 
-  fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
-    let conn = connection()?;
-    let setup = conn.get_setup();
-
-    let root_window = setup.roots().next();
-    if root_window.is_some() {
-      let window = XidNew::new(window_info.id);
-      let icon_atom = get_window_icon_atom(&conn);
-      if icon_atom != x::ATOM_NONE {
-        let icon_cookie = conn.send_request(&x::GetProperty {
+/**
+ * Get open windows with an opened connection
+ */
+fn open_windows(conn: &Connection) -> Result<Vec<WindowInfo>> {
+  let setup = conn.get_setup();
+
+  match setup.roots().next() {
+    Some(screen) => {
+      let open_windows_atom = get_client_list_stacking_atom(conn);
+
+      if open_windows_atom != x::ATOM_NONE {
+        let window_list = conn.send_request(&x::GetProperty {
           delete: false,
-          window,
-          property: icon_atom,
-          r#type: x::ATOM_CARDINAL,
+          window: screen.root(),
+          property: open_windows_atom,
+          r#type: x::ATOM_WINDOW,
           long_offset: 0,
           long_length: u32::MAX,
         });
-        if let Ok(icon_reply) = conn.wait_for_reply(icon_cookie) {
-          let icon_data: &[u32] = icon_reply.value::<u32>();
-          if !icon_data.is_empty() {
-            let width = icon_data[0] as usize;
-            let height = icon_data[1] as usize;
-            let bgra_data: &[u32] = &icon_data[2..2 + (width * height)];
-
-            let mut buffer: Vec<u8> = vec![0u8; height * width * 4];
-
-            for (i, &bgra) in bgra_data.iter().enumerate() {
-              let b = (bgra & 0xFF) as u8;
-              let g = ((bgra >> 8) & 0xFF) as u8;
-              let r = ((bgra >> 16) & 0xFF) as u8;
-              let a = ((bgra >> 24) & 0xFF) as u8;
-              let index = i * 4;
-              buffer[index] = r;
-              buffer[index + 1] = g;
-              buffer[index + 2] = b;
-              buffer[index + 3] = a;
-            }
-            let mut png_data: Vec<u8> = Vec::new();
-            {
-              if let Some(buffer) = image::RgbaImage::from_raw(width as u32, height as u32, buffer)
-              {
-                let _ = buffer.write_to(&mut std::io::Cursor::new(&mut png_data), ImageFormat::Png);
-                let data = base64::prelude::BASE64_STANDARD.encode(png_data);
-                return Ok(IconInfo {
-                  data: format!("data:image/png;base64,{data}").to_owned(),
-                  height: height as u32,
-                  width: width as u32,
-                });
+
+        if let Ok(windows_reply) = conn.wait_for_reply(window_list) {
+          let window_list: Vec<x::Window> = windows_reply.value::<x::Window>().to_vec();
+          let mut results: Vec<WindowInfo> = Vec::new();
+
+          if window_list.len().ne(&0) {
+            for window in window_list {
+              let window: &x::Window = &window;
+              if let Ok(result) = get_window_information(conn, window) {
+                if result.id.ne(&0) && is_normal_window(conn, *window) {
+                  results.push(result);
+                }
               }
             }
           }
+
+          return Ok(results);
         }
       }
+      Err(String::from("Something got wrong, not possible to get active window calling _NET_CLIENT_LIST_STACKING").into())
+    }
+    None => {
+      Err(String::from("Something got wrong, not possible to get access of X Server!").into())
     }
-    Ok(empty_icon())
   }
+}
 
-  fn get_browser_url(&self, _: &WindowInfo) -> Result<String> {
-    Ok(super::common_api::get_browser_url())
+/**
+ * Get icon from `_NET_WM_ICON` with an opened connection
+ */
+fn icon_data(
+  conn: &Connection,
+  window_info: &WindowInfo,
+  options: &IconOptions,
+) -> Result<IconData> {
+  let setup = conn.get_setup();
+
+  let root_window = setup.roots().next();
+  if root_window.is_some() {
+    let window = XidNew::new(window_info.id);
+    let icon_atom = get_window_icon_atom(conn);
+    if icon_atom != x::ATOM_NONE {
+      let icon_cookie = conn.send_request(&x::GetProperty {
+        delete: false,
+        window,
+        property: icon_atom,
+        r#type: x::ATOM_CARDINAL,
+        long_offset: 0,
+        long_length: u32::MAX,
+      });
+      if let Ok(icon_reply) = conn.wait_for_reply(icon_cookie) {
+        let icons = parse_net_wm_icons(icon_reply.value::<u32>());
+        if let Some((width, height, argb_data)) = select_icon(&icons, options.preferred_size) {
+          return encode_icon(argb_to_rgba(argb_data), width, height, options.format);
+        }
+      }
+    }
   }
+  Ok(empty_icon_data())
 }
 
-fn connection() -> Result<Connection> {
+pub fn connection() -> Result<Connection> {
   let (conn, _) = xcb::Connection::connect(None)?;
   Ok(conn)
 }
@@ -317,7 +359,7 @@ fn get_window_pid_atom(conn: &xcb::Connection) -> x::Atom {
 /**
  * Generate Atom of _NET_ACTIVE_WINDOW value
  */
-fn get_active_window_atom(conn: &xcb::Connection) -> x::Atom {
+pub fn get_active_window_atom(conn: &xcb::Connection) -> x::Atom {
   get_atom(conn, b"_NET_ACTIVE_WINDOW", true)
 }
 
@@ -363,10 +405,65 @@ fn get_window_icon_atom(conn: &xcb::Connection) -> x::Atom {
   get_atom(conn, b"_NET_WM_ICON", false)
 }
 
+/**
+ * Split `_NET_WM_ICON` value into `(width, height, pixels)` entries.
+ * The property holds images back to back, parsing stop at the first malformed entry.
+ */
+fn parse_net_wm_icons(icon_data: &[u32]) -> Vec<(u32, u32, &[u32])> {
+  let mut icons: Vec<(u32, u32, &[u32])> = Vec::new();
+  let mut offset: usize = 0;
+  while offset + 2 <= icon_data.len() {
+    let width = icon_data[offset];
+    let height = icon_data[offset + 1];
+    let end = (width as usize)
+      .checked_mul(height as usize)
+      .and_then(|size| size.checked_add(offset + 2));
+    match end {
+      Some(end) if width > 0 && height > 0 && end <= icon_data.len() => {
+        icons.push((width, height, &icon_data[offset + 2..end]));
+        offset = end;
+      }
+      _ => break,
+    }
+  }
+  icons
+}
+
+/**
+ * Pick the smallest icon bigger or equal to `preferred_size`, otherwise the biggest one
+ */
+fn select_icon<'a>(
+  icons: &[(u32, u32, &'a [u32])],
+  preferred_size: u32,
+) -> Option<(u32, u32, &'a [u32])> {
+  let size = |icon: &(u32, u32, &[u32])| icon.0.max(icon.1);
+  icons
+    .iter()
+    .filter(|icon| size(icon) >= preferred_size)
+    .min_by_key(|icon| size(icon))
+    .or_else(|| icons.iter().max_by_key(|icon| size(icon)))
+    .copied()
+}
+
+/**
+ * Convert ARGB pixels of `_NET_WM_ICON` to a RGBA buffer
+ */
+fn argb_to_rgba(argb_data: &[u32]) -> Vec<u8> {
+  let mut buffer: Vec<u8> = Vec::with_capacity(argb_data.len() * 4);
+  for &argb in argb_data {
+    let b = (argb & 0xFF) as u8;
+    let g = ((argb >> 8) & 0xFF) as u8;
+    let r = ((argb >> 16) & 0xFF) as u8;
+    let a = ((argb >> 24) & 0xFF) as u8;
+    buffer.extend_from_slice(&[r, g, b, a]);
+  }
+  buffer
+}
+
 /**
  * Generate Atom of name parameter
  */
-fn get_atom(conn: &xcb::Connection, name: &[u8], only_if_exists: bool) -> x::Atom {
+pub fn get_atom(conn: &xcb::Connection, name: &[u8], only_if_exists: bool) -> x::Atom {
   let atom_name = conn.send_request(&x::InternAtom {
     only_if_exists,
     name,
@@ -421,3 +518,196 @@ fn is_full_screen_window(conn: &xcb::Connection, window: x::Window) -> bool {
   }
   false
 }
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+
+  use xcb::{x, Xid};
+
+  use super::{argb_to_rgba, parse_net_wm_icons, select_icon, X11Api};
+  use crate::{
+    common::{
+      api::Api,
+      x_win_struct::icon_options::{IconFormat, IconOptions},
+    },
+    linux::api::test_x11::{FakeWindowManager, TestWindow, Xvfb},
+  };
+
+  /**
+   * Api connected to the display of `xvfb` instead of `DISPLAY`
+   */
+  fn x11_api(xvfb: &Xvfb) -> Result<X11Api, Box<dyn std::error::Error>> {
+    Ok(X11Api {
+      conn: Mutex::new(Some(xvfb.connect()?)),
+    })
+  }
+
+  #[test]
+  fn test_parse_net_wm_icons() {
+    let icon_data: Vec<u32> = vec![1, 1, 0xFF000000, 2, 1, 0, 0, 3, 3, 0];
+    let icons = parse_net_wm_icons(&icon_data);
+    assert_eq!(icons.len(), 2);
+    assert_eq!((icons[0].0, icons[0].1, icons[0].2.len()), (1, 1, 1));
+    assert_eq!((icons[1].0, icons[1].1, icons[1].2.len()), (2, 1, 2));
+
+    assert!(parse_net_wm_icons(&[]).is_empty());
+    assert!(parse_net_wm_icons(&[16]).is_empty());
+    assert!(parse_net_wm_icons(&[16, 16, 0, 0]).is_empty());
+    assert!(parse_net_wm_icons(&[u32::MAX, u32::MAX, 0]).is_empty());
+  }
+
+  #[test]
+  fn test_select_icon() {
+    let small = vec![0u32; 16 * 16];
+    let medium = vec![0u32; 32 * 32];
+    let large = vec![0u32; 128 * 128];
+    let icons: Vec<(u32, u32, &[u32])> =
+      vec![(16, 16, &small), (128, 128, &large), (32, 32, &medium)];
+
+    assert_eq!(select_icon(&icons, 24).map(|icon| icon.0), Some(32));
+    assert_eq!(select_icon(&icons, 32).map(|icon| icon.0), Some(32));
+    assert_eq!(select_icon(&icons, 256).map(|icon| icon.0), Some(128));
+    assert_eq!(select_icon(&icons, 0).map(|icon| icon.0), Some(16));
+    assert!(select_icon(&[], 32).is_none());
+  }
+
+  #[test]
+  fn test_argb_to_rgba() {
+    assert_eq!(argb_to_rgba(&[0x80112233]), vec![0x11, 0x22, 0x33, 0x80]);
+  }
+
+  #[test]
+  #[ignore = "Requires Xvfb, run with `cargo test -- --ignored`"]
+  fn test_xvfb_active_window() -> Result<(), Box<dyn std::error::Error>> {
+    let xvfb = Xvfb::start()?;
+    let mut wm = FakeWindowManager::start(&xvfb)?;
+    let api = x11_api(&xvfb)?;
+    assert_eq!(api.get_active_window()?.id, 0);
+
+    let terminal = wm.open(&TestWindow::new("Terminal", "Gnome-terminal"))?;
+    let code = wm.open(&TestWindow {
+      x: 100,
+      y: 50,
+      width: 800,
+      height: 600,
+      full_screen: true,
+      ..TestWindow::new("x-win - Visual Studio Code", "Code")
+    })?;
+    wm.focus(code)?;
+
+    let active_window = api.get_active_window()?;
+    assert_eq!(active_window.id, code.resource_id());
+    assert_eq!(active_window.title, "x-win - Visual Studio Code");
+    assert_eq!(active_window.info.name, "Code");
+    assert_eq!(active_window.info.process_id, std::process::id());
+    let exe = std::env::current_exe()?;
+    assert_eq!(
+      Some(active_window.info.exec_name.as_str()),
+      exe.file_name().and_then(|name| name.to_str())
+    );
+    assert!(active_window.usage.memory > 0);
+    assert_eq!(
+      (
+        active_window.position.x,
+        active_window.position.y,
+        active_window.position.width,
+        active_window.position.height
+      ),
+      (100, 50, 800, 600)
+    );
+    assert!(active_window.position.is_full_screen);
+
+    wm.focus(terminal)?;
+    wm.set_title(terminal, "vim README.md")?;
+    let active_window = api.get_active_window()?;
+    assert_eq!(active_window.id, terminal.resource_id());
+    assert_eq!(active_window.title, "vim README.md");
+    assert!(!active_window.position.is_full_screen);
+
+    wm.focus(x::Window::none())?;
+    assert_eq!(api.get_active_window()?.id, 0);
+    Ok(())
+  }
+
+  #[test]
+  #[ignore = "Requires Xvfb, run with `cargo test -- --ignored`"]
+  fn test_xvfb_open_windows() -> Result<(), Box<dyn std::error::Error>> {
+    let xvfb = Xvfb::start()?;
+    let mut wm = FakeWindowManager::start(&xvfb)?;
+    let api = x11_api(&xvfb)?;
+    assert!(api.get_open_windows()?.is_empty());
+
+    let terminal = wm.open(&TestWindow::new("Terminal", "Gnome-terminal"))?;
+    wm.open(&TestWindow {
+      normal: false,
+      ..TestWindow::new("Save as", "Code")
+    })?;
+    let code = wm.open(&TestWindow::new("x-win - Visual Studio Code", "Code"))?;
+
+    // Dialogs are not listed, windows are in stacking order
+    let open_windows = api.get_open_windows()?;
+    let ids: Vec<u32> = open_windows.iter().map(|window| window.id).collect();
+    assert_eq!(ids, vec![terminal.resource_id(), code.resource_id()]);
+    assert_eq!(open_windows[0].title, "Terminal");
+    assert_eq!(open_windows[1].info.name, "Code");
+
+    wm.close(terminal)?;
+    let open_windows = api.get_open_windows()?;
+    assert_eq!(open_windows.len(), 1);
+    assert_eq!(open_windows[0].id, code.resource_id());
+    Ok(())
+  }
+
+  #[test]
+  #[ignore = "Requires Xvfb, run with `cargo test -- --ignored`"]
+  fn test_xvfb_window_icon() -> Result<(), Box<dyn std::error::Error>> {
+    let xvfb = Xvfb::start()?;
+    let mut wm = FakeWindowManager::start(&xvfb)?;
+    let api = x11_api(&xvfb)?;
+
+    let mut icon = vec![16, 16];
+    icon.extend(vec![0xFF0000FFu32; 16 * 16]);
+    icon.extend([32, 32]);
+    icon.extend(vec![0x80112233u32; 32 * 32]);
+    let with_icon = wm.open(&TestWindow {
+      icon,
+      ..TestWindow::new("Terminal", "Gnome-terminal")
+    })?;
+    let without_icon = wm.open(&TestWindow::new("xterm", "XTerm"))?;
+    let open_windows = api.get_open_windows()?;
+    let window = open_windows
+      .iter()
+      .find(|window| window.id.eq(&with_icon.resource_id()))
+      .ok_or("window with icon not listed")?;
+
+    let raw = api.get_app_icon_data(
+      window,
+      &IconOptions {
+        preferred_size: 32,
+        format: IconFormat::RawRgba,
+        ..IconOptions::default()
+      },
+    )?;
+    assert_eq!((raw.width, raw.height), (32, 32));
+    assert_eq!(raw.mime, IconFormat::RawRgba.mime());
+    assert_eq!(&raw.data[0..4], &[0x11, 0x22, 0x33, 0x80]);
+
+    let small = api.get_app_icon_with(
+      window,
+      &IconOptions {
+        preferred_size: 16,
+        ..IconOptions::default()
+      },
+    )?;
+    assert_eq!((small.width, small.height), (16, 16));
+    assert!(!small.data.is_empty());
+
+    let window = open_windows
+      .iter()
+      .find(|window| window.id.eq(&without_icon.resource_id()))
+      .ok_or("window without icon not listed")?;
+    assert!(api.get_app_icon(window)?.data.is_empty());
+    Ok(())
+  }
+}
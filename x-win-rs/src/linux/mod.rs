@@ -1,22 +1,36 @@
 #![deny(unused_imports)]
 
 mod api;
-use crate::common::{api::Api, result::Result};
+use crate::common::{
+  api::Api,
+  result::Result,
+  x_win_struct::{
+    capabilities::Backend, diagnostic_report::DiagnosticReport, extension_status::ExtensionStatus,
+    install_options::InstallOptions, sandbox_info::SandboxInfo, session_state::SessionState,
+  },
+};
+
+#[cfg(feature = "async")]
+use crate::common::x_win_struct::window_info::WindowInfo;
 
 use api::LinuxAPI;
 
 use self::api::APIGnome;
 
-pub fn init_platform_api() -> impl Api {
-  LinuxAPI {}
+pub fn init_platform_api() -> Box<dyn Api + Send + Sync> {
+  Box::new(LinuxAPI::new())
+}
+
+pub fn init_platform_api_for(backend: &Backend) -> Result<Box<dyn Api + Send + Sync>> {
+  api::api_for(backend)
 }
 
-pub fn gnome_install_extension() -> Result<bool> {
-  LinuxAPI::install_extension()
+pub fn gnome_install_extension_with(options: &InstallOptions) -> Result<bool> {
+  LinuxAPI::install_extension_with(options)
 }
 
-pub fn gnome_uninstall_extension() -> Result<bool> {
-  LinuxAPI::uninstall_extension()
+pub fn gnome_uninstall_extension_with(options: &InstallOptions) -> Result<bool> {
+  LinuxAPI::uninstall_extension_with(options)
 }
 
 pub fn gnome_enable_extension() -> Result<bool> {
@@ -34,3 +48,38 @@ pub fn gnome_is_enabled_extension() -> Result<bool> {
 pub fn gnome_is_installed_extension() -> Result<bool> {
   LinuxAPI::is_installed_extension()
 }
+
+pub fn gnome_extension_status() -> Result<ExtensionStatus> {
+  LinuxAPI::extension_status()
+}
+
+pub fn diagnose() -> DiagnosticReport {
+  api::diagnose()
+}
+
+pub fn sandbox_info() -> SandboxInfo {
+  api::sandbox_info()
+}
+
+pub fn session_state() -> Result<SessionState> {
+  api::session_state()
+}
+
+pub fn watch_session_state() -> Result<std::sync::mpsc::Receiver<SessionState>> {
+  api::watch_session_state()
+}
+
+#[cfg(feature = "async")]
+pub async fn get_active_window_async() -> crate::r#async::Result<WindowInfo> {
+  api::get_active_window().await
+}
+
+#[cfg(feature = "async")]
+pub async fn get_open_windows_async() -> crate::r#async::Result<Vec<WindowInfo>> {
+  api::get_open_windows().await
+}
+
+#[cfg(feature = "async")]
+pub fn watch_active_window(interval: std::time::Duration) -> crate::r#async::WindowStream {
+  api::watch_active_window(interval)
+}
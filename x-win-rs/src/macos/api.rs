@@ -3,18 +3,26 @@
 use std::ffi::c_void;
 use std::process::Command;
 use std::ptr::null_mut;
+use std::time::Duration;
 
 use crate::common::x_win_struct::icon_info::IconInfo;
 use crate::common::{
-  api::{empty_entity, empty_icon, os_name, Api},
+  api::{empty_entity, empty_icon_data, os_name, Api},
+  browser::browser_info,
+  error::XWinError,
+  icon::convert_icon_data,
   result::Result,
   x_win_struct::{
-    process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+    browser_info::{BrowserInfo, BrowserKind},
+    capabilities::{Backend, Capabilities},
+    icon_data::IconData,
+    icon_options::IconOptions,
+    process_info::ProcessInfo,
+    usage_info::UsageInfo,
+    window_info::WindowInfo,
     window_position::WindowPosition,
   },
 };
-use base64::prelude::BASE64_STANDARD;
-use base64::Engine;
 use objc2::rc::{autoreleasepool, Retained};
 use objc2::{AllocAnyThread, Encode, RefEncode};
 use objc2_app_kit::{
@@ -25,8 +33,8 @@ use objc2_core_foundation::{
   CGSize,
 };
 use objc2_core_graphics::{
-  kCGNullWindowID, CGRectMakeWithDictionaryRepresentation, CGWindowListCopyWindowInfo,
-  CGWindowListOption,
+  kCGNullWindowID, CGEventSource, CGEventSourceStateID, CGEventType,
+  CGRectMakeWithDictionaryRepresentation, CGWindowListCopyWindowInfo, CGWindowListOption,
 };
 use objc2_foundation::{MainThreadMarker, NSDictionary, NSObject, NSRect, NSString};
 
@@ -68,15 +76,45 @@ impl Api for MacosAPI {
   }
 
   fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
-    autoreleasepool(|_pool| get_app_icon(window_info))
+    Ok(
+      self
+        .get_app_icon_data(window_info, &IconOptions::default())?
+        .to_icon_info(true),
+    )
   }
 
-  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<String> {
-    autoreleasepool(|_pool| get_browser_url(window_info.info.process_id))
+  fn get_app_icon_data(&self, window_info: &WindowInfo, options: &IconOptions) -> Result<IconData> {
+    let icon_data = autoreleasepool(|_pool| get_app_icon_data(window_info))?;
+    convert_icon_data(icon_data, options.format)
+  }
+
+  fn get_browser_url(&self, window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+    autoreleasepool(|_pool| get_browser_url(window_info))
+  }
+
+  fn capabilities(&self) -> Capabilities {
+    // Firefox does not expose its url and returns `XWinError::Unsupported`
+    Capabilities {
+      browser_url: true,
+      ..Capabilities::with_windows(Backend::MacOS)
+    }
+  }
+
+  fn get_idle_time(&self) -> Result<Duration> {
+    // `kCGAnyInputEventType`
+    let any_input = CGEventType(!0);
+    let seconds = CGEventSource::seconds_since_last_event_type(
+      CGEventSourceStateID::CombinedSessionState,
+      any_input,
+    );
+    Ok(Duration::from_secs_f64(seconds.max(0.0)))
   }
 }
 
-fn get_app_icon(window_info: &WindowInfo) -> Result<IconInfo> {
+/**
+ * Return the PNG icon of the application at window_info.info.path
+ */
+fn get_app_icon_data(window_info: &WindowInfo) -> Result<IconData> {
   if !window_info.info.path.is_empty() {
     let path: &NSString = &NSString::from_str(&window_info.info.path);
 
@@ -98,21 +136,20 @@ fn get_app_icon(window_info: &WindowInfo) -> Result<IconInfo> {
       match pngdata {
         Some(pngdata) => {
           let bytes = unsafe { pngdata.as_bytes_unchecked() };
-          let data = BASE64_STANDARD.encode(bytes);
-          let icon = IconInfo {
-            data: format!("data:image/png;base64,{data}"),
-            width: imagesize.width as u32,
-            height: imagesize.height as u32,
-          };
-          return Ok(icon);
+          return Ok(IconData::new(
+            bytes.to_vec(),
+            String::from("image/png"),
+            imagesize.height as u32,
+            imagesize.width as u32,
+          ));
         }
         None => {
-          return Ok(empty_icon());
+          return Ok(empty_icon_data());
         }
       }
     }
   }
-  Ok(empty_icon())
+  Ok(empty_icon_data())
 }
 
 fn get_windows_informations(only_active: bool) -> Result<Vec<WindowInfo>> {
@@ -295,13 +332,12 @@ fn is_from_document(bundle_id: &str) -> bool {
   )
 }
 
-// fn is_firefox_browser(bundle_id: &str) -> bool {
-//   match bundle_id {
-//     | "org.mozilla.firefox"
-//     | "org.mozilla.firefoxdeveloperedition" => true,
-//     _ => false,
-//   }
-// }
+fn is_firefox_browser(bundle_id: &str) -> bool {
+  matches!(
+    bundle_id,
+    "org.mozilla.firefox" | "org.mozilla.firefoxdeveloperedition"
+  )
+}
 
 fn execute_applescript(script: &str) -> String {
   let output = Command::new("osascript").args(["-e", script]).output();
@@ -328,27 +364,35 @@ fn is_full_screen(window_rect: CGRect, screen_rect: NSRect) -> bool {
 }
 
 // Recover browser url using process id to get it
-fn get_browser_url(process_id: u32) -> Result<String> {
-  let app = get_running_application_from_pid(process_id);
+fn get_browser_url(window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+  let app = get_running_application_from_pid(window_info.info.process_id);
 
   match app {
     Ok(app) => {
       let bundle_identifier = get_bundle_identifier(app);
       if bundle_identifier.is_empty() || !is_browser_bundle_id(&bundle_identifier) {
-        return Ok(String::from(""));
+        return Ok(None);
+      }
+      if is_firefox_browser(&bundle_identifier) {
+        return Err(
+          XWinError::Unsupported(format!(
+            "{bundle_identifier} does not expose its url to AppleScript"
+          ))
+          .into(),
+        );
       }
       let mut command =
         format!("tell app id \"{bundle_identifier}\" to get URL of active tab of front window");
       if is_from_document(&bundle_identifier) {
         command = format!("tell app id \"{bundle_identifier}\" to get URL of front document");
       }
-      // else if is_firefox_browser(&bundle_identifier)
-      // {
-      //   command = format!("tell app id \"{}\" to get URL of active tab of front window", bundle_identifier);
-      // }
-      Ok(execute_applescript(&command))
+      Ok(browser_info(
+        window_info,
+        BrowserKind::from_bundle_id(&bundle_identifier),
+        execute_applescript(&command),
+      ))
     }
-    Err(_) => Ok(String::from("")),
+    Err(_) => Ok(None),
   }
 }
 
@@ -3,9 +3,20 @@
 mod api;
 pub mod permission;
 
-use crate::common::api::Api;
+use crate::common::{
+  api::Api, error::XWinError, result::Result, x_win_struct::capabilities::Backend,
+};
 use api::MacosAPI;
 
-pub fn init_platform_api() -> impl Api {
-  MacosAPI {}
+pub fn init_platform_api() -> Box<dyn Api + Send + Sync> {
+  Box::new(MacosAPI {})
+}
+
+pub fn init_platform_api_for(backend: &Backend) -> Result<Box<dyn Api + Send + Sync>> {
+  match backend {
+    Backend::MacOS => Ok(init_platform_api()),
+    backend => {
+      Err(XWinError::Unsupported(format!("{backend:?} backend is not available on macOS")).into())
+    }
+  }
 }
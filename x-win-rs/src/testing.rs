@@ -0,0 +1,412 @@
+#![deny(unused_imports)]
+
+use std::{
+  fs,
+  io::{BufRead, BufReader, Write},
+  path::Path,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use crate::common::{
+  api::{empty_entity, empty_icon_data, Api},
+  result::Result,
+  x_win_struct::{
+    browser_info::BrowserInfo,
+    capabilities::{Backend, Capabilities},
+    icon_data::IconData,
+    icon_info::IconInfo,
+    icon_options::IconOptions,
+    window_info::WindowInfo,
+  },
+};
+
+/**
+ * Environment variable with the path of a timeline file replayed by the default instance and `Backend::Fake`
+ */
+pub const FAKE_TIMELINE_ENV: &str = "X_WIN_FAKE_TIMELINE";
+
+/**
+ * State of the desktop at one step of a `Timeline`
+ */
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+  /**
+   * Time since the start of the timeline in milliseconds, used when replaying in real time
+   */
+  pub elapsed: u64,
+  pub active_window: Option<WindowInfo>,
+  pub open_windows: Vec<WindowInfo>,
+}
+
+/**
+ * Steps of window states played by `FakeApi`, each event adds a step
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Timeline {
+  snapshots: Vec<Snapshot>,
+  elapsed: u64,
+}
+
+impl Timeline {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /**
+   * Open `window` on top of other windows, the focus does not change
+   */
+  pub fn open(self, window: WindowInfo) -> Self {
+    self.step(|snapshot| snapshot.open_windows.push(window))
+  }
+
+  /**
+   * Close the window with `id`, no window is active anymore if it had the focus
+   */
+  pub fn close(self, id: u32) -> Self {
+    self.step(|snapshot| {
+      snapshot.open_windows.retain(|window| window.id.ne(&id));
+      if is_active(snapshot, id) {
+        snapshot.active_window = None;
+      }
+    })
+  }
+
+  /**
+   * Give the focus to the open window with `id`
+   */
+  pub fn focus(self, id: u32) -> Self {
+    self.step(|snapshot| {
+      snapshot.active_window = snapshot
+        .open_windows
+        .iter()
+        .find(|window| window.id.eq(&id))
+        .cloned();
+    })
+  }
+
+  /**
+   * Change the title of the window with `id`
+   */
+  pub fn set_title(self, id: u32, title: &str) -> Self {
+    self.step(|snapshot| {
+      for window in snapshot
+        .open_windows
+        .iter_mut()
+        .chain(snapshot.active_window.iter_mut())
+        .filter(|window| window.id.eq(&id))
+      {
+        window.title = title.to_owned();
+      }
+    })
+  }
+
+  /**
+   * Wait `duration` before the next step when the timeline is replayed in real time
+   */
+  pub fn wait(mut self, duration: Duration) -> Self {
+    self.elapsed += duration.as_millis() as u64;
+    self
+  }
+
+  /**
+   * Add a step with a full state of the desktop
+   */
+  pub fn snapshot(mut self, mut snapshot: Snapshot) -> Self {
+    snapshot.elapsed = self.elapsed;
+    self.snapshots.push(snapshot);
+    self
+  }
+
+  pub fn snapshots(&self) -> &[Snapshot] {
+    &self.snapshots
+  }
+
+  /**
+   * Read a timeline written by `save`, one JSON snapshot per line
+   */
+  pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+    let mut snapshots = Vec::new();
+    for line in BufReader::new(fs::File::open(path)?).lines() {
+      let line = line?;
+      if !line.trim().is_empty() {
+        snapshots.push(serde_json::from_str::<Snapshot>(&line)?);
+      }
+    }
+    let elapsed = snapshots
+      .last()
+      .map(|snapshot| snapshot.elapsed)
+      .unwrap_or(0);
+    Ok(Self { snapshots, elapsed })
+  }
+
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+    let mut file = fs::File::create(path)?;
+    for snapshot in &self.snapshots {
+      writeln!(file, "{}", serde_json::to_string(snapshot)?)?;
+    }
+    Ok(())
+  }
+
+  fn step(self, change: impl FnOnce(&mut Snapshot)) -> Self {
+    let mut snapshot = self.snapshots.last().cloned().unwrap_or_default();
+    change(&mut snapshot);
+    self.snapshot(snapshot)
+  }
+}
+
+fn is_active(snapshot: &Snapshot, id: u32) -> bool {
+  snapshot
+    .active_window
+    .as_ref()
+    .map(|window| window.id.eq(&id))
+    .unwrap_or(false)
+}
+
+/**
+ * Capture `samples` snapshots of the real desktop, one every `interval`
+ */
+pub fn record(samples: usize, interval: Duration) -> Result<Timeline> {
+  let mut timeline = Timeline::new();
+  for sample in 0..samples {
+    if sample.ne(&0) {
+      std::thread::sleep(interval);
+      timeline = timeline.wait(interval);
+    }
+    let active_window = crate::get_active_window()?;
+    timeline = timeline.snapshot(Snapshot {
+      elapsed: 0,
+      active_window: Some(active_window).filter(|window| window.id.ne(&0)),
+      open_windows: crate::get_open_windows()?,
+    });
+  }
+  Ok(timeline)
+}
+
+#[derive(Debug)]
+enum Clock {
+  /**
+   * Step changed with `FakeApi::advance`
+   */
+  Manual(Mutex<usize>),
+  /**
+   * Step chosen from the time elapsed since the creation
+   */
+  Realtime(Instant),
+}
+
+#[derive(Debug)]
+struct FakeState {
+  timeline: Timeline,
+  clock: Clock,
+  error: Option<String>,
+}
+
+/**
+ * Backend playing a `Timeline` instead of reading windows of the desktop, to test code using x-win without display.
+ * Clones share the same step.
+ */
+#[derive(Debug, Clone)]
+pub struct FakeApi {
+  state: Arc<FakeState>,
+}
+
+impl FakeApi {
+  /**
+   * Play `timeline` from its first step, next steps are played with `advance`
+   */
+  pub fn new(timeline: Timeline) -> Self {
+    Self::with_clock(timeline, Clock::Manual(Mutex::new(0)))
+  }
+
+  /**
+   * Play `timeline` following the `elapsed` time of its steps
+   */
+  pub fn realtime(timeline: Timeline) -> Self {
+    Self::with_clock(timeline, Clock::Realtime(Instant::now()))
+  }
+
+  /**
+   * Replay in real time a timeline saved in `path`
+   */
+  pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+    Ok(Self::realtime(Timeline::load(path)?))
+  }
+
+  /**
+   * Replay the timeline of `X_WIN_FAKE_TIMELINE` when it is set.
+   * When the file can't be read, every query returns the error.
+   */
+  pub fn from_env() -> Option<Self> {
+    let path = std::env::var_os(FAKE_TIMELINE_ENV)?;
+    Some(Self::replay(&path).unwrap_or_else(|err| Self {
+      state: Arc::new(FakeState {
+        timeline: Timeline::new(),
+        clock: Clock::Manual(Mutex::new(0)),
+        error: Some(format!(
+          "Not possible to read {FAKE_TIMELINE_ENV} \"{}\": {err}",
+          path.to_string_lossy()
+        )),
+      }),
+    }))
+  }
+
+  /**
+   * Go to the next step of a manual timeline, return `false` when the last step is reached
+   */
+  pub fn advance(&self) -> bool {
+    match &self.state.clock {
+      Clock::Manual(step) => match step.lock() {
+        Ok(mut step) if *step + 1 < self.state.timeline.snapshots.len() => {
+          *step += 1;
+          true
+        }
+        _ => false,
+      },
+      Clock::Realtime(_) => false,
+    }
+  }
+
+  /**
+   * State of the desktop at the current step
+   */
+  pub fn current(&self) -> Snapshot {
+    let snapshots = self.state.timeline.snapshots();
+    let step = match &self.state.clock {
+      Clock::Manual(step) => step.lock().map(|step| *step).unwrap_or(0),
+      Clock::Realtime(start) => {
+        let elapsed = start.elapsed().as_millis() as u64;
+        snapshots
+          .iter()
+          .rposition(|snapshot| snapshot.elapsed.le(&elapsed))
+          .unwrap_or(0)
+      }
+    };
+    snapshots.get(step).cloned().unwrap_or_default()
+  }
+
+  fn with_clock(timeline: Timeline, clock: Clock) -> Self {
+    Self {
+      state: Arc::new(FakeState {
+        timeline,
+        clock,
+        error: None,
+      }),
+    }
+  }
+
+  fn check(&self) -> Result<()> {
+    match &self.state.error {
+      Some(err) => Err(err.clone().into()),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Api for FakeApi {
+  fn get_active_window(&self) -> Result<WindowInfo> {
+    self.check()?;
+    Ok(self.current().active_window.unwrap_or_else(empty_entity))
+  }
+
+  fn get_open_windows(&self) -> Result<Vec<WindowInfo>> {
+    self.check()?;
+    Ok(self.current().open_windows)
+  }
+
+  fn get_app_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
+    self.get_app_icon_with(window_info, &IconOptions::default())
+  }
+
+  fn get_app_icon_data(
+    &self,
+    _window_info: &WindowInfo,
+    _options: &IconOptions,
+  ) -> Result<IconData> {
+    self.check()?;
+    Ok(empty_icon_data())
+  }
+
+  fn get_browser_url(&self, _window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+    self.check()?;
+    Ok(None)
+  }
+
+  fn capabilities(&self) -> Capabilities {
+    Capabilities::with_windows(Backend::Fake)
+  }
+
+  fn get_idle_time(&self) -> Result<Duration> {
+    self.check()?;
+    Ok(Duration::ZERO)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{FakeApi, Timeline};
+  use crate::common::{api::empty_entity, api::Api, x_win_struct::window_info::WindowInfo};
+
+  fn window(id: u32, title: &str) -> WindowInfo {
+    let mut window = empty_entity();
+    window.id = id;
+    window.title = title.to_owned();
+    window
+  }
+
+  #[test]
+  fn test_fake_api_timeline() -> Result<(), Box<dyn std::error::Error>> {
+    let fake = FakeApi::new(
+      Timeline::new()
+        .open(window(1, "Terminal"))
+        .focus(1)
+        .open(window(2, "Firefox"))
+        .focus(2)
+        .set_title(2, "x-win - Firefox")
+        .close(2),
+    );
+    assert_eq!(fake.get_active_window()?.id, 0);
+    assert_eq!(fake.get_open_windows()?.len(), 1);
+
+    assert!(fake.advance());
+    assert_eq!(fake.get_active_window()?.title, "Terminal");
+    fake.advance();
+    fake.advance();
+    assert_eq!(fake.get_active_window()?.id, 2);
+    assert_eq!(fake.get_open_windows()?.len(), 2);
+
+    // Clones share the step
+    assert!(fake.clone().advance());
+    assert_eq!(fake.get_active_window()?.title, "x-win - Firefox");
+    assert_eq!(fake.get_open_windows()?[1].title, "x-win - Firefox");
+
+    assert!(fake.advance());
+    assert_eq!(fake.get_active_window()?.id, 0);
+    assert_eq!(fake.get_open_windows()?.len(), 1);
+    assert!(!fake.advance());
+    Ok(())
+  }
+
+  #[test]
+  fn test_fake_api_replay() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("x-win-timeline-{}.jsonl", std::process::id()));
+    let timeline = Timeline::new()
+      .open(window(1, "Terminal"))
+      .focus(1)
+      .wait(Duration::from_secs(3600))
+      .close(1);
+    timeline.save(&path)?;
+    let loaded = Timeline::load(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(loaded, timeline);
+
+    // Last step happens in one hour
+    let fake = FakeApi::realtime(loaded);
+    assert_eq!(fake.get_active_window()?.id, 1);
+    assert!(!fake.advance());
+    Ok(())
+  }
+}
@@ -1,7 +1,5 @@
 #![deny(unused_imports)]
 
-use base64::Engine;
-
 use windows::{
   core::{w, BOOL},
   Win32::{
@@ -19,9 +17,18 @@ use windows::{
 };
 
 use crate::common::{
-  api::{empty_entity, empty_icon, os_name, Api},
+  api::{empty_entity, empty_icon_data, os_name, Api},
+  browser::{browser_info, is_browser},
+  icon::convert_icon_data,
   x_win_struct::{
-    icon_info::IconInfo, process_info::ProcessInfo, usage_info::UsageInfo, window_info::WindowInfo,
+    browser_info::{BrowserInfo, BrowserKind},
+    capabilities::{Backend, Capabilities},
+    icon_data::IconData,
+    icon_info::IconInfo,
+    icon_options::IconOptions,
+    process_info::ProcessInfo,
+    usage_info::UsageInfo,
+    window_info::WindowInfo,
     window_position::WindowPosition,
   },
 };
@@ -29,12 +36,17 @@ use std::{ffi::c_void, os::windows::ffi::OsStrExt};
 use std::{
   ffi::OsStr,
   path::{Path, PathBuf},
+  time::Duration,
 };
 use windows::Win32::{
   Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED},
-  System::{ProcessStatus::GetProcessMemoryInfo, StationsAndDesktops::EnumDesktopWindows},
+  System::{
+    ProcessStatus::GetProcessMemoryInfo, StationsAndDesktops::EnumDesktopWindows,
+    SystemInformation::GetTickCount,
+  },
   UI::{
     Accessibility::CUIAutomation,
+    Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
     WindowsAndMessaging::{
       GetWindowInfo, GetWindowPlacement, IsWindow, IsWindowVisible, SW_SHOWMAXIMIZED, WINDOWINFO,
       WINDOWPLACEMENT, WS_ACTIVECAPTION, WS_CAPTION, WS_CHILD, WS_EX_TOOLWINDOW,
@@ -100,136 +112,180 @@ impl Api for WindowsAPI {
   }
 
   fn get_app_icon(&self, window_info: &WindowInfo) -> crate::common::result::Result<IconInfo> {
-    if !window_info.info.path.is_empty() {
-      let lpszfile: Vec<u16> = std::path::Path::new(&window_info.info.path)
-        .as_os_str()
+    Ok(
+      self
+        .get_app_icon_data(window_info, &IconOptions::default())?
+        .to_icon_info(true),
+    )
+  }
+
+  fn get_app_icon_data(
+    &self,
+    window_info: &WindowInfo,
+    options: &IconOptions,
+  ) -> crate::common::result::Result<IconData> {
+    convert_icon_data(get_app_icon_data(window_info)?, options.format)
+  }
+
+  fn get_browser_url(
+    &self,
+    window_info: &WindowInfo,
+  ) -> crate::common::result::Result<Option<BrowserInfo>> {
+    if window_info.info.exec_name.is_empty() || !is_browser(window_info.info.exec_name.as_str()) {
+      return Ok(None);
+    }
+
+    let mut url: String = String::from("");
+    let hwnd = unsafe {
+      let data: Vec<u16> = OsStr::new(&window_info.title.to_owned())
         .encode_wide()
         .chain(Some(0))
         .collect();
+      let window_title = windows::core::PCWSTR(data.as_ptr());
+      FindWindowW(None, window_title)
+    };
+    if let Ok(hwnd) = hwnd {
+      url = get_browser_url(hwnd, window_info.info.exec_name.clone())?;
+    }
 
-      let mut phiconlarge = HICON::default();
-      let mut phiconsmall = HICON::default();
+    Ok(browser_info(
+      window_info,
+      BrowserKind::from_exec_name(&window_info.info.exec_name),
+      url,
+    ))
+  }
 
-      let value = unsafe {
-        ExtractIconExW(
-          PCWSTR(lpszfile.as_ptr()),
-          0,
-          Some(&mut phiconlarge as *mut HICON),
-          Some(&mut phiconsmall as *mut HICON),
-          1,
-        )
-      };
+  fn capabilities(&self) -> Capabilities {
+    Capabilities {
+      browser_url: true,
+      ..Capabilities::with_windows(Backend::Win32)
+    }
+  }
 
-      if value.ne(&0) && (!phiconlarge.0.is_null() || !phiconsmall.0.is_null()) {
-        let mut piconinfo: ICONINFO = ICONINFO::default();
-        let phicon = {
-          if !phiconlarge.0.is_null() {
-            phiconlarge
-          } else {
-            phiconsmall
-          }
-        };
-        let icon_info = unsafe { GetIconInfo(phicon, &mut piconinfo as *mut ICONINFO as _) };
-        if icon_info.is_ok() {
-          let hbm = piconinfo.hbmColor;
+  fn get_idle_time(&self) -> crate::common::result::Result<Duration> {
+    let mut last_input = LASTINPUTINFO {
+      cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+      dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut last_input) }.as_bool() {
+      return Err("Not possible to get the last input of the user".into());
+    }
+    // Both tick counts wrap around after 49.7 days
+    let idle_time = unsafe { GetTickCount() }.wrapping_sub(last_input.dwTime);
+    Ok(Duration::from_millis(idle_time as u64))
+  }
+}
 
-          let mut cbitmap = BITMAP::default();
+/**
+ * Return the PNG icon of the executable at window_info.info.path
+ */
+fn get_app_icon_data(window_info: &WindowInfo) -> crate::common::result::Result<IconData> {
+  if !window_info.info.path.is_empty() {
+    let lpszfile: Vec<u16> = std::path::Path::new(&window_info.info.path)
+      .as_os_str()
+      .encode_wide()
+      .chain(Some(0))
+      .collect();
+
+    let mut phiconlarge = HICON::default();
+    let mut phiconsmall = HICON::default();
+
+    let value = unsafe {
+      ExtractIconExW(
+        PCWSTR(lpszfile.as_ptr()),
+        0,
+        Some(&mut phiconlarge as *mut HICON),
+        Some(&mut phiconsmall as *mut HICON),
+        1,
+      )
+    };
+
+    if value.ne(&0) && (!phiconlarge.0.is_null() || !phiconsmall.0.is_null()) {
+      let mut piconinfo: ICONINFO = ICONINFO::default();
+      let phicon = {
+        if !phiconlarge.0.is_null() {
+          phiconlarge
+        } else {
+          phiconsmall
+        }
+      };
+      let icon_info = unsafe { GetIconInfo(phicon, &mut piconinfo as *mut ICONINFO as _) };
+      if icon_info.is_ok() {
+        let hbm = piconinfo.hbmColor;
+
+        let mut cbitmap = BITMAP::default();
+
+        let objectw = unsafe {
+          GetObjectW(
+            hbm.into(),
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut cbitmap as *mut _ as _),
+          )
+        };
 
-          let objectw = unsafe {
-            GetObjectW(
-              hbm.into(),
-              std::mem::size_of::<BITMAP>() as i32,
-              Some(&mut cbitmap as *mut _ as _),
+        if objectw > 0 {
+          let mut lpbmi = BITMAPINFO::default();
+          lpbmi.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+          lpbmi.bmiHeader.biWidth = cbitmap.bmWidth;
+          lpbmi.bmiHeader.biHeight = -cbitmap.bmHeight;
+          lpbmi.bmiHeader.biPlanes = 1;
+          lpbmi.bmiHeader.biBitCount = 32;
+          lpbmi.bmiHeader.biCompression = BI_RGB.0;
+
+          let hdc = unsafe { windows::Win32::Graphics::Gdi::CreateCompatibleDC(None) };
+          let mut buffer: Vec<u8> = vec![0u8; (cbitmap.bmHeight * cbitmap.bmWidth * 4) as usize];
+          let height = unsafe {
+            windows::Win32::Graphics::Gdi::GetDIBits(
+              hdc,
+              hbm,
+              0,
+              cbitmap.bmHeight as u32,
+              Some(buffer.as_mut_ptr().cast()),
+              &mut lpbmi,
+              DIB_RGB_COLORS,
             )
           };
 
-          if objectw > 0 {
-            let mut lpbmi = BITMAPINFO::default();
-            lpbmi.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
-            lpbmi.bmiHeader.biWidth = cbitmap.bmWidth;
-            lpbmi.bmiHeader.biHeight = -cbitmap.bmHeight;
-            lpbmi.bmiHeader.biPlanes = 1;
-            lpbmi.bmiHeader.biBitCount = 32;
-            lpbmi.bmiHeader.biCompression = BI_RGB.0;
-
-            let hdc = unsafe { windows::Win32::Graphics::Gdi::CreateCompatibleDC(None) };
-            let mut buffer: Vec<u8> = vec![0u8; (cbitmap.bmHeight * cbitmap.bmWidth * 4) as usize];
-            let height = unsafe {
-              windows::Win32::Graphics::Gdi::GetDIBits(
-                hdc,
-                hbm,
-                0,
-                cbitmap.bmHeight as u32,
-                Some(buffer.as_mut_ptr().cast()),
-                &mut lpbmi,
-                DIB_RGB_COLORS,
-              )
-            };
-
-            let mut data: String = String::new();
-
-            if height.eq(&cbitmap.bmHeight) {
-              //Reverse table to have rgba value from bgra buffer
-              for chunk in buffer.chunks_mut(4) {
-                let [b, _, r, _] = chunk else { unreachable!() };
-                std::mem::swap(b, r);
-              }
-              let mut png_data = Vec::new();
-              {
-                let cursor = std::io::Cursor::new(&mut png_data);
-                let mut encoder =
-                  png::Encoder::new(cursor, cbitmap.bmWidth as u32, cbitmap.bmHeight as u32);
-                encoder.set_color(png::ColorType::Rgba);
-                encoder.set_depth(png::BitDepth::Eight);
-
-                let mut writer = encoder.write_header()?;
-                writer.write_image_data(&buffer)?;
-              }
-              data = base64::prelude::BASE64_STANDARD.encode(png_data);
+          let mut png_data = Vec::new();
+
+          if height.eq(&cbitmap.bmHeight) {
+            //Reverse table to have rgba value from bgra buffer
+            for chunk in buffer.chunks_mut(4) {
+              let [b, _, r, _] = chunk else { unreachable!() };
+              std::mem::swap(b, r);
+            }
+            {
+              let cursor = std::io::Cursor::new(&mut png_data);
+              let mut encoder =
+                png::Encoder::new(cursor, cbitmap.bmWidth as u32, cbitmap.bmHeight as u32);
+              encoder.set_color(png::ColorType::Rgba);
+              encoder.set_depth(png::BitDepth::Eight);
+
+              let mut writer = encoder.write_header()?;
+              writer.write_image_data(&buffer)?;
             }
+          }
 
-            unsafe {
-              let _ = DeleteDC(hdc);
-              let _ = DeleteObject(hbm.into());
-            };
+          unsafe {
+            let _ = DeleteDC(hdc);
+            let _ = DeleteObject(hbm.into());
+          };
 
-            cleanup_hicons(phiconlarge, phiconsmall);
+          cleanup_hicons(phiconlarge, phiconsmall);
 
-            return Ok(IconInfo {
-              data: format!("data:image/png;base64,{data}").to_owned(),
-              height: cbitmap.bmHeight as u32,
-              width: cbitmap.bmWidth as u32,
-            });
-          }
+          return Ok(IconData::new(
+            png_data,
+            String::from("image/png"),
+            cbitmap.bmHeight as u32,
+            cbitmap.bmWidth as u32,
+          ));
         }
-        cleanup_hicons(phiconlarge, phiconsmall);
       }
+      cleanup_hicons(phiconlarge, phiconsmall);
     }
-
-    Ok(empty_icon())
   }
 
-  fn get_browser_url(&self, window_info: &WindowInfo) -> crate::common::result::Result<String> {
-    let mut url: String = String::from("");
-
-    if !window_info.info.exec_name.is_empty() && is_browser(window_info.info.exec_name.as_str()) {
-      let hwnd = unsafe {
-        let data: Vec<u16> = OsStr::new(&window_info.title.to_owned())
-          .encode_wide()
-          .chain(Some(0))
-          .collect();
-        let window_title = windows::core::PCWSTR(data.as_ptr());
-        FindWindowW(None, window_title)
-      };
-      if hwnd.is_ok() {
-        let hwnd = hwnd?;
-        let browser_url = get_browser_url(hwnd, window_info.info.exec_name.clone())?;
-        url = browser_url.clone();
-      }
-    }
-
-    Ok(url)
-  }
+  Ok(empty_icon_data())
 }
 
 /** Functions for callback */
@@ -687,28 +743,6 @@ fn get_url_for_chromium_from_ctrlk(
   Ok(String::from(""))
 }
 
-fn is_browser(browser_name: &str) -> bool {
-  matches!(
-    browser_name,
-    "chrome"
-      | "msedge"
-      | "opera"
-      | "opera_gx"
-      | "brave"
-      | "vivaldi"
-      | "iron"
-      | "epic"
-      | "chromium"
-      | "ucozmedia"
-      | "blisk"
-      | "maxthon"
-      | "beaker"
-      | "beaker browser"
-      | "firefox"
-      | "librewolf"
-  )
-}
-
 fn decode_variant_string(variant: &VARIANT) -> String {
   unsafe {
     match VariantToStringAlloc(variant) {
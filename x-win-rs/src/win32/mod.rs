@@ -2,9 +2,20 @@
 
 mod api;
 
-use crate::common::api::Api;
+use crate::common::{
+  api::Api, error::XWinError, result::Result, x_win_struct::capabilities::Backend,
+};
 use api::WindowsAPI;
 
-pub fn init_platform_api() -> impl Api {
-  WindowsAPI {}
+pub fn init_platform_api() -> Box<dyn Api + Send + Sync> {
+  Box::new(WindowsAPI {})
+}
+
+pub fn init_platform_api_for(backend: &Backend) -> Result<Box<dyn Api + Send + Sync>> {
+  match backend {
+    Backend::Win32 => Ok(init_platform_api()),
+    backend => {
+      Err(XWinError::Unsupported(format!("{backend:?} backend is not available on Windows")).into())
+    }
+  }
 }
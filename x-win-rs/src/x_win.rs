@@ -0,0 +1,507 @@
+#![deny(unused_imports)]
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+  common::{
+    api::Api,
+    icon_cache::{self, IconCache, IconCacheOptions},
+    redaction::{global_redaction, Redaction},
+    result::Result,
+    x_win_struct::{
+      browser_info::BrowserInfo, capabilities::Backend, capabilities::Capabilities,
+      icon_data::IconData, icon_info::IconInfo, icon_options::IconOptions, window_info::WindowInfo,
+    },
+  },
+  init_platform_api, init_platform_api_for,
+};
+
+#[cfg(feature = "testing")]
+use crate::{
+  common::error::XWinError,
+  testing::{FakeApi, FAKE_TIMELINE_ENV},
+};
+
+/**
+ * Instance used by the free functions of x-win
+ */
+static DEFAULT_X_WIN: Lazy<XWin> = Lazy::new(|| XWin {
+  api: default_api(),
+  icon_cache: Mutex::new(IconCache::new(IconCacheOptions::default())),
+  redaction: None,
+});
+
+fn default_api() -> Box<dyn Api + Send + Sync> {
+  #[cfg(feature = "testing")]
+  if let Some(fake) = FakeApi::from_env() {
+    return Box::new(fake);
+  }
+  init_platform_api()
+}
+
+/**
+ * Handle owning the backend of the platform, keeping its connections between calls.
+ * Created with `XWin::builder()`, free functions like `x_win::get_active_window()` use a default instance.
+ */
+pub struct XWin {
+  api: Box<dyn Api + Send + Sync>,
+  icon_cache: Mutex<IconCache>,
+  /**
+   * Rules of the builder, the ones of `set_redaction_rules` are used without them
+   */
+  redaction: Option<Arc<Redaction>>,
+}
+
+impl XWin {
+  pub fn builder() -> XWinBuilder {
+    XWinBuilder::default()
+  }
+
+  /**
+   * Instance detecting the backend from the session, shared by the free functions
+   */
+  pub(crate) fn shared() -> &'static XWin {
+    &DEFAULT_X_WIN
+  }
+
+  /**
+   * Retrieve information the about currently active window.
+   * Return `WindowInfo` containing details about a specific active window.
+   */
+  pub fn get_active_window(&self) -> Result<WindowInfo> {
+    let window = self.api.get_active_window()?;
+    Ok(match self.redaction() {
+      Some(redaction) => redaction.redact_active_window(window),
+      None => window,
+    })
+  }
+
+  /**
+   * Retrieve information about the currently open windows.
+   * Return `Vec<WindowInfo>` each containing details about a specific open window.
+   */
+  pub fn get_open_windows(&self) -> Result<Vec<WindowInfo>> {
+    let windows = self.api.get_open_windows()?;
+    Ok(match self.redaction() {
+      Some(redaction) => redaction.redact_windows(windows),
+      None => windows,
+    })
+  }
+
+  /**
+   * Recover icon of window.
+   * Return `IconInfo`
+   */
+  pub fn get_window_icon(&self, window_info: &WindowInfo) -> Result<IconInfo> {
+    self.get_window_icon_with(window_info, IconOptions::default())
+  }
+
+  /**
+   * Recover icon of window with a preferred size and output format.
+   * Return `IconInfo`
+   */
+  pub fn get_window_icon_with(
+    &self,
+    window_info: &WindowInfo,
+    options: IconOptions,
+  ) -> Result<IconInfo> {
+    let icon_data = self.get_window_icon_data(window_info, options.clone())?;
+    Ok(icon_data.to_icon_info(options.as_data_uri))
+  }
+
+  /**
+   * Recover raw bytes of the icon of window with a preferred size and output format.
+   * Return `IconData`
+   */
+  pub fn get_window_icon_data(
+    &self,
+    window_info: &WindowInfo,
+    options: IconOptions,
+  ) -> Result<IconData> {
+    icon_cache::get_icon_data(&self.icon_cache, self.api.as_ref(), window_info, &options)
+  }
+
+  /**
+   * Change options of the icon cache of this instance, least recently used icons are dropped if capacity is reduced
+   */
+  pub fn set_icon_cache_options(&self, options: IconCacheOptions) -> Result<()> {
+    self
+      .icon_cache
+      .lock()
+      .map_err(|err| err.to_string())?
+      .set_options(options);
+    Ok(())
+  }
+
+  /**
+   * Remove all icons from the icon cache of this instance and from its cache directory
+   */
+  pub fn clear_icon_cache(&self) -> Result<()> {
+    self
+      .icon_cache
+      .lock()
+      .map_err(|err| err.to_string())?
+      .clear()
+  }
+
+  /**
+   * Recover browser url of window.
+   * Return `Some(BrowserInfo)` when the window is a browser and its url has been recovered, `None` otherwise.
+   */
+  pub fn get_browser_url(&self, window_info: &WindowInfo) -> Result<Option<BrowserInfo>> {
+    match self.redaction() {
+      Some(redaction) => {
+        // Redacted windows are looked up with their original title
+        let original = redaction.original(window_info);
+        let browser = self
+          .api
+          .get_browser_url(original.as_ref().unwrap_or(window_info))?;
+        Ok(browser.and_then(|browser| redaction.redact_browser(window_info, browser)))
+      }
+      None => self.api.get_browser_url(window_info),
+    }
+  }
+
+  /**
+   * Return the time since the last keyboard or mouse input of the user.
+   */
+  pub fn get_idle_time(&self) -> Result<Duration> {
+    self.api.get_idle_time()
+  }
+
+  /**
+   * Return the features available with the backend of this instance.
+   */
+  pub fn capabilities(&self) -> Capabilities {
+    self.api.capabilities()
+  }
+
+  /**
+   * Backend of this instance, windows it returns are not redacted
+   */
+  #[cfg(all(feature = "async", not(target_os = "linux")))]
+  pub(crate) fn api(&self) -> &(dyn Api + Send + Sync) {
+    self.api.as_ref()
+  }
+
+  fn redaction(&self) -> Option<Arc<Redaction>> {
+    self.redaction.clone().or_else(global_redaction)
+  }
+}
+
+/**
+ * Options of a `XWin` instance
+ */
+#[derive(Debug, Clone, Default)]
+pub struct XWinBuilder {
+  backend: Option<Backend>,
+  icon_cache: IconCacheOptions,
+  redaction: Option<Redaction>,
+  #[cfg(feature = "testing")]
+  fake: Option<FakeApi>,
+}
+
+impl XWinBuilder {
+  /**
+   * Use `backend` instead of detecting it from the session on each call.
+   * `build` returns an `XWinError::Unsupported` error when the backend does not exist on this platform.
+   */
+  pub fn backend(mut self, backend: Backend) -> Self {
+    self.backend = Some(backend);
+    self
+  }
+
+  /**
+   * Keep icons in the icon cache of the instance, enabled by default
+   */
+  pub fn cache_icons(mut self, cache_icons: bool) -> Self {
+    self.icon_cache.enabled = cache_icons;
+    self
+  }
+
+  /**
+   * Options of the icon cache owned by the instance, not shared with other instances
+   */
+  pub fn icon_cache_options(mut self, options: IconCacheOptions) -> Self {
+    self.icon_cache = options;
+    self
+  }
+
+  /**
+   * Redact windows and browser urls of this instance with `redaction` instead of the rules of `set_redaction_rules`
+   */
+  pub fn redaction(mut self, redaction: Redaction) -> Self {
+    self.redaction = Some(redaction);
+    self
+  }
+
+  /**
+   * Play the timeline of `fake` instead of reading windows of the desktop, same as `backend(Backend::Fake)`
+   */
+  #[cfg(feature = "testing")]
+  pub fn fake(mut self, fake: FakeApi) -> Self {
+    self.backend = Some(Backend::Fake);
+    self.fake = Some(fake);
+    self
+  }
+
+  pub fn build(self) -> Result<XWin> {
+    let api = match &self.backend {
+      #[cfg(feature = "testing")]
+      Some(Backend::Fake) => fake_api(self.fake.clone())?,
+      Some(backend) => init_platform_api_for(backend)?,
+      None => init_platform_api(),
+    };
+    Ok(XWin {
+      api,
+      icon_cache: Mutex::new(IconCache::new(self.icon_cache)),
+      redaction: self.redaction.map(Arc::new),
+    })
+  }
+}
+
+/**
+ * Fake given to the builder or replaying `X_WIN_FAKE_TIMELINE`
+ */
+#[cfg(feature = "testing")]
+fn fake_api(fake: Option<FakeApi>) -> Result<Box<dyn Api + Send + Sync>> {
+  match fake.or_else(FakeApi::from_env) {
+    Some(fake) => Ok(Box::new(fake)),
+    None => Err(
+      XWinError::Unsupported(format!(
+        "Fake backend needs a timeline from XWinBuilder::fake() or {FAKE_TIMELINE_ENV}"
+      ))
+      .into(),
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc, Mutex,
+  };
+
+  use super::XWin;
+  use crate::common::{
+    api::{empty_entity, empty_icon_data, Api},
+    error::XWinError,
+    icon_cache::{IconCache, IconCacheOptions},
+    redaction::{Redaction, RedactionAction, RedactionRule, RuleMatch},
+    result::Result as XWinResult,
+    x_win_struct::{
+      browser_info::{BrowserInfo, BrowserKind},
+      capabilities::Backend,
+      icon_data::IconData,
+      icon_info::IconInfo,
+      icon_options::IconOptions,
+      window_info::WindowInfo,
+    },
+  };
+
+  #[test]
+  fn test_builder_backend() {
+    #[cfg(target_os = "linux")]
+    let (available, unavailable) = (Backend::X11, Backend::Win32);
+    #[cfg(target_os = "windows")]
+    let (available, unavailable) = (Backend::Win32, Backend::X11);
+    #[cfg(target_os = "macos")]
+    let (available, unavailable) = (Backend::MacOS, Backend::X11);
+
+    assert!(XWin::builder().backend(available).build().is_ok());
+    let err = XWin::builder()
+      .backend(unavailable)
+      .cache_icons(false)
+      .build()
+      .err()
+      .expect("backend of another platform accepted");
+    assert!(matches!(
+      err.downcast_ref::<XWinError>(),
+      Some(XWinError::Unsupported(_))
+    ));
+  }
+
+  #[cfg(feature = "testing")]
+  #[test]
+  fn test_builder_fake() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::testing::{FakeApi, Timeline};
+
+    let mut window = crate::common::api::empty_entity();
+    window.id = 1;
+    let fake = FakeApi::new(Timeline::new().open(window).focus(1));
+    let x_win = XWin::builder().fake(fake.clone()).build()?;
+    assert_eq!(x_win.capabilities().backend, Backend::Fake);
+    assert_eq!(x_win.get_open_windows()?.len(), 1);
+    assert_eq!(x_win.get_active_window()?.id, 0);
+    fake.advance();
+    assert_eq!(x_win.get_active_window()?.id, 1);
+    Ok(())
+  }
+
+  #[cfg(feature = "testing")]
+  #[test]
+  fn test_builder_redaction() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::{
+      common::redaction::{Redaction, RedactionAction, RedactionRule, RuleMatch},
+      testing::{FakeApi, Timeline},
+    };
+
+    let mut mail = crate::common::api::empty_entity();
+    mail.id = 1;
+    mail.info.name = String::from("Thunderbird");
+    mail.title = String::from("Inbox - Mail");
+    let mut vault = mail.clone();
+    vault.id = 2;
+    vault.info.name = String::from("KeePassXC");
+    let fake = FakeApi::new(Timeline::new().open(mail).open(vault).focus(2));
+    fake.advance();
+    let redaction = Redaction::new(vec![
+      RedactionRule::new(
+        RuleMatch::App(String::from("keepassxc")),
+        RedactionAction::DropWindow,
+      ),
+      RedactionRule::new(RuleMatch::Any, RedactionAction::TruncateTitle(5)),
+    ])?;
+    let x_win = XWin::builder().fake(fake).redaction(redaction).build()?;
+    let open_windows = x_win.get_open_windows()?;
+    assert_eq!(open_windows.len(), 1);
+    assert_eq!(open_windows[0].title, "Inbox");
+    assert_eq!(x_win.get_active_window()?.id, 0);
+    Ok(())
+  }
+
+  /**
+   * Backend returning a new icon on each call
+   */
+  struct CountingApi(AtomicU8);
+
+  impl Api for CountingApi {
+    fn get_active_window(&self) -> XWinResult<WindowInfo> {
+      Ok(empty_entity())
+    }
+
+    fn get_open_windows(&self) -> XWinResult<Vec<WindowInfo>> {
+      Ok(vec![])
+    }
+
+    fn get_app_icon(&self, _window_info: &WindowInfo) -> XWinResult<IconInfo> {
+      Ok(empty_icon_data().to_icon_info(true))
+    }
+
+    fn get_app_icon_data(
+      &self,
+      _window_info: &WindowInfo,
+      _options: &IconOptions,
+    ) -> XWinResult<IconData> {
+      let count = self.0.fetch_add(1, Ordering::SeqCst);
+      Ok(IconData::new(vec![count], String::from("image/png"), 1, 1))
+    }
+
+    fn get_browser_url(&self, _window_info: &WindowInfo) -> XWinResult<Option<BrowserInfo>> {
+      Ok(None)
+    }
+  }
+
+  #[test]
+  fn test_icon_cache_per_instance() -> Result<(), Box<dyn std::error::Error>> {
+    let x_win = || XWin {
+      api: Box::new(CountingApi(AtomicU8::new(0))),
+      icon_cache: Mutex::new(IconCache::new(IconCacheOptions::new(true, 1, None))),
+      redaction: None,
+    };
+    let icon_data = |x_win: &XWin, path: &str| {
+      let mut window = empty_entity();
+      window.info.path = path.to_owned();
+      x_win
+        .get_window_icon_data(&window, IconOptions::default())
+        .map(|icon_data| icon_data.data)
+    };
+    let first = x_win();
+    let second = x_win();
+
+    assert_eq!(icon_data(&first, "/a")?, vec![0]);
+    // Icons of another instance do not evict the ones of the first instance
+    assert_eq!(icon_data(&second, "/b")?, vec![0]);
+    assert_eq!(icon_data(&first, "/a")?, vec![0]);
+    assert_eq!(icon_data(&second, "/a")?, vec![1]);
+
+    first.clear_icon_cache()?;
+    assert_eq!(icon_data(&first, "/a")?, vec![1]);
+    Ok(())
+  }
+
+  /**
+   * Backend of one browser window, its url is found from the id like on Windows and macOS
+   */
+  struct BrowserApi(AtomicU8);
+
+  impl Api for BrowserApi {
+    fn get_active_window(&self) -> XWinResult<WindowInfo> {
+      let mut window = empty_entity();
+      window.id = 1;
+      window.title = String::from("Inbox — Mozilla Firefox");
+      window.usage.memory = self.0.fetch_add(1, Ordering::SeqCst) as u32;
+      Ok(window)
+    }
+
+    fn get_open_windows(&self) -> XWinResult<Vec<WindowInfo>> {
+      Ok(vec![self.get_active_window()?])
+    }
+
+    fn get_app_icon(&self, _window_info: &WindowInfo) -> XWinResult<IconInfo> {
+      Ok(empty_icon_data().to_icon_info(true))
+    }
+
+    fn get_app_icon_data(
+      &self,
+      _window_info: &WindowInfo,
+      _options: &IconOptions,
+    ) -> XWinResult<IconData> {
+      Ok(empty_icon_data())
+    }
+
+    fn get_browser_url(&self, window_info: &WindowInfo) -> XWinResult<Option<BrowserInfo>> {
+      Ok((window_info.id == 1).then(|| {
+        BrowserInfo::new(
+          String::from("https://mail.example.com/u/0/inbox"),
+          BrowserKind::Firefox,
+          false,
+          String::from("Inbox"),
+        )
+      }))
+    }
+  }
+
+  #[test]
+  fn test_browser_url_of_redacted_window() -> Result<(), Box<dyn std::error::Error>> {
+    let x_win = XWin {
+      api: Box::new(BrowserApi(AtomicU8::new(0))),
+      icon_cache: Mutex::new(IconCache::new(IconCacheOptions::default())),
+      redaction: Some(Arc::new(Redaction::new(vec![
+        RedactionRule::new(
+          RuleMatch::Title(String::from("Inbox")),
+          RedactionAction::HashTitle,
+        ),
+        RedactionRule::new(
+          RuleMatch::Title(String::from("Inbox")),
+          RedactionAction::StripUrlPath,
+        ),
+      ])?)),
+    };
+    let listed = x_win.get_open_windows()?.pop().ok_or("no open window")?;
+    let active = x_win.get_active_window()?;
+    assert_ne!(listed.usage.memory, active.usage.memory);
+    assert_eq!(listed.title, active.title);
+    assert_eq!(
+      x_win.get_browser_url(&listed)?.map(|browser| browser.url),
+      Some(String::from("https://mail.example.com"))
+    );
+    Ok(())
+  }
+}